@@ -0,0 +1,96 @@
+use crate::{Failure, Repo};
+
+/// options for `Repo::apply`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplyOptions {
+    /// only check the patch applies, without changing anything (`--check`)
+    pub check: bool,
+
+    /// fall back to a three-way merge when the patch does not apply cleanly (`--3way`)
+    pub three_way: bool,
+
+    /// apply the patch in reverse (`--reverse`)
+    pub reverse: bool,
+}
+
+impl Repo {
+    /// Applies a unified diff to the working tree, passing it to `git apply` on stdin
+    /// 
+    /// Malformed patches and conflicts fail with git's message as stderr
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{ApplyOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let patch = std::fs::read_to_string("fix.patch").unwrap();
+    /// let check = ApplyOptions { check: true, ..Default::default() };
+    /// if repo.apply(&patch, check).is_ok() {
+    ///     repo.apply(&patch, ApplyOptions::default()).unwrap();
+    /// }
+    /// ```
+    pub fn apply(&self, patch: &str, opts: ApplyOptions) -> Result<(), Failure> {
+        let mut cmd = self.git(vec!["apply"]);
+        if opts.check {
+            cmd = cmd.arg("--check");
+        }
+        if opts.three_way {
+            cmd = cmd.arg("--3way");
+        }
+        if opts.reverse {
+            cmd = cmd.arg("--reverse");
+        }
+
+        cmd.arg("-").stdin(patch).run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::fs;
+
+    const PATCH: &str = "diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ one
+-two
++three
+";
+
+    #[test]
+    fn test_apply() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\ntwo\n");
+        fixture.commit_all("initial");
+        let path = fixture.repo.path().join("a.txt");
+
+        fixture.repo.apply(PATCH, ApplyOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\nthree\n");
+
+        let reverse = ApplyOptions {
+            reverse: true,
+            ..Default::default()
+        };
+        fixture.repo.apply(PATCH, reverse).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_apply_check_invalid() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "something else\n");
+        fixture.commit_all("initial");
+
+        let check = ApplyOptions {
+            check: true,
+            ..Default::default()
+        };
+        let failure = fixture.repo.apply(PATCH, check.clone()).unwrap_err();
+        assert!(failure.stderr.unwrap().contains("patch does not apply"));
+
+        let garbage = fixture.repo.apply("not a patch\n", check).unwrap_err();
+        assert_ne!(garbage.code, 0);
+    }
+}
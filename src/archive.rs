@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+use std::process::{Output, Stdio};
+
+use crate::{read_to_end, Failure, FailureKind, Repo};
+
+/// the kind of file `Repo::archive` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveFormat {
+    Tar,
+    /// a gzipped tarball
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "--format=tar",
+            ArchiveFormat::TarGz => "--format=tar.gz",
+            ArchiveFormat::Zip => "--format=zip",
+        }
+    }
+}
+
+impl Repo {
+    /// Writes the tree at `rev` to `out` as an archive, using `archive`
+    /// 
+    /// The archive is copied to `out` as git produces it, rather than held in memory. `rev` is checked first, so a
+    /// bad revision fails before anything is written - a failure part way through may still leave `out` with a
+    /// partial archive. Like `Git::stream`, this always runs the real git
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use rsgit::{ArchiveFormat, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let file = File::create("release.tar.gz").unwrap();
+    /// repo.archive("v1.0", ArchiveFormat::TarGz, file).unwrap();
+    /// ```
+    pub fn archive<W: Write>(&self, rev: &str, format: ArchiveFormat, mut out: W) -> Result<(), Failure> {
+        // an archive of the commit, rather than of this tree, keeps its date and SHA
+        self.rev_parse(&format!("{}^{{tree}}", rev))?;
+        let mut cmd = self.git(vec!["archive", format.as_arg(), rev]).command();
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        let io_failure = |err: io::Error| Failure::new(FailureKind::Io, format!("failed to archive: {}", err));
+        let mut child = cmd.spawn().map_err(io_failure)?;
+        let stderr = child.stderr.take().map(read_to_end);
+        let copied = match child.stdout.as_mut() {
+            Some(stdout) => io::copy(stdout, &mut out).and_then(|_| out.flush()),
+            None => Ok(()),
+        };
+        if copied.is_err() {
+            // git would otherwise block on a full pipe which nothing is reading
+            let _ = child.kill();
+        }
+        let status = child.wait().map_err(io_failure)?;
+        copied.map_err(io_failure)?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            let stderr = stderr.and_then(|x| x.join().ok()).unwrap_or_default();
+            Err(Failure::from_output(Output {
+                status,
+                stdout: Vec::new(),
+                stderr,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_archive() {
+        let fixture = Fixture::new();
+        fixture.write("hello.txt", "hello\n");
+        fixture.commit_all("initial");
+
+        let mut tar = Vec::new();
+        fixture.repo.archive("HEAD", ArchiveFormat::Tar, &mut tar).unwrap();
+        assert_eq!(&tar[257..262], b"ustar");
+        // the commit's SHA, in the global pax header, then the file
+        assert!(tar.starts_with(b"pax_global_header\0"));
+        assert!(tar.windows(10).any(|x| x == b"hello.txt\0"));
+
+        let mut gz = Vec::new();
+        fixture.repo.archive("HEAD", ArchiveFormat::TarGz, &mut gz).unwrap();
+        assert!(gz.starts_with(&[0x1f, 0x8b]));
+        let mut zip = Vec::new();
+        fixture.repo.archive("HEAD", ArchiveFormat::Zip, &mut zip).unwrap();
+        assert!(zip.starts_with(b"PK\x03\x04"));
+
+        let mut nothing = Vec::new();
+        assert!(fixture.repo.archive("no-such-rev", ArchiveFormat::Tar, &mut nothing).is_err());
+        assert!(nothing.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::repo::{path_bytes, path_from_bytes};
+use crate::{Failure, Repo};
+
+/// the state of a gitattribute for a path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttributeValue {
+    /// `attr`
+    Set,
+    /// `-attr`
+    Unset,
+    /// not mentioned by any pattern, or reset with `!attr`
+    Unspecified,
+    /// `attr=value`
+    Value(String),
+}
+
+impl AttributeValue {
+    fn parse(info: &str) -> AttributeValue {
+        match info {
+            "set" => AttributeValue::Set,
+            "unset" => AttributeValue::Unset,
+            "unspecified" => AttributeValue::Unspecified,
+            value => AttributeValue::Value(value.to_string()),
+        }
+    }
+}
+
+/// one attribute of one path - see `Repo::check_attr`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeResult {
+    pub path: PathBuf,
+    pub attribute: String,
+    pub value: AttributeValue,
+}
+
+impl Repo {
+    /// Looks up `attr` for each of `paths`, using `check-attr --stdin -z`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{AttributeValue, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// for result in repo.check_attr("diff", &["image.png"]).unwrap() {
+    ///     if result.value == AttributeValue::Unset {
+    ///         println!("{} is treated as binary", result.path.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn check_attr<P: AsRef<Path>>(
+        &self,
+        attr: &str,
+        paths: &[P],
+    ) -> Result<Vec<AttributeResult>, Failure> {
+        self.check_attr_raw(&[attr], paths)
+    }
+
+    /// Looks up several attributes at once, grouped by path then attribute name
+    pub fn check_attrs<P: AsRef<Path>>(
+        &self,
+        attrs: &[&str],
+        paths: &[P],
+    ) -> Result<HashMap<PathBuf, HashMap<String, AttributeValue>>, Failure> {
+        let mut grouped: HashMap<PathBuf, HashMap<String, AttributeValue>> = HashMap::new();
+        for result in self.check_attr_raw(attrs, paths)? {
+            grouped
+                .entry(result.path)
+                .or_default()
+                .insert(result.attribute, result.value);
+        }
+        Ok(grouped)
+    }
+
+    fn check_attr_raw<P: AsRef<Path>>(
+        &self,
+        attrs: &[&str],
+        paths: &[P],
+    ) -> Result<Vec<AttributeResult>, Failure> {
+        if attrs.is_empty() || paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // raw bytes both ways, so paths which aren't UTF-8 survive
+        let mut input = Vec::new();
+        for path in paths {
+            input.extend(path_bytes(path.as_ref())?);
+            input.push(b'\0');
+        }
+
+        let stdout = self
+            .git(vec!["check-attr", "--stdin", "-z"])
+            .args(attrs)
+            .arg("--")
+            .stdin(input)
+            .run_bytes()?;
+
+        // `<path>\0<attribute>\0<info>\0`
+        let fields: Vec<&[u8]> = stdout.split(|x| *x == b'\0').collect();
+        Ok(fields
+            .chunks(3)
+            .filter(|chunk| chunk.len() == 3)
+            .map(|chunk| AttributeResult {
+                path: path_from_bytes(chunk[0].to_vec()),
+                attribute: String::from_utf8_lossy(chunk[1]).into_owned(),
+                value: AttributeValue::parse(&String::from_utf8_lossy(chunk[2])),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_check_attr() {
+        let fixture = Fixture::new();
+        fixture.write(
+            ".gitattributes",
+            "*.png binary\n*.rs diff=rust text\n*.txt -text\n",
+        );
+
+        let results = fixture
+            .repo
+            .check_attr("diff", &["logo.png", "src/lib.rs", "notes.md"])
+            .unwrap();
+        assert_eq!(
+            results,
+            vec![
+                AttributeResult {
+                    path: PathBuf::from("logo.png"),
+                    attribute: "diff".to_string(),
+                    value: AttributeValue::Unset,
+                },
+                AttributeResult {
+                    path: PathBuf::from("src/lib.rs"),
+                    attribute: "diff".to_string(),
+                    value: AttributeValue::Value("rust".to_string()),
+                },
+                AttributeResult {
+                    path: PathBuf::from("notes.md"),
+                    attribute: "diff".to_string(),
+                    value: AttributeValue::Unspecified,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_attrs() {
+        let fixture = Fixture::new();
+        fixture.write(".gitattributes", "*.rs diff=rust text\n*.txt -text\n");
+
+        let results = fixture
+            .repo
+            .check_attrs(&["text", "diff"], &["main.rs", "a.txt"])
+            .unwrap();
+        let rust = &results[&PathBuf::from("main.rs")];
+        assert_eq!(rust["text"], AttributeValue::Set);
+        assert_eq!(rust["diff"], AttributeValue::Value("rust".to_string()));
+        let text = &results[&PathBuf::from("a.txt")];
+        assert_eq!(text["text"], AttributeValue::Unset);
+        assert_eq!(text["diff"], AttributeValue::Unspecified);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_attr_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fixture = Fixture::new();
+        fixture.write(".gitattributes", "*.png binary\n");
+        let path = Path::new(OsStr::from_bytes(b"caf\xe9.png"));
+        let results = fixture.repo.check_attr("diff", &[path]).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, path);
+        assert_eq!(results[0].value, AttributeValue::Unset);
+    }
+}
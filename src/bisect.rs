@@ -0,0 +1,171 @@
+use crate::{Failure, Repo};
+
+/// where a bisect session has got to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BisectState {
+    /// `sha` is checked out and waiting to be marked good or bad
+    Testing {
+        sha: String,
+        /// revisions still to test after this one
+        revisions_left: u32,
+        /// git's estimate of the steps remaining
+        steps: u32,
+    },
+    /// the session is over, and `sha` is the first bad commit
+    Found { sha: String },
+}
+
+impl Repo {
+    /// Starts bisecting between the known `bad` and `good` revisions, checking out the first candidate
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{BisectState, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let mut state = repo.bisect_start("HEAD", "v1.0").unwrap();
+    /// while let BisectState::Testing { .. } = state {
+    ///     let works = std::process::Command::new("make").arg("test").status().unwrap().success();
+    ///     state = if works { repo.bisect_good() } else { repo.bisect_bad() }.unwrap();
+    /// }
+    /// repo.bisect_reset().unwrap();
+    /// ```
+    pub fn bisect_start(&self, bad: &str, good: &str) -> Result<BisectState, Failure> {
+        let output = self.git(vec!["bisect", "start", bad, good, "--"]).run()?;
+        parse_bisect(&output.stdout.unwrap_or_default())
+    }
+
+    /// Marks the checked out candidate as good
+    pub fn bisect_good(&self) -> Result<BisectState, Failure> {
+        let output = self.git(vec!["bisect", "good"]).run()?;
+        parse_bisect(&output.stdout.unwrap_or_default())
+    }
+
+    /// Marks the checked out candidate as bad
+    pub fn bisect_bad(&self) -> Result<BisectState, Failure> {
+        let output = self.git(vec!["bisect", "bad"]).run()?;
+        parse_bisect(&output.stdout.unwrap_or_default())
+    }
+
+    /// Bisects between `bad` and `good` automatically, running `command` on each candidate
+    /// 
+    /// `command` exiting 0 means good, 125 means skip, and anything else up to 127 means bad - see
+    /// `git help bisect`. The session is left open, so call `bisect_reset` afterwards
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{BisectState, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let state = repo.bisect_run("HEAD", "v1.0", &["cargo", "test", "--quiet"]).unwrap();
+    /// if let BisectState::Found { sha } = state {
+    ///     println!("broken by {}", sha);
+    /// }
+    /// repo.bisect_reset().unwrap();
+    /// ```
+    pub fn bisect_run(
+        &self,
+        bad: &str,
+        good: &str,
+        command: &[&str],
+    ) -> Result<BisectState, Failure> {
+        self.bisect_start(bad, good)?;
+        let output = self.git(vec!["bisect", "run"]).args(command).run()?;
+        parse_bisect(&output.stdout.unwrap_or_default())
+    }
+
+    /// Ends the bisect session, checking out the branch it started from
+    pub fn bisect_reset(&self) -> Result<(), Failure> {
+        self.git(vec!["bisect", "reset"]).run()?;
+        Ok(())
+    }
+}
+
+/// reads the last state git reported - `bisect run` prints one per step
+fn parse_bisect(stdout: &str) -> Result<BisectState, Failure> {
+    let mut state = None;
+    let mut lines = stdout.lines();
+    while let Some(line) = lines.next() {
+        if let Some(sha) = line.strip_suffix(" is the first bad commit") {
+            return Ok(BisectState::Found {
+                sha: sha.to_string(),
+            });
+        }
+
+        // `Bisecting: 3 revisions left to test after this (roughly 2 steps)`, then `[<sha>] <subject>`
+        let Some(rest) = line.strip_prefix("Bisecting: ") else {
+            continue;
+        };
+        let numbers: Vec<u32> = rest
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|x| x.parse().ok())
+            .collect();
+        let sha = lines
+            .next()
+            .and_then(|x| x.strip_prefix('['))
+            .and_then(|x| x.split(']').next());
+        if let ([revisions_left, steps], Some(sha)) = (numbers.as_slice(), sha) {
+            state = Some(BisectState::Testing {
+                sha: sha.to_string(),
+                revisions_left: *revisions_left,
+                steps: *steps,
+            });
+        }
+    }
+    state.ok_or_else(|| Failure::parse(format!("unexpected bisect output: {:?}", stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// eight commits writing 1 to 8 into `n`, where 5 and up count as broken
+    fn history(fixture: &Fixture) -> Vec<String> {
+        (1..=8)
+            .map(|i| {
+                fixture.write("n", &i.to_string());
+                fixture.commit_all(&format!("commit {}", i))
+            })
+            .collect()
+    }
+
+    fn current(fixture: &Fixture) -> u32 {
+        let output = fixture.repo.git(vec!["show", "HEAD:n"]).run().unwrap();
+        output.stdout.unwrap().trim().parse().unwrap()
+    }
+
+    #[test]
+    fn test_bisect_manual() {
+        let fixture = Fixture::new();
+        let shas = history(&fixture);
+
+        let mut state = fixture.repo.bisect_start("HEAD", &shas[0]).unwrap();
+        assert!(matches!(state, BisectState::Testing { revisions_left: 3, steps: 2, .. }));
+        let mut tested = 0;
+        while let BisectState::Testing { sha, .. } = &state {
+            assert_eq!(*sha, fixture.repo.rev_parse("HEAD").unwrap());
+            tested += 1;
+            state = if current(&fixture) < 5 {
+                fixture.repo.bisect_good()
+            } else {
+                fixture.repo.bisect_bad()
+            }
+            .unwrap();
+        }
+        assert!(tested <= 3);
+        assert_eq!(state, BisectState::Found { sha: shas[4].clone() });
+
+        fixture.repo.bisect_reset().unwrap();
+        assert_eq!(fixture.repo.symbolic_ref_get("HEAD").unwrap(), "refs/heads/master");
+    }
+
+    #[test]
+    fn test_bisect_run() {
+        let fixture = Fixture::new();
+        let shas = history(&fixture);
+
+        let state = fixture
+            .repo
+            .bisect_run("HEAD", &shas[0], &["sh", "-c", "test $(cat n) -lt 5"])
+            .unwrap();
+        assert_eq!(state, BisectState::Found { sha: shas[4].clone() });
+        fixture.repo.bisect_reset().unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{Failure, FailureKind, Repo};
+
+/// options for `Repo::branch_create`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchOptions {
+    /// reset the branch if it already exists (`--force`)
+    pub force: bool,
+
+    /// set this branch, eg `origin/main`, as the upstream
+    pub track: Option<String>,
+}
+
+impl Repo {
+    /// Creates the branch `name` at `start_point`, or at `HEAD` if `None`
+    /// 
+    /// Invalid names fail with `FailureKind::InvalidRefName` before git is asked to create anything
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{BranchOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = BranchOptions { track: Some("origin/main".to_string()), ..Default::default() };
+    /// repo.branch_create("feature", Some("origin/main"), opts).unwrap();
+    /// ```
+    pub fn branch_create(
+        &self,
+        name: &str,
+        start_point: Option<&str>,
+        opts: BranchOptions,
+    ) -> Result<(), Failure> {
+        self.check_branch_name(name)?;
+
+        let mut cmd = self.git(vec!["branch", "--no-track"]);
+        if opts.force {
+            cmd = cmd.arg("--force");
+        }
+        cmd = cmd.args(vec!["--", name]);
+        if let Some(start_point) = start_point {
+            cmd = cmd.arg(start_point);
+        }
+        cmd.run()?;
+
+        if let Some(upstream) = &opts.track {
+            self.branch_set_upstream(name, upstream)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the branch `name`
+    /// 
+    /// Without `force`, a branch with unmerged commits fails with `FailureKind::NotFullyMerged`. Deleting the checked out
+    /// branch always fails, with `FailureKind::BranchCheckedOut`
+    pub fn branch_delete(&self, name: &str, force: bool) -> Result<(), Failure> {
+        let flag = if force { "-D" } else { "-d" };
+        self.git(vec!["branch", flag, "--", name])
+            .run()
+            .map_err(|failure| classify_branch(failure, name))?;
+        Ok(())
+    }
+
+    /// Renames the branch `old` to `new`, keeping its upstream
+    /// 
+    /// With `force`, an existing branch called `new` is overwritten
+    pub fn branch_rename(&self, old: &str, new: &str, force: bool) -> Result<(), Failure> {
+        self.check_branch_name(new)?;
+        let flag = if force { "-M" } else { "-m" };
+        self.git(vec!["branch", flag, "--", old, new]).run()?;
+        Ok(())
+    }
+
+    /// Sets the upstream of `name`, eg to `origin/main`
+    pub fn branch_set_upstream(&self, name: &str, upstream: &str) -> Result<(), Failure> {
+        self.git(vec!["branch", &format!("--set-upstream-to={}", upstream), "--", name])
+            .run()?;
+        Ok(())
+    }
+
+    /// Removes the upstream of `name`
+    pub fn branch_unset_upstream(&self, name: &str) -> Result<(), Failure> {
+        self.git(vec!["branch", "--unset-upstream", "--", name]).run()?;
+        Ok(())
+    }
+
+    /// The branches whose history contains `rev`, using `for-each-ref --contains` - such as the release branches a
+    /// fix has reached
+    /// 
+    /// Names are short, eg `main`, and remote branches such as `origin/main` are included with `include_remotes`.
+    /// `rev` is resolved first, so a typo fails rather than matching nothing
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let released = repo.branches_containing("abc1234", true).unwrap();
+    /// println!("the fix is on {}", released.join(", "));
+    /// ```
+    pub fn branches_containing(&self, rev: &str, include_remotes: bool) -> Result<Vec<String>, Failure> {
+        self.branches_filtered("--contains", rev, include_remotes)
+    }
+
+    /// The branches whose history is entirely in `rev`, using `for-each-ref --merged` - such as the feature branches
+    /// which can be deleted
+    /// 
+    /// A branch pointing at `rev` itself is included. Names are as for `branches_containing`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for branch in repo.branches_merged_into("main", false).unwrap() {
+    ///     if branch != "main" {
+    ///         repo.branch_delete(&branch, false).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn branches_merged_into(&self, rev: &str, include_remotes: bool) -> Result<Vec<String>, Failure> {
+        self.branches_filtered("--merged", rev, include_remotes)
+    }
+
+    /// The branches with commits which `rev` doesn't have, using `for-each-ref --no-merged` - every branch not in
+    /// `branches_merged_into`
+    pub fn not_merged_into(&self, rev: &str, include_remotes: bool) -> Result<Vec<String>, Failure> {
+        self.branches_filtered("--no-merged", rev, include_remotes)
+    }
+
+    /// lists the branches `filter` picks for `rev`, dropping symbolic refs such as `origin/HEAD`
+    fn branches_filtered(&self, filter: &str, rev: &str, include_remotes: bool) -> Result<Vec<String>, Failure> {
+        let sha = self.rev_parse(&format!("{}^{{commit}}", rev))?;
+        let mut cmd = self.git(vec![
+            "for-each-ref".to_string(),
+            "--format=%(refname)%00%(symref)".to_string(),
+            format!("{}={}", filter, sha),
+            "refs/heads/".to_string(),
+        ]);
+        if include_remotes {
+            cmd = cmd.arg("refs/remotes/");
+        }
+        let output = cmd.run()?;
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (name, symref) = line.split_once('\0')?;
+                let short = name
+                    .strip_prefix("refs/heads/")
+                    .or_else(|| name.strip_prefix("refs/remotes/"))?;
+                symref.is_empty().then(|| short.to_string())
+            })
+            .collect())
+    }
+
+    /// validates with `check-ref-format --branch`
+    fn check_branch_name(&self, name: &str) -> Result<(), Failure> {
+        self.git(vec!["check-ref-format", "--branch", name])
+            .run()
+            .map_err(|failure| {
+                let message = format!("'{}' is not a valid branch name", name);
+                Failure {
+                    stderr: Some(message),
+                    kind: FailureKind::InvalidRefName(name.to_string()),
+                    ..failure
+                }
+            })?;
+        Ok(())
+    }
+}
+
+fn classify_branch(mut failure: Failure, name: &str) -> Failure {
+    let stderr = failure.stderr.as_deref().unwrap_or_default();
+    if stderr.contains("is not fully merged") {
+        failure.kind = FailureKind::NotFullyMerged(name.to_string());
+    } else if stderr.contains("Cannot delete branch") && stderr.contains("checked out") {
+        failure.kind = FailureKind::BranchCheckedOut(name.to_string());
+    }
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn upstream(fixture: &Fixture, branch: &str) -> Option<String> {
+        fixture
+            .repo
+            .git(vec!["rev-parse", "--abbrev-ref", &format!("{}@{{upstream}}", branch)])
+            .run()
+            .ok()
+            .and_then(|x| x.stdout)
+            .map(|x| x.trim().to_string())
+    }
+
+    #[test]
+    fn test_branch_delete_errors() {
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+        fixture
+            .repo
+            .branch_create("feature", None, BranchOptions::default())
+            .unwrap();
+        fixture.repo.git(vec!["checkout", "-q", "feature"]).run().unwrap();
+        fixture.commit_all("unmerged");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+
+        let unmerged = fixture.repo.branch_delete("feature", false).unwrap_err();
+        assert_eq!(unmerged.kind, FailureKind::NotFullyMerged("feature".to_string()));
+
+        let current = fixture.repo.branch_delete("master", true).unwrap_err();
+        assert_eq!(current.kind, FailureKind::BranchCheckedOut("master".to_string()));
+
+        fixture.repo.branch_delete("feature", true).unwrap();
+        assert!(fixture.repo.rev_parse("refs/heads/feature").is_err());
+    }
+
+    #[test]
+    fn test_branch_invalid_name() {
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+
+        let failure = fixture
+            .repo
+            .branch_create("bad..name", None, BranchOptions::default())
+            .unwrap_err();
+        assert_eq!(failure.kind, FailureKind::InvalidRefName("bad..name".to_string()));
+        assert!(failure.stderr.unwrap().contains("not a valid branch name"));
+    }
+
+    #[test]
+    fn test_branch_rename_keeps_upstream() {
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+
+        let opts = BranchOptions {
+            track: Some("master".to_string()),
+            ..Default::default()
+        };
+        fixture.repo.branch_create("topic", None, opts).unwrap();
+        assert_eq!(upstream(&fixture, "topic").as_deref(), Some("master"));
+
+        fixture.repo.branch_rename("topic", "renamed", false).unwrap();
+        assert_eq!(upstream(&fixture, "renamed").as_deref(), Some("master"));
+
+        fixture.repo.branch_unset_upstream("renamed").unwrap();
+        assert_eq!(upstream(&fixture, "renamed"), None);
+        fixture.repo.branch_set_upstream("renamed", "master").unwrap();
+        assert_eq!(upstream(&fixture, "renamed").as_deref(), Some("master"));
+    }
+
+    #[test]
+    fn test_branches_containing_and_merged() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let base = fixture.commit_all("base");
+        fixture.repo.git(vec!["branch", "merged"]).run().unwrap();
+        fixture.repo.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        fixture.write("b.txt", "b\n");
+        let fix = fixture.commit_all("fix");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.repo.git(vec!["branch", "release", &fix]).run().unwrap();
+        fixture.repo.git(vec!["update-ref", "refs/remotes/origin/release", &fix]).run().unwrap();
+        fixture.repo.git(vec!["update-ref", "refs/remotes/origin/master", &base]).run().unwrap();
+        fixture
+            .repo
+            .git(vec!["symbolic-ref", "refs/remotes/origin/HEAD", "refs/remotes/origin/master"])
+            .run()
+            .unwrap();
+
+        let repo = &fixture.repo;
+        assert_eq!(repo.branches_containing(&fix, false).unwrap(), ["feature", "release"]);
+        assert_eq!(
+            repo.branches_containing(&fix, true).unwrap(),
+            ["feature", "release", "origin/release"]
+        );
+        assert_eq!(repo.branches_containing("master", false).unwrap(), ["feature", "master", "merged", "release"]);
+
+        assert_eq!(repo.branches_merged_into("master", false).unwrap(), ["master", "merged"]);
+        assert_eq!(
+            repo.branches_merged_into("master", true).unwrap(),
+            ["master", "merged", "origin/master"]
+        );
+        assert_eq!(repo.not_merged_into("master", true).unwrap(), ["feature", "release", "origin/release"]);
+
+        let failure = repo.branches_containing("no-such-rev", true).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert!(repo.branches_merged_into("no-such-rev", false).is_err());
+    }
+}
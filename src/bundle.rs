@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use crate::{Failure, Git, Repo};
+
+/// what a bundle holds, from `Repo::bundle_verify`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleInfo {
+    /// SHAs of the commits a repository must already have to unbundle it - empty for a complete history
+    pub prerequisites: Vec<String>,
+    /// the refs the bundle carries, eg `refs/heads/main` or `HEAD`
+    pub included_refs: Vec<String>,
+}
+
+impl Repo {
+    /// Writes the commits reachable from `refs` to a bundle file at `output`, using `bundle create`
+    /// 
+    /// `refs` are anything `rev-list` takes, so `--all`, or a range such as `v1.0..main` for an incremental bundle
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.bundle_create("backup.bundle", &["--all"]).unwrap();
+    /// ```
+    pub fn bundle_create<P: AsRef<Path>>(&self, output: P, refs: &[&str]) -> Result<(), Failure> {
+        self.git(vec!["bundle", "create", "--quiet"])
+            .arg_os(output.as_ref())
+            .args(refs.to_vec())
+            .run()?;
+        Ok(())
+    }
+
+    /// Checks that `bundle` is valid and that this repository has everything it requires, using `bundle verify`
+    pub fn bundle_verify<P: AsRef<Path>>(&self, bundle: P) -> Result<BundleInfo, Failure> {
+        let output = self
+            .git(vec!["bundle", "verify"])
+            .arg_os(bundle.as_ref())
+            .env("LC_ALL", "C")
+            .run()?;
+        Ok(parse_verify(&output.stdout.unwrap_or_default()))
+    }
+
+    /// Clones the repository in the bundle file `bundle` into `dest`, returning the new repository
+    /// 
+    /// The bundle must record a complete history, and `origin` is left pointing at the bundle file
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::clone_from_bundle("backup.bundle", "restored").unwrap();
+    /// ```
+    pub fn clone_from_bundle<P: AsRef<Path>, Q: AsRef<Path>>(bundle: P, dest: Q) -> Result<Repo, Failure> {
+        Git::new(vec!["clone", "--quiet", "--"])
+            .arg_os(bundle.as_ref())
+            .arg_os(dest.as_ref())
+            .run()?;
+        Repo::open(dest)
+    }
+}
+
+/// reads the `<sha> <ref>` lines under the "contains" and "requires" headings of `bundle verify`
+fn parse_verify(stdout: &str) -> BundleInfo {
+    let mut info = BundleInfo::default();
+    // true under "contains", false under "requires", and `None` under anything else
+    let mut section = None;
+    for line in stdout.lines() {
+        if line.starts_with("The bundle contains") {
+            section = Some(true);
+        } else if line.starts_with("The bundle requires") {
+            section = Some(false);
+        } else if line.starts_with("The bundle ") {
+            section = None;
+        } else if let Some(contains) = section {
+            let (sha, name) = line.split_once(' ').unwrap_or((line, ""));
+            if contains {
+                info.included_refs.push(name.trim().to_string());
+            } else {
+                info.prerequisites.push(sha.to_string());
+            }
+        }
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{Fixture, TempDir};
+
+    #[test]
+    fn test_parse_verify() {
+        let stdout = "The bundle contains these 2 refs:\n\
+                      1111111111111111111111111111111111111111 refs/heads/main\n\
+                      1111111111111111111111111111111111111111 HEAD\n\
+                      The bundle requires this ref:\n\
+                      2222222222222222222222222222222222222222 \n\
+                      The bundle uses this hash algorithm: sha1\n";
+        assert_eq!(
+            parse_verify(stdout),
+            BundleInfo {
+                prerequisites: vec!["2222222222222222222222222222222222222222".to_string()],
+                included_refs: vec!["refs/heads/main".to_string(), "HEAD".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_bundle_and_clone() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let first = fixture.commit_all("first");
+        fixture.write("a.txt", "b\n");
+        let second = fixture.commit_all("second");
+
+        let dir = TempDir::new();
+        let full = dir.path().join("full.bundle");
+        fixture.repo.bundle_create(&full, &["--all"]).unwrap();
+        let info = fixture.repo.bundle_verify(&full).unwrap();
+        assert!(info.prerequisites.is_empty());
+        assert_eq!(info.included_refs, ["refs/heads/master", "HEAD"]);
+
+        let incremental = dir.path().join("incremental.bundle");
+        fixture.repo.bundle_create(&incremental, &["HEAD~1..master"]).unwrap();
+        let info = fixture.repo.bundle_verify(&incremental).unwrap();
+        assert_eq!(info.prerequisites, vec![first.clone()]);
+        assert_eq!(info.included_refs, ["refs/heads/master"]);
+
+        let clone = Repo::clone_from_bundle(&full, dir.path().join("clone")).unwrap();
+        assert_eq!(clone.rev_parse("HEAD").unwrap(), second);
+        assert_eq!(clone.rev_parse("HEAD~1").unwrap(), first);
+        assert_eq!(std::fs::read_to_string(clone.path().join("a.txt")).unwrap(), "b\n");
+        assert!(Repo::clone_from_bundle(&incremental, dir.path().join("partial")).is_err());
+    }
+}
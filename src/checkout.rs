@@ -0,0 +1,243 @@
+use std::path::Path;
+
+use crate::{Failure, Repo};
+
+/// `switch` and `restore` arrived in git 2.23
+const SWITCH_RESTORE: (u32, u32, u32) = (2, 23, 0);
+
+/// options for `Repo::switch`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwitchOptions {
+    /// create the branch first (`-c`), or reset it if it exists and `force` is set (`-C`)
+    pub create: bool,
+
+    /// throw away local changes (`--discard-changes`)
+    pub force: bool,
+
+    /// detach `HEAD` at the given commit rather than switching branch (`--detach`)
+    pub detach: bool,
+
+    /// where a created branch starts, defaulting to `HEAD`
+    pub start_point: Option<String>,
+}
+
+/// options for `Repo::restore`
+/// 
+/// with neither `staged` nor `worktree` set, the working tree is restored, as with git
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreOptions {
+    /// restore the index (`--staged`)
+    pub staged: bool,
+
+    /// restore the working tree (`--worktree`)
+    pub worktree: bool,
+
+    /// restore from this commit, rather than the index or `HEAD` (`--source`)
+    pub source: Option<String>,
+}
+
+impl Repo {
+    /// Switches to `branch`, using `git switch` or, before git 2.23, the equivalent `git checkout`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, SwitchOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = SwitchOptions { create: true, ..Default::default() };
+    /// repo.switch("feature", opts).unwrap();
+    /// ```
+    pub fn switch(&self, branch: &str, opts: SwitchOptions) -> Result<(), Failure> {
+        let modern = self.git_version()? >= SWITCH_RESTORE;
+        self.git(switch_args(branch, &opts, modern)).run()?;
+        Ok(())
+    }
+
+    /// Restores `paths`, using `git restore` or, before git 2.23, the equivalent `checkout`/`reset`
+    /// 
+    /// On older git, restoring only the working tree from a `source` also updates the index, as `checkout` does
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, RestoreOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// let unstage = RestoreOptions { staged: true, ..Default::default() };
+    /// repo.restore(&["src/lib.rs"], unstage).unwrap();
+    /// ```
+    pub fn restore<P: AsRef<Path>>(&self, paths: &[P], opts: RestoreOptions) -> Result<(), Failure> {
+        let modern = self.git_version()? >= SWITCH_RESTORE;
+        self.git(restore_args(&opts, modern))
+            .arg("--")
+            .args_os(paths.iter().map(|x| x.as_ref()))
+            .run()?;
+        Ok(())
+    }
+}
+
+fn switch_args(branch: &str, opts: &SwitchOptions, modern: bool) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    args.push(if modern { "switch" } else { "checkout" }.to_string());
+    args.push("--quiet".to_string());
+
+    if opts.force {
+        args.push(if modern { "--discard-changes" } else { "--force" }.to_string());
+    }
+    if opts.detach {
+        args.push("--detach".to_string());
+    }
+    if opts.create {
+        let flag = match (modern, opts.force) {
+            (true, false) => "-c",
+            (true, true) => "-C",
+            (false, false) => "-b",
+            (false, true) => "-B",
+        };
+        args.push(flag.to_string());
+    }
+
+    args.push(branch.to_string());
+    if let Some(start_point) = &opts.start_point {
+        args.push(start_point.clone());
+    }
+    args
+}
+
+/// everything up to the `--`
+fn restore_args(opts: &RestoreOptions, modern: bool) -> Vec<String> {
+    if modern {
+        let mut args = vec!["restore".to_string()];
+        if opts.staged {
+            args.push("--staged".to_string());
+        }
+        if opts.worktree {
+            args.push("--worktree".to_string());
+        }
+        if let Some(source) = &opts.source {
+            args.push(format!("--source={}", source));
+        }
+        return args;
+    }
+
+    match (opts.staged, opts.worktree) {
+        // index only: reset the entries to the source
+        (true, false) => {
+            let mut args = vec!["reset".to_string(), "--quiet".to_string()];
+            args.extend(opts.source.clone());
+            args
+        }
+        // both: check the paths out of a commit
+        (true, true) => vec![
+            "checkout".to_string(),
+            opts.source.clone().unwrap_or_else(|| "HEAD".to_string()),
+        ],
+        // working tree only: from the index, or the source
+        _ => {
+            let mut args = vec!["checkout".to_string()];
+            args.extend(opts.source.clone());
+            args
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::fs;
+
+    #[test]
+    fn test_switch_args() {
+        let create = SwitchOptions {
+            create: true,
+            start_point: Some("main".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(switch_args("topic", &create, true), ["switch", "--quiet", "-c", "topic", "main"]);
+        assert_eq!(switch_args("topic", &create, false), ["checkout", "--quiet", "-b", "topic", "main"]);
+
+        let detach = SwitchOptions {
+            detach: true,
+            force: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            switch_args("HEAD~1", &detach, true),
+            ["switch", "--quiet", "--discard-changes", "--detach", "HEAD~1"]
+        );
+        assert_eq!(
+            switch_args("HEAD~1", &detach, false),
+            ["checkout", "--quiet", "--force", "--detach", "HEAD~1"]
+        );
+    }
+
+    #[test]
+    fn test_restore_args() {
+        let staged = RestoreOptions {
+            staged: true,
+            ..Default::default()
+        };
+        assert_eq!(restore_args(&staged, true), ["restore", "--staged"]);
+        assert_eq!(restore_args(&staged, false), ["reset", "--quiet"]);
+
+        let both = RestoreOptions {
+            staged: true,
+            worktree: true,
+            source: Some("HEAD~1".to_string()),
+        };
+        assert_eq!(
+            restore_args(&both, true),
+            ["restore", "--staged", "--worktree", "--source=HEAD~1"]
+        );
+        assert_eq!(restore_args(&both, false), ["checkout", "HEAD~1"]);
+        assert_eq!(restore_args(&RestoreOptions::default(), false), ["checkout"]);
+    }
+
+    #[test]
+    fn test_switch_and_restore() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\n");
+        fixture.commit_all("initial");
+
+        let create = SwitchOptions {
+            create: true,
+            ..Default::default()
+        };
+        fixture.repo.switch("topic", create).unwrap();
+        assert_eq!(fixture.repo.symbolic_ref("HEAD").unwrap(), "refs/heads/topic");
+        fixture.repo.switch("master", SwitchOptions::default()).unwrap();
+        assert_eq!(fixture.repo.symbolic_ref("HEAD").unwrap(), "refs/heads/master");
+
+        fixture.write("a.txt", "two\n");
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+        let staged = RestoreOptions {
+            staged: true,
+            ..Default::default()
+        };
+        fixture.repo.restore(&["a.txt"], staged).unwrap();
+        assert!(!fixture.repo.has_staged_changes().unwrap());
+        assert!(fixture.repo.has_unstaged_changes().unwrap());
+
+        fixture.repo.restore(&["a.txt"], RestoreOptions::default()).unwrap();
+        let contents = fs::read_to_string(fixture.repo.path().join("a.txt")).unwrap();
+        assert_eq!(contents, "one\n");
+    }
+
+    #[test]
+    fn test_restore_fallback() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\n");
+        fixture.commit_all("initial");
+        fixture.write("a.txt", "two\n");
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+
+        let both = RestoreOptions {
+            staged: true,
+            worktree: true,
+            ..Default::default()
+        };
+        fixture
+            .repo
+            .git(restore_args(&both, false))
+            .args(vec!["--", "a.txt"])
+            .run()
+            .unwrap();
+        assert!(fixture.repo.is_clean().unwrap());
+    }
+}
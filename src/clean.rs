@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use crate::repo::path_from_bytes;
+use crate::{Failure, FailureKind, Repo};
+
+/// options for `Repo::clean` and `Repo::clean_preview`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanOptions {
+    /// also remove untracked directories (`-d`)
+    pub directories: bool,
+
+    /// also remove files git ignores, such as build output (`-x`)
+    pub ignored: bool,
+
+    /// must be set for `clean` to remove anything - `clean_preview` ignores it
+    pub force: bool,
+}
+
+impl Repo {
+    /// Lists what `clean` would remove with `opts`, without removing anything, using `clean --dry-run`
+    /// 
+    /// Only the directory the repository was opened at is cleaned, as git runs from it, and paths are relative to
+    /// it - a `Repo` opened in a subdirectory leaves the rest of the working tree alone. An untracked directory is
+    /// listed once, as itself, rather than as each file inside it
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{CleanOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = CleanOptions { directories: true, ..Default::default() };
+    /// for path in repo.clean_preview(opts.clone()).unwrap() {
+    ///     println!("would remove {}", path.display());
+    /// }
+    /// repo.clean(CleanOptions { force: true, ..opts }).unwrap();
+    /// ```
+    pub fn clean_preview(&self, opts: CleanOptions) -> Result<Vec<PathBuf>, Failure> {
+        self.run_clean("--dry-run", &opts, "Would remove ")
+    }
+
+    /// Removes untracked files, and with `opts`, directories and ignored files, returning what was removed
+    /// 
+    /// As with `clean_preview`, only under the directory the repository was opened at, with paths relative to it.
+    /// Fails with `FailureKind::ForceRequired`, before running git, unless `opts.force` is set
+    pub fn clean(&self, opts: CleanOptions) -> Result<Vec<PathBuf>, Failure> {
+        if !opts.force {
+            return Err(Failure::new(
+                FailureKind::ForceRequired,
+                "refusing to clean without `CleanOptions::force` - see `clean_preview`",
+            ));
+        }
+        self.run_clean("--force", &opts, "Removing ")
+    }
+
+    /// runs `clean` with `mode`, reading the paths from lines starting with `prefix`
+    fn run_clean(&self, mode: &str, opts: &CleanOptions, prefix: &str) -> Result<Vec<PathBuf>, Failure> {
+        let mut cmd = self.git(vec!["clean", mode]);
+        if opts.directories {
+            cmd = cmd.arg("-d");
+        }
+        if opts.ignored {
+            cmd = cmd.arg("-x");
+        }
+
+        let output = cmd.run()?;
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.strip_prefix(prefix))
+            .map(|path| unquote(path.trim_end_matches('/')))
+            .collect())
+    }
+}
+
+/// undoes git's C-style quoting of unusual paths, eg `"tab\there"` or `"caf\303\251"` - `clean` has no `-z`
+pub(crate) fn unquote(path: &str) -> PathBuf {
+    let Some(quoted) = path.strip_prefix('"').and_then(|x| x.strip_suffix('"')) else {
+        return PathBuf::from(path);
+    };
+
+    let mut bytes = Vec::new();
+    let mut chars = quoted.bytes().peekable();
+    while let Some(c) = chars.next() {
+        if c != b'\\' {
+            bytes.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'v') => bytes.push(0x0b),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(digit @ b'0'..=b'3') => {
+                let mut value = digit - b'0';
+                for _ in 0..2 {
+                    if let Some(digit) = chars.next_if(u8::is_ascii_digit) {
+                        value = value * 8 + (digit - b'0');
+                    }
+                }
+                bytes.push(value);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+    path_from_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_unquote() {
+        assert_eq!(unquote("plain.txt"), PathBuf::from("plain.txt"));
+        assert_eq!(unquote(r#""tab\there""#), PathBuf::from("tab\there"));
+        assert_eq!(unquote(r#""say \"hi\"""#), PathBuf::from("say \"hi\""));
+        assert_eq!(unquote(r#""caf\303\251""#), PathBuf::from("café"));
+        assert_eq!(unquote(r#""back\\slash""#), PathBuf::from("back\\slash"));
+    }
+
+    #[test]
+    fn test_clean_preview_and_clean() {
+        let fixture = Fixture::new();
+        fixture.write(".gitignore", "*.log\n");
+        fixture.write("tracked.txt", "tracked\n");
+        fixture.commit_all("initial");
+        fixture.write("untracked.txt", "untracked\n");
+        fixture.write("café.txt", "untracked\n");
+        fixture.write("build/out.o", "untracked\n");
+        fixture.write("debug.log", "ignored\n");
+
+        let preview = fixture.repo.clean_preview(CleanOptions::default()).unwrap();
+        assert_eq!(preview, vec![PathBuf::from("café.txt"), PathBuf::from("untracked.txt")]);
+        let everything = CleanOptions {
+            directories: true,
+            ignored: true,
+            ..Default::default()
+        };
+        let preview = fixture.repo.clean_preview(everything.clone()).unwrap();
+        assert_eq!(preview.len(), 4);
+        assert!(preview.contains(&PathBuf::from("build")));
+        assert!(preview.contains(&PathBuf::from("debug.log")));
+
+        // nothing is removed without force
+        let failure = fixture.repo.clean(everything.clone()).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::ForceRequired);
+        assert!(fixture.repo.path().join("untracked.txt").exists());
+
+        let directories = CleanOptions {
+            directories: true,
+            force: true,
+            ..Default::default()
+        };
+        let removed = fixture.repo.clean(directories).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert!(!fixture.repo.path().join("untracked.txt").exists());
+        assert!(!fixture.repo.path().join("build").exists());
+        assert!(fixture.repo.path().join("debug.log").exists());
+        assert!(fixture.repo.path().join("tracked.txt").exists());
+        assert_eq!(
+            fixture.repo.clean_preview(everything).unwrap(),
+            vec![PathBuf::from("debug.log")]
+        );
+    }
+
+    #[test]
+    fn test_clean_subdirectory() {
+        let fixture = Fixture::new();
+        fixture.write("sub/tracked.txt", "tracked\n");
+        fixture.commit_all("initial");
+        fixture.write("top.txt", "untracked\n");
+        fixture.write("sub/inner.txt", "untracked\n");
+        let sub = Repo::open(fixture.repo.path().join("sub")).unwrap();
+
+        let opts = CleanOptions {
+            force: true,
+            ..Default::default()
+        };
+        assert_eq!(sub.clean_preview(opts.clone()).unwrap(), vec![PathBuf::from("inner.txt")]);
+        assert_eq!(sub.clean(opts).unwrap(), vec![PathBuf::from("inner.txt")]);
+        assert!(!fixture.repo.path().join("sub/inner.txt").exists());
+        assert!(fixture.repo.path().join("top.txt").exists());
+    }
+}
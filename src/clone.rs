@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, FailureKind, Git, Repo};
+
+/// options for `Repo::clone_from`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloneOptions {
+    /// clone into a repository with no working tree, opened with `Repo::open_bare` (`--bare`)
+    pub bare: bool,
+
+    /// only fetch this many commits of history (`--depth`)
+    /// 
+    /// git ignores this for plain local paths - use a `file://` url instead
+    pub depth: Option<u32>,
+
+    /// make a partial clone, eg `blob:none` (`--filter`)
+    pub filter: Option<String>,
+
+    /// only fetch this branch, and check it out (`--single-branch --branch`)
+    pub single_branch: Option<String>,
+
+    /// borrow objects from this local repository rather than copying them, through its alternates (`--reference`)
+    /// 
+    /// the clone is broken if the reference repository is later deleted or pruned
+    pub reference: Option<PathBuf>,
+}
+
+impl Repo {
+    /// Clones `url` into `dest`, returning the new repository
+    /// 
+    /// `Repo::open` is the constructor for an existing repository, and `Repo::create` for a new, empty one
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{CloneOptions, Repo};
+    /// let opts = CloneOptions { depth: Some(1), ..Default::default() };
+    /// let repo = Repo::clone_from("https://github.com/werdl/gitrs", "gitrs", opts).unwrap();
+    /// ```
+    pub fn clone_from<P: AsRef<Path>>(
+        url: &str,
+        dest: P,
+        opts: CloneOptions,
+    ) -> Result<Repo, Failure> {
+        let mut cmd = Git::new(vec!["clone", "--quiet"]).env("LC_ALL", "C");
+        if let Some(depth) = opts.depth {
+            cmd = cmd.arg(format!("--depth={}", depth));
+        }
+        if let Some(filter) = &opts.filter {
+            cmd = cmd.arg(format!("--filter={}", filter));
+        }
+        if opts.bare {
+            cmd = cmd.arg("--bare");
+        }
+        if let Some(branch) = &opts.single_branch {
+            cmd = cmd.args(vec!["--single-branch", "--branch", branch.as_str()]);
+        }
+        if let Some(reference) = &opts.reference {
+            cmd = cmd.arg("--reference").arg_os(reference);
+        }
+
+        cmd.args(vec!["--", url])
+            .arg_os(dest.as_ref())
+            .run()
+            .map_err(classify_shallow)?;
+        if opts.bare {
+            Repo::open_bare(dest)
+        } else {
+            Repo::open(dest)
+        }
+    }
+
+    /// Checks whether the repository has truncated history, using `rev-parse --is-shallow-repository`
+    pub fn is_shallow(&self) -> Result<bool, Failure> {
+        let output = self
+            .git(vec!["rev-parse", "--is-shallow-repository"])
+            .run()?;
+        Ok(output.stdout.unwrap_or_default().trim() == "true")
+    }
+
+    /// Fetches `depth` more commits of history from the default remote
+    pub fn fetch_deepen(&self, depth: u32) -> Result<(), Failure> {
+        self.git(vec!["fetch", "--quiet", &format!("--deepen={}", depth)])
+            .run()
+            .map_err(classify_shallow)?;
+        Ok(())
+    }
+
+    /// Fetches the complete history, turning a shallow repository into a full one
+    pub fn unshallow(&self) -> Result<(), Failure> {
+        self.git(vec!["fetch", "--quiet", "--unshallow"])
+            .run()
+            .map_err(classify_shallow)?;
+        Ok(())
+    }
+}
+
+/// marks failures caused by missing history as `FailureKind::ShallowRepository`
+/// 
+/// callers seeing this can `unshallow` and retry
+pub(crate) fn classify_shallow(mut failure: Failure) -> Failure {
+    let stderr = failure.stderr.as_deref().unwrap_or_default();
+    if stderr.contains("shallow update not allowed")
+        || stderr.contains("from a shallow repository")
+    {
+        failure.kind = FailureKind::ShallowRepository;
+    }
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{Fixture, TempDir};
+    use crate::RevListOptions;
+
+    fn file_url(fixture: &Fixture) -> String {
+        format!("file://{}", fixture.repo.path().display())
+    }
+
+    #[test]
+    fn test_shallow_clone() {
+        let origin = Fixture::new();
+        for message in ["one", "two", "three"] {
+            origin.write("a.txt", message);
+            origin.commit_all(message);
+        }
+        assert!(!origin.repo.is_shallow().unwrap());
+
+        let dir = TempDir::new();
+        let opts = CloneOptions {
+            depth: Some(1),
+            ..Default::default()
+        };
+        let clone = Repo::clone_from(&file_url(&origin), dir.path().join("clone"), opts).unwrap();
+
+        assert!(clone.is_shallow().unwrap());
+        assert_eq!(clone.commit_count("HEAD").unwrap(), 1);
+
+        clone.fetch_deepen(1).unwrap();
+        assert_eq!(clone.commit_count("HEAD").unwrap(), 2);
+
+        clone.unshallow().unwrap();
+        assert!(!clone.is_shallow().unwrap());
+        let shas = |repo: &Repo| -> Vec<String> {
+            let all = repo.rev_list(RevListOptions::default()).unwrap();
+            all.map(|x| x.unwrap()).collect()
+        };
+        assert_eq!(shas(&clone), shas(&origin.repo));
+    }
+
+    #[test]
+    fn test_partial_clone() {
+        let origin = Fixture::new();
+        origin.write("a.txt", "a");
+        origin.commit_all("initial");
+        origin
+            .repo
+            .git(vec!["config", "uploadpack.allowFilter", "true"])
+            .run()
+            .unwrap();
+
+        let dir = TempDir::new();
+        let opts = CloneOptions {
+            filter: Some("blob:none".to_string()),
+            ..Default::default()
+        };
+        let clone = Repo::clone_from(&file_url(&origin), dir.path().join("clone"), opts).unwrap();
+        let promisor = clone
+            .git(vec!["config", "remote.origin.promisor"])
+            .run()
+            .unwrap();
+        assert_eq!(promisor.stdout.unwrap().trim(), "true");
+    }
+
+    #[test]
+    fn test_clone_options() {
+        let origin = Fixture::new();
+        origin.write("a.txt", "a");
+        let first = origin.commit_all("initial");
+        origin.repo.git(vec!["branch", "other"]).run().unwrap();
+        origin.write("a.txt", "b");
+        origin.commit_all("second");
+
+        let dir = TempDir::new();
+        let clone = Repo::clone_from(&file_url(&origin), dir.path().join("plain"), CloneOptions::default()).unwrap();
+        assert!(!clone.is_dirty().unwrap());
+
+        let opts = CloneOptions {
+            single_branch: Some("other".to_string()),
+            reference: Some(origin.repo.path().to_path_buf()),
+            ..Default::default()
+        };
+        let clone = Repo::clone_from(&file_url(&origin), dir.path().join("single"), opts).unwrap();
+        assert!(!clone.is_dirty().unwrap());
+        assert_eq!(clone.rev_parse("HEAD").unwrap(), first);
+        assert!(clone.rev_parse("origin/master").is_err());
+        let alternates = clone.path().join(".git/objects/info/alternates");
+        assert!(std::fs::read_to_string(alternates).unwrap().contains("objects"));
+
+        let opts = CloneOptions {
+            bare: true,
+            ..Default::default()
+        };
+        let bare = Repo::clone_from(&file_url(&origin), dir.path().join("bare.git"), opts).unwrap();
+        assert!(bare.path().join("HEAD").is_file());
+        assert_eq!(bare.rev_parse("refs/heads/other").unwrap(), first);
+    }
+
+    #[test]
+    fn test_classify_shallow() {
+        let failure = Failure {
+            stderr: Some(" ! [remote rejected] main -> main (shallow update not allowed)\n".to_string()),
+            stdout: None,
+            code: 1,
+            kind: FailureKind::Exit,
+        };
+        assert_eq!(classify_shallow(failure).kind, FailureKind::ShallowRepository);
+    }
+}
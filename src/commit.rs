@@ -0,0 +1,271 @@
+use std::path::Path;
+
+use crate::identity::classify_identity;
+use crate::{Failure, FailureKind, Git, Repo};
+
+/// options for `Repo::commit`
+/// 
+/// all default to off, so `CommitOptions::default()` behaves like a plain `git commit`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    /// stage modified and deleted files first (`-a`)
+    pub all: bool,
+
+    /// replace the tip of the current branch (`--amend`)
+    pub amend: bool,
+
+    /// with `amend`, keep the existing message, ignoring the one given (`--no-edit`)
+    pub no_edit: bool,
+
+    /// allow a commit with no changes (`--allow-empty`)
+    pub allow_empty: bool,
+
+    /// add a `Signed-off-by` trailer (`--signoff`)
+    pub signoff: bool,
+
+    /// skip the pre-commit and commit-msg hooks (`--no-verify`)
+    pub no_verify: bool,
+
+    /// sign the commit with the configured key (`--gpg-sign`)
+    pub sign: bool,
+
+    /// sign the commit with this key instead, implying `sign` (`--gpg-sign=<key>`)
+    pub sign_key: Option<String>,
+
+    /// override the author, in the form `Name <email>`
+    pub author: Option<String>,
+
+    /// override the author date, in any format git understands
+    pub date: Option<String>,
+}
+
+/// the outcome of a successful `Repo::commit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitResult {
+    /// full SHA of the new commit
+    pub sha: String,
+
+    /// git's summary line, eg `[main 1a2b3c4] message`
+    pub summary: String,
+}
+
+impl Repo {
+    /// Records a commit with the given message
+    /// 
+    /// The message is passed on stdin (`-F -`), so it may span multiple lines and contain any characters
+    /// 
+    /// If there is nothing to commit, the failure has kind `FailureKind::NothingToCommit`, and if no identity is set,
+    /// `FailureKind::MissingIdentity`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{CommitOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let result = repo.commit("Fix the thing\n\nLonger description", CommitOptions::default());
+    /// ```
+    pub fn commit(&self, message: &str, opts: CommitOptions) -> Result<CommitResult, Failure> {
+        let cmd = if opts.amend && opts.no_edit {
+            self.commit_command(&opts)
+        } else {
+            self.commit_command(&opts).args(["-F", "-"]).stdin(message)
+        };
+        self.run_commit(cmd)
+    }
+
+    /// Records a commit with the message read from the file at `message_path`, relative to the repository root
+    /// 
+    /// For tooling which writes the message out first - it is used as written, as for `commit`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{CommitOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let result = repo.commit_from_file("target/COMMIT_MSG", CommitOptions::default()).unwrap();
+    /// println!("committed {}", result.sha);
+    /// ```
+    pub fn commit_from_file<P: AsRef<Path>>(
+        &self,
+        message_path: P,
+        opts: CommitOptions,
+    ) -> Result<CommitResult, Failure> {
+        let cmd = self.commit_command(&opts).arg("-F").arg_os(message_path.as_ref());
+        self.run_commit(cmd)
+    }
+
+    /// `commit` with every flag from `opts`, but no message
+    fn commit_command(&self, opts: &CommitOptions) -> Git {
+        let mut cmd = self.git(vec!["commit"]);
+        if opts.all {
+            cmd = cmd.arg("--all");
+        }
+        if opts.amend {
+            cmd = cmd.arg("--amend");
+        }
+        if opts.no_edit {
+            cmd = cmd.arg("--no-edit");
+        }
+        if opts.allow_empty {
+            cmd = cmd.arg("--allow-empty");
+        }
+        if opts.signoff {
+            cmd = cmd.arg("--signoff");
+        }
+        if opts.no_verify {
+            cmd = cmd.arg("--no-verify");
+        }
+        if let Some(key) = &opts.sign_key {
+            cmd = cmd.arg(format!("--gpg-sign={}", key));
+        } else if opts.sign {
+            cmd = cmd.arg("--gpg-sign");
+        }
+        if let Some(author) = &opts.author {
+            cmd = cmd.arg(format!("--author={}", author));
+        }
+        if let Some(date) = &opts.date {
+            cmd = cmd.arg(format!("--date={}", date));
+        }
+        cmd
+    }
+
+    fn run_commit(&self, cmd: Git) -> Result<CommitResult, Failure> {
+        let output = cmd.run().map_err(|mut failure| {
+            if nothing_to_commit(&failure) {
+                failure.kind = FailureKind::NothingToCommit;
+            }
+            classify_identity(failure)
+        })?;
+
+        let summary = output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(CommitResult {
+            sha: self.rev_parse("HEAD")?,
+            summary,
+        })
+    }
+}
+
+/// git reports an empty commit on stdout, with exit code 1
+fn nothing_to_commit(failure: &Failure) -> bool {
+    let stdout = failure.stdout.as_deref().unwrap_or_default();
+    failure.code == 1
+        && (stdout.contains("nothing to commit")
+            || stdout.contains("nothing added to commit")
+            || stdout.contains("no changes added to commit"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_commit() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+
+        let result = fixture
+            .repo
+            .commit("first line\n\nsecond 'quoted' \"line\"", CommitOptions::default())
+            .unwrap();
+
+        assert_eq!(result.sha, fixture.repo.rev_parse("HEAD").unwrap());
+        assert!(result.summary.contains("first line"), "got {}", result.summary);
+
+        let body = fixture.repo.git(vec!["log", "-1", "--format=%B"]).run().unwrap();
+        assert_eq!(
+            body.stdout.unwrap().trim(),
+            "first line\n\nsecond 'quoted' \"line\""
+        );
+    }
+
+    #[test]
+    fn test_commit_allow_empty() {
+        let fixture = Fixture::new();
+        let opts = CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+
+        let result = fixture.repo.commit("empty", opts).unwrap();
+        assert_eq!(result.sha.len(), 40);
+    }
+
+    #[test]
+    fn test_commit_amend() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        let first = fixture.commit_all("original");
+
+        let opts = CommitOptions {
+            amend: true,
+            ..Default::default()
+        };
+        let result = fixture.repo.commit("amended", opts).unwrap();
+
+        assert_ne!(result.sha, first);
+        let subject = fixture.repo.git(vec!["log", "-1", "--format=%s"]).run().unwrap();
+        assert_eq!(subject.stdout.unwrap().trim(), "amended");
+    }
+
+    #[test]
+    fn test_commit_amend_no_edit() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        let first = fixture.commit_all("original\n\nwith a body");
+        fixture.write("b.txt", "forgotten\n");
+        fixture.repo.git(vec!["add", "b.txt"]).run().unwrap();
+
+        let opts = CommitOptions {
+            amend: true,
+            no_edit: true,
+            ..Default::default()
+        };
+        let result = fixture.repo.commit("ignored", opts).unwrap();
+
+        assert_ne!(result.sha, first);
+        let message = fixture.repo.git(vec!["log", "-1", "--format=%B"]).run().unwrap();
+        assert_eq!(message.stdout.unwrap().trim(), "original\n\nwith a body");
+        let files = fixture.repo.git(vec!["ls-tree", "--name-only", "HEAD"]).run().unwrap();
+        assert_eq!(files.stdout.unwrap(), "a.txt\nb.txt\n");
+    }
+
+    #[test]
+    fn test_commit_from_file() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        fixture.write(".git/COMMIT_DRAFT", "from a file\n\nthe body\n");
+
+        let opts = CommitOptions {
+            all: true,
+            signoff: true,
+            ..Default::default()
+        };
+        fixture.repo.add(["a.txt"], Default::default()).unwrap();
+        let result = fixture.repo.commit_from_file(".git/COMMIT_DRAFT", opts).unwrap();
+        assert_eq!(result.sha, fixture.repo.rev_parse("HEAD").unwrap());
+
+        let message = fixture.repo.git(vec!["log", "-1", "--format=%B"]).run().unwrap();
+        assert_eq!(
+            message.stdout.unwrap().trim(),
+            "from a file\n\nthe body\n\nSigned-off-by: Test User <test@example.com>"
+        );
+
+        let missing = fixture.repo.commit_from_file("missing.txt", CommitOptions::default());
+        assert_eq!(missing.unwrap_err().kind, FailureKind::Exit);
+    }
+
+    #[test]
+    fn test_commit_nothing_to_commit() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        fixture.commit_all("first");
+
+        let result = fixture.repo.commit("again", CommitOptions::default());
+        assert_eq!(result.unwrap_err().kind, FailureKind::NothingToCommit);
+    }
+}
@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, Repo};
+
+/// an unmerged path, with the blobs of each side - see `Repo::conflicts`
+/// 
+/// a side is `None` when it doesn't have the file, eg it was deleted there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub path: PathBuf,
+    /// stage 1, the common ancestor
+    pub base: Option<String>,
+    /// stage 2, the branch being merged into
+    pub ours: Option<String>,
+    /// stage 3, the branch being merged
+    pub theirs: Option<String>,
+}
+
+/// which side of a conflict to take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+impl Conflict {
+    /// Reads the common ancestor's version of the file
+    pub fn base(&self, repo: &Repo) -> Result<Option<Vec<u8>>, Failure> {
+        read_stage(repo, &self.base)
+    }
+
+    /// Reads our version of the file
+    pub fn ours(&self, repo: &Repo) -> Result<Option<Vec<u8>>, Failure> {
+        read_stage(repo, &self.ours)
+    }
+
+    /// Reads their version of the file
+    pub fn theirs(&self, repo: &Repo) -> Result<Option<Vec<u8>>, Failure> {
+        read_stage(repo, &self.theirs)
+    }
+}
+
+fn read_stage(repo: &Repo, sha: &Option<String>) -> Result<Option<Vec<u8>>, Failure> {
+    sha.as_deref().map(|sha| repo.cat_file_content(sha)).transpose()
+}
+
+impl Repo {
+    /// Lists unmerged paths, using `ls-files -u -z`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for conflict in repo.conflicts().unwrap() {
+    ///     let ours = conflict.ours(&repo).unwrap().unwrap_or_default();
+    ///     println!("{}: {} bytes on our side", conflict.path.display(), ours.len());
+    /// }
+    /// ```
+    pub fn conflicts(&self) -> Result<Vec<Conflict>, Failure> {
+        let output = self.git(vec!["ls-files", "-u", "-z", "--full-name"]).run()?;
+        let mut conflicts: Vec<Conflict> = Vec::new();
+
+        for record in output.stdout.unwrap_or_default().split('\0') {
+            if record.is_empty() {
+                continue;
+            }
+            // `<mode> <sha> <stage>\t<path>`
+            let (sha, stage, path) = record
+                .split_once('\t')
+                .and_then(|(meta, path)| {
+                    let mut fields = meta.split(' ').skip(1);
+                    Some((fields.next()?, fields.next()?, path))
+                })
+                .ok_or_else(|| Failure::parse(format!("unexpected ls-files output: {:?}", record)))?;
+
+            let path = PathBuf::from(path);
+            if conflicts.last().map(|x| &x.path) != Some(&path) {
+                conflicts.push(Conflict {
+                    path,
+                    base: None,
+                    ours: None,
+                    theirs: None,
+                });
+            }
+            let conflict = conflicts.last_mut().expect("pushed above");
+            let sha = Some(sha.to_string());
+            match stage {
+                "1" => conflict.base = sha,
+                "2" => conflict.ours = sha,
+                "3" => conflict.theirs = sha,
+                _ => return Err(Failure::parse(format!("unexpected stage: {:?}", record))),
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Resolves a conflicted path by taking one side whole, using `checkout --ours`/`--theirs`, then staging it
+    pub fn checkout_conflict_side<P: AsRef<Path>>(
+        &self,
+        path: P,
+        side: ConflictSide,
+    ) -> Result<(), Failure> {
+        let flag = match side {
+            ConflictSide::Ours => "--ours",
+            ConflictSide::Theirs => "--theirs",
+        };
+        let path = path.as_ref();
+        self.git(vec!["checkout", flag, "--"]).arg_os(path).run()?;
+        self.git(vec!["add", "--"]).arg_os(path).run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// `a.txt` conflicts, `b.txt` merges cleanly
+    fn conflicted() -> Fixture {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "base\n");
+        fixture.write("b.txt", "base\n");
+        fixture.commit_all("base");
+
+        fixture.repo.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        fixture.write("a.txt", "theirs\n");
+        fixture.write("b.txt", "feature\n");
+        fixture.commit_all("theirs");
+
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.write("a.txt", "ours\n");
+        fixture.commit_all("ours");
+
+        let merge = fixture.repo.git(vec!["merge", "feature"]).run();
+        assert!(merge.is_err());
+        fixture
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let fixture = conflicted();
+
+        let conflicts = fixture.repo.conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.path, PathBuf::from("a.txt"));
+
+        let repo = &fixture.repo;
+        assert_eq!(conflict.base(repo).unwrap().unwrap(), b"base\n");
+        assert_eq!(conflict.ours(repo).unwrap().unwrap(), b"ours\n");
+        assert_eq!(conflict.theirs(repo).unwrap().unwrap(), b"theirs\n");
+
+        repo.checkout_conflict_side("a.txt", ConflictSide::Ours).unwrap();
+        assert!(repo.conflicts().unwrap().is_empty());
+        let contents = std::fs::read_to_string(repo.path().join("a.txt")).unwrap();
+        assert_eq!(contents, "ours\n");
+    }
+
+    #[test]
+    fn test_deleted_side() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "base\n");
+        fixture.commit_all("base");
+        fixture.repo.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        fixture.repo.git(vec!["rm", "-q", "a.txt"]).run().unwrap();
+        fixture.commit_all("delete");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.write("a.txt", "ours\n");
+        fixture.commit_all("ours");
+        assert!(fixture.repo.git(vec!["merge", "feature"]).run().is_err());
+
+        let conflicts = fixture.repo.conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].theirs.is_none());
+        assert_eq!(conflicts[0].theirs(&fixture.repo).unwrap(), None);
+    }
+}
@@ -1,4 +1,12 @@
-use std::process::Command;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Output, Stdio};
+use std::thread::JoinHandle;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 
 /// IsFailure - providing idiomatic ways to access fields without unwrapping
 /// 
@@ -15,6 +23,120 @@ pub trait IsFailure {
 
     /// get stderr without unwrapping
     fn stderr(&self) -> Option<String>;
+
+    /// get stdout and stderr together, for destructuring
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let (stdout, stderr) = Git::new(vec!["--version"]).run().both();
+    /// assert!(stdout.unwrap().starts_with("git version"));
+    /// assert_eq!(stderr, None);
+    /// ```
+    fn both(&self) -> (Option<String>, Option<String>) {
+        (self.stdout(), self.stderr())
+    }
+
+    /// whether git was killed rather than exiting by itself - see `FailureKind::Killed`
+    /// 
+    /// `false` unless overridden, as an exit code alone can't tell - the `Result<Success, Failure>` impl checks the
+    /// failure's kind
+    fn was_killed(&self) -> bool {
+        false
+    }
+
+    /// take stdout as bytes, moving it out rather than cloning as `stdout` does
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let bytes = Git::new(vec!["--version"]).run().into_stdout_bytes().unwrap();
+    /// assert!(bytes.starts_with(b"git version"));
+    /// ```
+    /// 
+    /// The result is consumed, so it can't be used afterwards
+    /// ```rust,compile_fail
+    /// use rsgit::{IsFailure, Git};
+    /// let output = Git::new(vec!["--version"]).run();
+    /// let bytes = output.into_stdout_bytes();
+    /// println!("{}", output.code());
+    /// ```
+    fn into_stdout_bytes(self) -> Option<Vec<u8>>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+    {
+        match self.into() {
+            Ok(success) => success.into_stdout_bytes(),
+            Err(failure) => failure.into_stdout_bytes(),
+        }
+    }
+
+    /// take stderr as bytes, moving it out rather than cloning as `stderr` does - `None` on success
+    fn into_stderr_bytes(self) -> Option<Vec<u8>>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+    {
+        self.into().err().and_then(Failure::into_stderr_bytes)
+    }
+
+    /// run `f` on success, passing the failure through unchanged - like `Result::and_then`
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let output = Git::new(vec!["--version"])
+    ///     .run()
+    ///     .and_then_git(|_| Git::new(vec!["--help"]).run());
+    /// ```
+    fn and_then_git<F>(self, f: F) -> Result<Success, Failure>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+        F: FnOnce(Success) -> Result<Success, Failure>,
+    {
+        self.into().and_then(f)
+    }
+
+    /// run `f` on failure, passing the success through unchanged - like `Result::or_else`
+    fn or_else_git<F>(self, f: F) -> Result<Success, Failure>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+        F: FnOnce(Failure) -> Result<Success, Failure>,
+    {
+        self.into().or_else(f)
+    }
+
+    /// apply `f` to stdout, whether the command succeeded or failed, leaving `None` alone
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let output = Git::new(vec!["--version"]).run().map_stdout(|x| x.trim().to_owned());
+    /// assert!(!output.stdout().unwrap().ends_with('\n'));
+    /// ```
+    fn map_stdout<F>(self, f: F) -> Result<Success, Failure>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+        F: Fn(String) -> String,
+    {
+        match self.into() {
+            Ok(success) => Ok(Success {
+                stdout: success.stdout.map(f),
+                ..success
+            }),
+            Err(failure) => Err(Failure {
+                stdout: failure.stdout.map(f),
+                ..failure
+            }),
+        }
+    }
+
+    /// apply `f` to stderr, leaving `None` alone - a success, which has no stderr, is passed through unchanged
+    fn map_stderr<F>(self, f: F) -> Result<Success, Failure>
+    where
+        Self: Sized + Into<Result<Success, Failure>>,
+        F: Fn(String) -> String,
+    {
+        self.into().map_err(|failure| Failure {
+            stderr: failure.stderr.map(f),
+            ..failure
+        })
+    }
 }
 
 impl IsFailure for Result<Success, Failure> {
@@ -39,6 +161,33 @@ impl IsFailure for Result<Success, Failure> {
             Err(failure) => failure.stderr.clone(),
         }
     }
+    fn was_killed(&self) -> bool {
+        matches!(self, Err(failure) if failure.kind == FailureKind::Killed)
+    }
+}
+
+/// `git_chain!` - runs each command only if the previous one succeeded
+/// 
+/// Each argument is an expression returning `Result<Success, Failure>`, evaluated lazily, and the result of the last
+/// one run is returned
+/// # Examples
+/// ```rust
+/// use rsgit::{git_chain, Git};
+/// let output = git_chain!(
+///     Git::new(vec!["--version"]).run(),
+///     Git::new(vec!["--help"]).run()
+/// );
+/// ```
+#[macro_export]
+macro_rules! git_chain {
+    ($first:expr $(, $rest:expr)* $(,)?) => {{
+        use $crate::IsFailure as _;
+        let result: Result<$crate::Success, $crate::Failure> = $first;
+        $(
+            let result = result.and_then_git(|_| $rest);
+        )*
+        result
+    }};
 }
 
 /// Successful command execution struct
@@ -52,16 +201,274 @@ pub struct Success {
     pub code: i32,
 }
 
+impl Success {
+    /// Takes stdout as bytes, without copying it
+    pub fn into_stdout_bytes(self) -> Option<Vec<u8>> {
+        self.stdout.map(String::into_bytes)
+    }
+
+    /// Wraps the success in `Ok`
+    pub fn into_result(self) -> Result<Success, Failure> {
+        Ok(self)
+    }
+
+    /// Turns the success into a `FailureKind::Exit` failure with the given stderr, keeping stdout and the code
+    /// 
+    /// For commands which exit 0 even when they have failed, as far as the caller is concerned
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let output = Git::new(vec!["--version"]).run().unwrap();
+    /// let failure = output.to_failure(Some("too old".to_string()));
+    /// assert_eq!(failure.code, 0);
+    /// ```
+    pub fn to_failure(self, stderr: Option<String>) -> Failure {
+        Failure {
+            stderr,
+            stdout: self.stdout,
+            code: self.code,
+            kind: FailureKind::Exit,
+        }
+    }
+}
+
 /// Failed command execution struct
 /// 
 /// Therefore, `stderr` is provided
 /// 
 /// sometimes piped to parent, so `Option<String>` is used
+/// 
+/// `kind` was added after the other fields, so a `Failure { stderr, stdout, code }` literal no longer compiles - use
+/// `Failure::from_parts`, which fills in `FailureKind::Exit`, instead
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Failure {
     pub stderr: Option<String>,
     pub stdout: Option<String>,
     pub code: i32,
+    pub kind: FailureKind,
+}
+
+/// FailureKind - what went wrong, beyond the exit code
+/// 
+/// plain `git` invocations always produce `Exit`, the higher-level `Repo` methods classify further
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureKind {
+    /// git exited with a non-zero code
+    Exit,
+
+    /// git could not be started
+    Io,
+
+    /// git was killed before it finished, by `RunningGit::kill` or a signal
+    /// 
+    /// On unix, the code is `128 + signal`, as a shell reports it - see `Failure::signal`
+    Killed,
+
+    /// git succeeded, but its output could not be understood
+    Parse,
+
+    /// `commit` found nothing to commit
+    NothingToCommit,
+
+    /// a pathspec matched no files - holds the offending pathspec
+    PathspecNotMatched(String),
+
+    /// a path is outside the working tree - holds the path
+    PathOutsideRepository(String),
+
+    /// a pattern was rejected before running git - holds the pattern
+    InvalidPattern(String),
+
+    /// git's output was not valid UTF-8, with `OutputEncoding::Strict` - holds the raw output
+    InvalidUtf8(Vec<u8>),
+
+    /// a path is not valid Unicode, so can't be passed to git - only on platforms other than unix, where paths are
+    /// bytes - holds the path
+    NonUnicodePath(PathBuf),
+
+    /// a ref or branch name is not valid - holds the name
+    InvalidRefName(String),
+
+    /// the branch has commits which are not merged, so was not deleted - holds the branch name
+    NotFullyMerged(String),
+
+    /// the branch is checked out, so can't be deleted - holds the branch name
+    BranchCheckedOut(String),
+
+    /// a destructive operation was refused, as its options did not set `force`
+    ForceRequired,
+
+    /// no name or email is set for commits, in the config or the environment - see `Repo::ensure_identity`
+    MissingIdentity,
+
+    /// the operation needs a working tree, which a bare repository doesn't have
+    BareRepository,
+
+    /// the operation needs history a shallow repository does not have - `unshallow` and retry
+    ShallowRepository,
+
+    /// git wrote more than `Git::max_output` allowed, so was killed - holds the limit, and the failure holds the
+    /// output up to it
+    OutputLimitExceeded(usize),
+
+    /// the installed git is too old for the requested operation
+    UnsupportedVersion {
+        required: (u32, u32, u32),
+        found: (u32, u32, u32),
+    },
+}
+
+impl Failure {
+    /// Builds a failure from git's output and exit code, as `run` would for a non-zero exit - the kind is
+    /// `FailureKind::Exit`
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{Failure, FailureKind};
+    /// let failure = Failure::from_parts(Some("fatal: not a git repository\n".to_string()), None, 128);
+    /// assert_eq!(failure.kind, FailureKind::Exit);
+    /// ```
+    pub fn from_parts(stderr: Option<String>, stdout: Option<String>, code: i32) -> Failure {
+        Failure {
+            stderr,
+            stdout,
+            code,
+            kind: FailureKind::Exit,
+        }
+    }
+
+    /// a failure which did not come from git's exit code - `message` is stored as stderr, and `code` is -1
+    pub(crate) fn new<T: ToString>(kind: FailureKind, message: T) -> Failure {
+        Failure {
+            stderr: Some(message.to_string()),
+            stdout: None,
+            code: -1,
+            kind,
+        }
+    }
+
+    /// a failure from a finished process - `FailureKind::Exit`, or `Killed` if a signal ended it
+    pub(crate) fn from_output(output: Output) -> Failure {
+        let (code, kind) = exit_code(output.status);
+        Failure {
+            stderr: Some(executor::lossy(output.stderr)),
+            stdout: Some(executor::lossy(output.stdout)),
+            code,
+            kind,
+        }
+    }
+
+    /// The signal which killed git, on unix - `None` for any other failure
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// if let Err(failure) = Git::new(vec!["gc", "--aggressive"]).run() {
+    ///     if failure.signal() == Some(9) {
+    ///         eprintln!("git was killed - out of memory?");
+    ///     }
+    /// }
+    /// ```
+    pub fn signal(&self) -> Option<i32> {
+        if cfg!(unix) && self.kind == FailureKind::Killed && self.code > 128 {
+            Some(self.code - 128)
+        } else {
+            None
+        }
+    }
+
+    /// Takes stdout as bytes, without copying it
+    pub fn into_stdout_bytes(self) -> Option<Vec<u8>> {
+        self.stdout.map(String::into_bytes)
+    }
+
+    /// Takes stderr as bytes, without copying it
+    pub fn into_stderr_bytes(self) -> Option<Vec<u8>> {
+        self.stderr.map(String::into_bytes)
+    }
+
+    /// Where git's stdout stopped being valid UTF-8, for a `FailureKind::InvalidUtf8` failure - `None` for any other
+    /// 
+    /// A `Success` has no equivalent, as its stdout is a `String`, so is always valid - set `OutputEncoding::Strict`
+    /// with `Git::encoding` to fail rather than substitute `U+FFFD`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Git, OutputEncoding};
+    /// let output = Git::new(vec!["show", "HEAD:legacy.txt"]).encoding(OutputEncoding::Strict).run();
+    /// if let Some(err) = output.as_ref().err().and_then(|x| x.utf8_error()) {
+    ///     eprintln!("legacy.txt is only UTF-8 for its first {} bytes", err.valid_up_to());
+    /// }
+    /// ```
+    pub fn utf8_error(&self) -> Option<std::str::Utf8Error> {
+        match &self.kind {
+            FailureKind::InvalidUtf8(bytes) => std::str::from_utf8(bytes).err(),
+            _ => None,
+        }
+    }
+
+    /// Wraps the failure in `Err`
+    pub fn into_result(self) -> Result<Success, Failure> {
+        Err(self)
+    }
+
+    /// Treats the failure as a success, keeping stdout and the code, and dropping stderr and the kind
+    /// 
+    /// For commands whose non-zero exit is expected, such as `diff --exit-code` finding changes
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let output = Git::new(vec!["not-a-command"]).run().unwrap_or_else(|x| x.to_success());
+    /// assert_eq!(output.code, 1);
+    /// ```
+    pub fn to_success(self) -> Success {
+        Success {
+            stdout: self.stdout,
+            code: self.code,
+        }
+    }
+
+    /// shorthand for a `FailureKind::Parse` failure
+    pub(crate) fn parse<T: ToString>(message: T) -> Failure {
+        Failure::new(FailureKind::Parse, message)
+    }
+}
+
+/// IoMode - where one of git's standard streams is connected, for `Git::run_interactive` and `Git::run_inherited`
+/// 
+/// Set with `Git::stdin_mode`, `Git::stdout_mode` and `Git::stderr_mode`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoMode {
+    /// shared with the parent, such as its terminal
+    Inherit,
+
+    /// captured into the `Success` or `Failure` - for stdin, fed the bytes given to `Git::stdin`, if any, then closed
+    Piped,
+
+    /// connected to nothing - reads get end of file straight away, and writes are discarded
+    Null,
+
+    /// read from, or for stdout and stderr written to, the file at this path
+    File(PathBuf),
+}
+
+impl IoMode {
+    /// the `Stdio` for this mode - `output` is whether git writes to the stream
+    fn stdio(&self, output: bool) -> Result<Stdio, Failure> {
+        Ok(match self {
+            IoMode::Inherit => Stdio::inherit(),
+            IoMode::Piped => Stdio::piped(),
+            IoMode::Null => Stdio::null(),
+            IoMode::File(path) => {
+                let file = if output {
+                    std::fs::File::create(path)
+                } else {
+                    std::fs::File::open(path)
+                };
+                let file = file.map_err(|err| {
+                    Failure::new(FailureKind::Io, format!("failed to open {}: {}", path.display(), err))
+                })?;
+                Stdio::from(file)
+            }
+        })
+    }
 }
 
 /// git struct - the core of `rsgit`
@@ -69,7 +476,20 @@ pub struct Failure {
 /// to initialize, see `Git::new`
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Git {
-    command: Vec<String>,
+    global_args: Vec<OsString>,
+    command: Vec<OsString>,
+    dir: Option<PathBuf>,
+    envs: Vec<(OsString, OsString)>,
+    stdin: Option<Vec<u8>>,
+    quiet: bool,
+    interactive: bool,
+    dry_run: bool,
+    process_group: bool,
+    encoding: OutputEncoding,
+    max_output: Option<usize>,
+    io: [Option<IoMode>; 3],
+    on_exec: Option<trace::OnExec>,
+    executor: Option<executor::ExecutorHandle>,
 }
 
 impl Git {
@@ -87,10 +507,419 @@ impl Git {
     where
         T: IntoIterator,
         T::Item: ToString, 
+    {
+        Git::new_os(items.into_iter().map(|x| x.to_string()))
+    }
+
+    /// Creates a new instance of the Git structure from `OsStr`-like items, such as paths
+    /// 
+    /// Unlike `new`, arguments which are not valid UTF-8 are passed to git unchanged
+    /// # Examples
+    /// ```rust
+    /// use std::path::Path;
+    /// use rsgit::Git;
+    /// let cmd = Git::new_os([Path::new("log"), Path::new("--"), Path::new("src/lib.rs")]);
+    /// ```
+    pub fn new_os<T>(items: T) -> Git
+    where
+        T: IntoIterator,
+        T::Item: AsRef<OsStr>,
     {
         Git {
-            command: items.into_iter().map(|x| x.to_string()).collect(),
+            global_args: Vec::new(),
+            command: items.into_iter().map(|x| x.as_ref().to_os_string()).collect(),
+            dir: None,
+            envs: Vec::new(),
+            stdin: None,
+            quiet: false,
+            interactive: false,
+            dry_run: false,
+            process_group: false,
+            encoding: OutputEncoding::Lossy,
+            max_output: None,
+            io: [None, None, None],
+            on_exec: None,
+            executor: None,
+        }
+    }
+
+    /// Appends a single argument to the command
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["log"]).arg("--oneline");
+    /// ```
+    pub fn arg<T: ToString>(mut self, arg: T) -> Git {
+        self.command.push(arg.to_string().into());
+        self
+    }
+
+    /// Appends several arguments to the command
+    pub fn args<T>(mut self, items: T) -> Git
+    where
+        T: IntoIterator,
+        T::Item: ToString,
+    {
+        self.command.extend(items.into_iter().map(|x| x.to_string().into()));
+        self
+    }
+
+    /// Appends a single argument which may not be valid UTF-8, such as a path, unchanged
+    /// # Examples
+    /// ```rust
+    /// use std::path::Path;
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["add", "--"]).arg_os(Path::new("src/lib.rs"));
+    /// ```
+    pub fn arg_os<T: AsRef<OsStr>>(mut self, arg: T) -> Git {
+        self.command.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Appends several arguments which may not be valid UTF-8, unchanged
+    pub fn args_os<T>(mut self, items: T) -> Git
+    where
+        T: IntoIterator,
+        T::Item: AsRef<OsStr>,
+    {
+        self.command.extend(items.into_iter().map(|x| x.as_ref().to_os_string()));
+        self
+    }
+
+    /// Adds a global option, such as `--no-pager` or `--git-dir=...`, which goes before the subcommand
+    /// 
+    /// Global options are kept in the order they are added, ahead of every argument given to `new` or `arg`
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["log", "-1"]).with_global_arg("--no-pager");
+    /// assert_eq!(cmd.to_command_string(), "git --no-pager log -1");
+    /// ```
+    pub fn with_global_arg<T: Into<OsString>>(mut self, arg: T) -> Git {
+        self.global_args.push(arg.into());
+        self
+    }
+
+    /// Sets the config `key` to `value` for this command only, with a global `-c key=value`
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["commit", "-m", "wip"]).with_global_config("commit.gpgSign", "false");
+    /// assert_eq!(cmd.to_command_string(), "git -c commit.gpgSign=false commit -m wip");
+    /// ```
+    pub fn with_global_config(self, key: &str, value: &str) -> Git {
+        self.with_global_arg("-c").with_global_arg(format!("{}={}", key, value))
+    }
+
+    /// Appends a pathspec, after a `--` separator
+    /// 
+    /// The separator is only added once, so several pathspecs can be chained
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{Git, PathSpec};
+    /// let cmd = Git::new(vec!["ls-files"])
+    ///     .with_pathspec(PathSpec::new("*.rs").glob())
+    ///     .with_pathspec(PathSpec::new("target").exclude());
+    /// ```
+    pub fn with_pathspec(mut self, spec: PathSpec) -> Git {
+        if !self.command.iter().any(|x| x == "--") {
+            self.command.push("--".into());
+        }
+        self.command.push(spec.to_string().into());
+        self
+    }
+
+    /// The version of the `git` on `PATH`, as `(major, minor, patch)`
+    /// 
+    /// git is only run the first time, and the version kept for later calls - see `Repo::git_version` for the git
+    /// a repository's executor runs
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let version = Git::installed_version().unwrap();
+    /// assert!(version >= (2, 0, 0));
+    /// ```
+    pub fn installed_version() -> Result<(u32, u32, u32), Failure> {
+        version::installed_version()
+    }
+
+    /// Sets the working directory git is run in
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["status"]).current_dir("/tmp");
+    /// ```
+    pub fn current_dir<P: AsRef<Path>>(mut self, dir: P) -> Git {
+        self.dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets an environment variable for the git process
+    pub fn env<K, V>(mut self, key: K, value: V) -> Git
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Runs git against the repository at `path`, by setting `GIT_DIR` - see `Repo::open_bare` for a `Repo`
+    /// 
+    /// A relative path is relative to the working directory, as set by `current_dir`
+    pub fn git_dir<P: AsRef<Path>>(self, path: P) -> Git {
+        self.env("GIT_DIR", path.as_ref())
+    }
+
+    /// Uses `path` as the working tree, by setting `GIT_WORK_TREE` - usually alongside `git_dir`
+    pub fn work_tree<P: AsRef<Path>>(self, path: P) -> Git {
+        self.env("GIT_WORK_TREE", path.as_ref())
+    }
+
+    /// Uses `path` as the index in place of `.git/index`, by setting `GIT_INDEX_FILE`
+    /// 
+    /// For staging and building trees without disturbing what the user has staged
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// // a tree of just one file, leaving the real index alone
+    /// Git::new(vec!["add", "README.md"]).index_file("/tmp/scratch-index").run().unwrap();
+    /// let tree = Git::new(vec!["write-tree"]).index_file("/tmp/scratch-index").run().unwrap();
+    /// ```
+    pub fn index_file<P: AsRef<Path>>(self, path: P) -> Git {
+        self.env("GIT_INDEX_FILE", path.as_ref())
+    }
+
+    /// Makes git fail straight away when it needs credentials it doesn't have, rather than prompting for them
+    /// 
+    /// Sets `GIT_TERMINAL_PROMPT=0`, points `GIT_ASKPASS` at `false`, and stops Git Credential Manager opening a
+    /// window with `GCM_INTERACTIVE=never`. Credentials from a configured helper are still used
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// // in CI, a private remote is an error, not a hang
+    /// let output = Git::new(vec!["fetch", "origin"]).no_prompt().run();
+    /// ```
+    pub fn no_prompt(self) -> Git {
+        self.env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_ASKPASS", FALSE)
+            .env("GCM_INTERACTIVE", "never")
+    }
+
+    /// Feeds the given bytes to git's stdin
+    /// 
+    /// stdin is closed once everything has been written
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["hash-object", "--stdin"]).stdin("hello");
+    /// ```
+    pub fn stdin<T: AsRef<[u8]>>(mut self, data: T) -> Git {
+        self.stdin = Some(data.as_ref().to_vec());
+        self
+    }
+
+    /// Discards stderr in `stream` and `reader`, rather than inheriting it - stdout is still shown
+    /// 
+    /// Has no effect on `run`, which captures stderr anyway
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let output = Git::new(vec!["fetch"]).quiet(true).stream();
+    /// ```
+    pub fn quiet(mut self, quiet: bool) -> Git {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Makes `run` and `run_bytes` behave like `stream`, inheriting stdin, stdout and stderr from the parent
+    /// 
+    /// For commands which need a terminal, such as `rebase -i` or a credential prompt - capturing their output would
+    /// leave them waiting for input which never comes. Nothing is captured, so `stdout` is `None` on success, and
+    /// `run_bytes` returns no bytes. The executor is not used, as for `stream`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let output = Git::new(vec!["rebase", "-i", "HEAD~3"]).interactive().run();
+    /// ```
+    pub fn interactive(mut self) -> Git {
+        self.interactive = true;
+        self
+    }
+
+    /// Connects git's stdin as `mode` in `run_interactive` and `run_inherited`, in place of their default
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{Git, IoMode};
+    /// // fails straight away if git wants input, rather than waiting for the user
+    /// let output = Git::new(vec!["--version"]).stdin_mode(IoMode::Null).run_interactive();
+    /// ```
+    pub fn stdin_mode(mut self, mode: IoMode) -> Git {
+        self.io[0] = Some(mode);
+        self
+    }
+
+    /// Connects git's stdout as `mode` in `run_interactive` and `run_inherited`, in place of their default
+    pub fn stdout_mode(mut self, mode: IoMode) -> Git {
+        self.io[1] = Some(mode);
+        self
+    }
+
+    /// Connects git's stderr as `mode` in `run_interactive` and `run_inherited`, in place of their default
+    pub fn stderr_mode(mut self, mode: IoMode) -> Git {
+        self.io[2] = Some(mode);
+        self
+    }
+
+    /// Makes `run`, `run_bytes`, `stream`, `tee_to` and `run_io` skip running git, succeeding with the command that
+    /// would have run as stdout, as rendered by `to_command_string`
+    /// 
+    /// For showing what a destructive command would do before doing it.
+    /// 
+    /// **`reader` and `spawn` ignore this, and always run git** - as does anything built on them, such as
+    /// `Repo::rev_list`. Only set this on a `Git` which is then run with one of the methods above
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let output = Git::new(vec!["reset", "--hard", "origin/main"]).dry_run(true).run();
+    /// assert_eq!(output.stdout().unwrap(), "git reset --hard origin/main");
+    /// ```
+    pub fn dry_run(mut self, dry_run: bool) -> Git {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Renders the command as it would be typed into a POSIX shell - `git` then each argument, quoted where needed
+    /// 
+    /// The working directory and environment are not included, and arguments which are not valid UTF-8 are shown
+    /// lossily
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let cmd = Git::new(vec!["commit", "-m", "it's done"]);
+    /// assert_eq!(cmd.to_command_string(), "git commit -m 'it'\\''s done'");
+    /// ```
+    pub fn to_command_string(&self) -> String {
+        std::iter::once("git".into())
+            .chain(self.argv().iter().map(|x| x.to_string_lossy().into_owned()))
+            .map(|x| shell_quote(&x))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// The program which is run, without any arguments - for prefixing log lines
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// assert_eq!(Git::new(vec!["status"]).to_argv0(), "git");
+    /// ```
+    pub fn to_argv0(&self) -> OsString {
+        OsString::from("git")
+    }
+
+    /// Starts git in a process group of its own, so `RunningGit::kill_group` can kill it along with everything it
+    /// starts, such as `ssh` or a `git-remote-https` helper
+    /// 
+    /// Applies to `spawn`, `stream` and `reader` - `run` has no handle to kill, so is unaffected. On unix this is
+    /// `setpgid`, so git no longer gets the terminal's Ctrl-C. On Windows it is `CREATE_NEW_PROCESS_GROUP`
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::{Duration, Instant};
+    /// use rsgit::Git;
+    /// let mut running = Git::new(vec!["clone", "https://example.com/big.git"])
+    ///     .with_process_group()
+    ///     .spawn()
+    ///     .unwrap();
+    /// let start = Instant::now();
+    /// while !running.try_wait().unwrap() {
+    ///     if start.elapsed() > Duration::from_secs(600) {
+    ///         running.kill_group().unwrap();
+    ///     }
+    ///     std::thread::sleep(Duration::from_secs(1));
+    /// }
+    /// ```
+    pub fn with_process_group(mut self) -> Git {
+        self.process_group = true;
+        self
+    }
+
+    /// Sets how `run` decodes stdout - `OutputEncoding::Lossy` unless set
+    /// 
+    /// `run_bytes` always returns the raw bytes, whatever the encoding
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{FailureKind, Git, OutputEncoding};
+    /// let output = Git::new(vec!["log", "-1", "--format=%B"]).encoding(OutputEncoding::Strict).run();
+    /// if let Err(failure) = output {
+    ///     if let FailureKind::InvalidUtf8(bytes) = failure.kind {
+    ///         println!("the message isn't UTF-8: {:?}", bytes);
+    ///     }
+    /// }
+    /// ```
+    pub fn encoding(mut self, encoding: OutputEncoding) -> Git {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Caps the output `run` and `run_bytes` hold, stdout and stderr together, at `bytes` - unlimited unless set
+    /// 
+    /// Once git writes more, it is killed, and the command fails with `FailureKind::OutputLimitExceeded`, holding
+    /// the output up to the cap. Other executors are only passed the cap, in `ExecContext::max_output`
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{FailureKind, Git};
+    /// let output = Git::new(vec!["--version"]).max_output(4).run();
+    /// let failure = output.unwrap_err();
+    /// assert_eq!(failure.kind, FailureKind::OutputLimitExceeded(4));
+    /// assert_eq!(failure.stdout.unwrap(), "git ");
+    /// ```
+    pub fn max_output(mut self, bytes: usize) -> Git {
+        self.max_output = Some(bytes);
+        self
+    }
+
+    /// Runs `run` and `run_bytes` through `executor`, rather than starting git directly
+    /// 
+    /// `stream` and `reader` are unaffected, and always start git
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use rsgit::{Git, SystemExecutor};
+    /// let cmd = Git::new(vec!["status"]).with_executor(Arc::new(SystemExecutor));
+    /// ```
+    pub fn with_executor(mut self, executor: Arc<dyn Executor>) -> Git {
+        self.executor = Some(executor::ExecutorHandle(executor));
+        self
+    }
+
+    /// the global options, then the subcommand and its arguments
+    fn argv(&self) -> Vec<OsString> {
+        self.global_args.iter().chain(&self.command).cloned().collect()
+    }
+
+    /// the working directory, environment and stdin, as passed to an `Executor`
+    fn context(&self) -> ExecContext {
+        ExecContext {
+            dir: self.dir.clone(),
+            envs: self.envs.clone(),
+            stdin: self.stdin.clone(),
+            encoding: self.encoding,
+            max_output: self.max_output,
+        }
+    }
+
+    /// builds the `Command` - shared by `stream`, `reader` and `spawn`
+    fn command(&self) -> Command {
+        let mut out = executor::command("git", &self.argv(), &self.context());
+        if self.process_group {
+            new_process_group(&mut out);
         }
+        out
+    }
+
+    /// writes the stdin buffer on its own thread, so a full stdout pipe can't deadlock us
+    fn feed_stdin(&self, child: &mut std::process::Child) {
+        executor::feed_stdin(&self.context(), child);
     }
 
 
@@ -107,24 +936,36 @@ impl Git {
     /// println!("git log --shortstat returned code {}", output.code());
     /// ```
     pub fn stream(&self) -> Result<Success, Failure> {
-        let mut out = Command::new("git");
-        for argument in self.command.clone() {
-            out.arg(argument);
+        if self.dry_run {
+            return self.run();
         }
-
-        let output = out.status().expect("Failed to execute `git`");
-        if output.success() {
-            Ok(Success {
-                stdout: None, 
-                code: output.code().unwrap_or(0)
-            })
-        } else {
-            Err(Failure{
-                stderr: None,
-                stdout: None,
-                code: output.code().unwrap_or(1)
-            })
+        let mut out = self.command();
+        if self.stdin.is_some() {
+            out.stdin(Stdio::piped());
+        }
+        if self.quiet {
+            out.stderr(Stdio::null());
         }
+
+        self.traced(|| {
+            let mut child = out.spawn().expect("Failed to execute `git`");
+            self.feed_stdin(&mut child);
+            let output = child.wait().expect("Failed to execute `git`");
+            let (code, kind) = exit_code(output);
+            if output.success() {
+                Ok((Success {
+                    stdout: None, 
+                    code,
+                }, 0))
+            } else {
+                Err(Failure{
+                    stderr: None,
+                    stdout: None,
+                    code,
+                    kind,
+                })
+            }
+        })
     }
 
     /// Runs the specified commands, prefixed by `git`
@@ -140,28 +981,681 @@ impl Git {
     /// println!("The output of git log --shortstat was {}", output.stdout().unwrap_or_default());
     /// ```
     pub fn run(&self) -> Result<Success, Failure> {
-        let mut out = Command::new("git");
-        for argument in self.command.clone() {
-            out.arg(argument);
+        if self.dry_run {
+            return Ok(Success {
+                stdout: Some(self.to_command_string()),
+                code: 0,
+            });
+        }
+        if self.interactive {
+            return self.stream();
         }
+        self.traced(|| match &self.executor {
+            // other executors don't hand stderr back on success
+            Some(executor) => executor.0.execute("git", &self.argv(), &self.context()).map(|x| (x, 0)),
+            None => {
+                let output = executor::system_output("git", &self.argv(), &self.context())?;
+                let stderr_len = output.stderr.len();
+                executor::from_output(output, self.encoding).map(|x| (x, stderr_len))
+            }
+        })
+    }
 
-        let output = out.output().expect("Failed to execute `git`");
+    /// Runs the specified commands, prefixed by `git`
+    /// 
+    /// Returns stdout as raw bytes on success, for binary output such as blobs or archives
+    /// 
+    /// On failure, the `Failure` is the same as the one `run` would return
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let bytes = Git::new(vec!["--version"]).run_bytes().unwrap();
+    /// assert!(bytes.starts_with(b"git version"));
+    /// ```
+    pub fn run_bytes(&self) -> Result<Vec<u8>, Failure> {
+        if self.dry_run {
+            return Ok(self.to_command_string().into_bytes());
+        }
+        if self.interactive {
+            return self.stream().map(|_| Vec::new());
+        }
+        // executors deal in strings, so only the system one keeps binary output intact
+        if self.executor.is_some() {
+            let lossy = self.clone().encoding(OutputEncoding::Lossy);
+            return lossy.run().map(|x| x.stdout.unwrap_or_default().into_bytes());
+        }
+        let output = self.output()?;
 
         if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(Failure::from_output(output))
+        }
+    }
+
+    /// Starts the command, returning a reader over its stdout
+    /// 
+    /// Output is read as git produces it, so nothing is buffered in memory - useful for large output such as
+    /// `git archive`. stderr is inherited from the parent, unless `quiet` is set
+    /// 
+    /// Dropping the reader kills git if it is still running, and always reaps it. `dry_run` is ignored - git always
+    /// runs
+    /// # Examples
+    /// ```rust
+    /// use std::io::Read;
+    /// use rsgit::Git;
+    /// let mut reader = Git::new(vec!["--version"]).reader().unwrap();
+    /// let mut output = String::new();
+    /// reader.read_to_string(&mut output).unwrap();
+    /// ```
+    pub fn reader(&self) -> io::Result<GitReader> {
+        let mut out = self.command();
+        out.stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped());
+        if self.quiet {
+            out.stderr(Stdio::null());
+        }
+
+        let mut child = out.spawn()?;
+        self.feed_stdin(&mut child);
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("git stdout was not piped"))?;
+        Ok(GitReader { child, stdout })
+    }
+
+    /// Starts the command, returning a handle to wait for or kill it
+    /// 
+    /// stdout and stderr are captured as `run` does, and the child is killed if the handle is dropped while it is
+    /// still running - see `RunningGit::kill_on_drop`. Like `stream`, this always runs the real git, and `dry_run` is
+    /// ignored
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let mut running = Git::new(vec!["fetch", "--all"]).spawn().unwrap();
+    /// println!("fetching as process {}", running.pid());
+    /// // the user pressed cancel
+    /// running.kill().unwrap();
+    /// assert!(running.wait().is_err());
+    /// ```
+    pub fn spawn(&self) -> Result<RunningGit, Failure> {
+        let mut out = self.command();
+        out.stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = out
+            .spawn()
+            .map_err(|err| Failure::new(FailureKind::Io, format!("failed to run git: {}", err)))?;
+        self.feed_stdin(&mut child);
+        let stdout = child.stdout.take().map(read_to_end);
+        let stderr = child.stderr.take().map(read_to_end);
+        Ok(RunningGit {
+            child,
+            stdout,
+            stderr,
+            killed: false,
+            kill_on_drop: true,
+            process_group: self.process_group,
+        })
+    }
+
+    /// Runs the command, showing its output as it arrives as `stream` does, while also capturing it as `run` does
+    /// 
+    /// stdout and stderr are copied to the parent's a line at a time, so each keeps its own order, though lines of
+    /// one may land between lines of the other. The exit code is handled as for `run`. Like `stream`, this always runs
+    /// the real git
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let pushed = Git::new(vec!["push", "origin", "main"]).tee().unwrap();
+    /// // the user has seen the output, which can still be inspected
+    /// if pushed.stdout.unwrap_or_default().contains("Everything up-to-date") {
+    ///     println!("nothing was pushed");
+    /// }
+    /// ```
+    pub fn tee(&self) -> Result<Success, Failure> {
+        self.tee_to(io::stdout(), io::stderr())
+    }
+
+    /// Runs the command as `tee` does, but copies its stdout and stderr to the given writers, rather than the
+    /// parent's
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Git;
+    /// let mut log = Vec::new();
+    /// let output = Git::new(vec!["--version"]).tee_to(&mut log, std::io::sink()).unwrap();
+    /// assert_eq!(output.stdout.unwrap().as_bytes(), log.as_slice());
+    /// ```
+    pub fn tee_to<O, E>(&self, stdout: O, stderr: E) -> Result<Success, Failure>
+    where
+        O: Write + Send,
+        E: Write + Send,
+    {
+        if self.dry_run {
+            return self.run();
+        }
+        let mut out = self.command();
+        out.stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        self.traced(|| {
+            let mut child = out
+                .spawn()
+                .map_err(|err| Failure::new(FailureKind::Io, format!("failed to run git: {}", err)))?;
+            self.feed_stdin(&mut child);
+            let child_stdout = child.stdout.take();
+            let child_stderr = child.stderr.take();
+
+            let (stdout, stderr) = thread::scope(|scope| {
+                let stdout = scope.spawn(|| copy_lines(child_stdout, stdout));
+                let stderr = scope.spawn(|| copy_lines(child_stderr, stderr));
+                (
+                    stdout.join().unwrap_or_default(),
+                    stderr.join().unwrap_or_default(),
+                )
+            });
+            let status = child
+                .wait()
+                .map_err(|err| Failure::new(FailureKind::Io, format!("failed to wait for git: {}", err)))?;
+
+            if status.success() {
+                let success = Success {
+                    stdout: executor::decode(stdout, self.encoding)?,
+                    code: exit_code(status).0,
+                };
+                Ok((success, stderr.len()))
+            } else {
+                Err(Failure::from_output(Output { status, stdout, stderr }))
+            }
+        })
+    }
+
+    /// Runs the command with stdin and stderr shared with the parent, but stdout captured
+    /// 
+    /// For commands which talk to the user, such as `commit` opening an editor, `add -p` or a credential prompt,
+    /// where the final output is still wanted. Each stream can be changed with `stdin_mode`, `stdout_mode` and
+    /// `stderr_mode` - `Git::stdin` data is fed to stdin if its mode isn't set. Like `stream`, this always runs the
+    /// real git
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let output = Git::new(vec!["commit"]).run_interactive().unwrap();
+    /// println!("{}", output.stdout.unwrap_or_default());
+    /// ```
+    pub fn run_interactive(&self) -> Result<Success, Failure> {
+        self.run_io([IoMode::Inherit, IoMode::Piped, IoMode::Inherit])
+    }
+
+    /// Runs the command with stdin, stdout and stderr all shared with the parent, as `stream` does, but with each
+    /// open to change by `stdin_mode`, `stdout_mode` and `stderr_mode`
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::path::PathBuf;
+    /// use rsgit::{Git, IoMode};
+    /// let output = Git::new(vec!["am"])
+    ///     .stdin_mode(IoMode::File(PathBuf::from("fix.patch")))
+    ///     .run_inherited();
+    /// ```
+    pub fn run_inherited(&self) -> Result<Success, Failure> {
+        self.run_io([IoMode::Inherit, IoMode::Inherit, IoMode::Inherit])
+    }
+
+    /// runs with each of stdin, stdout and stderr connected as set, or as `defaults` if not
+    fn run_io(&self, defaults: [IoMode; 3]) -> Result<Success, Failure> {
+        if self.dry_run {
+            return self.run();
+        }
+        let [stdin_default, stdout_default, stderr_default] = defaults;
+        let stdin_default = if self.stdin.is_some() { IoMode::Piped } else { stdin_default };
+        let stdin = self.io[0].clone().unwrap_or(stdin_default);
+        let stdout = self.io[1].clone().unwrap_or(stdout_default);
+        let stderr = self.io[2].clone().unwrap_or(stderr_default);
+
+        let mut out = self.command();
+        out.stdin(stdin.stdio(false)?)
+            .stdout(stdout.stdio(true)?)
+            .stderr(stderr.stdio(true)?);
+
+        self.traced(|| {
+            let mut child = out
+                .spawn()
+                .map_err(|err| Failure::new(FailureKind::Io, format!("failed to run git: {}", err)))?;
+            self.feed_stdin(&mut child);
+            // piped with nothing to feed, so close it
+            drop(child.stdin.take());
+            let child_stdout = child.stdout.take().map(read_to_end);
+            let child_stderr = child.stderr.take().map(read_to_end);
+            let status = child
+                .wait()
+                .map_err(|err| Failure::new(FailureKind::Io, format!("failed to wait for git: {}", err)))?;
+
+            let collect = |pipe: Option<JoinHandle<Vec<u8>>>| pipe.map(|x| x.join().unwrap_or_default());
+            let (stdout, stderr) = (collect(child_stdout), collect(child_stderr));
+            let (code, kind) = exit_code(status);
+            if status.success() {
+                let stdout = match stdout {
+                    Some(stdout) => executor::decode(stdout, self.encoding)?,
+                    None => None,
+                };
+                Ok((Success { stdout, code }, stderr.map_or(0, |x| x.len())))
+            } else {
+                Err(Failure {
+                    stderr: stderr.map(executor::lossy),
+                    stdout: stdout.map(executor::lossy),
+                    code,
+                    kind,
+                })
+            }
+        })
+    }
+
+    /// Runs every command with `run`, at most `parallelism` at a time, returning the results in the same order as
+    /// `commands`
+    /// 
+    /// A `parallelism` of 0 is treated as 1
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let commands = vec![Git::new(vec!["--version"]), Git::new(vec!["not-a-command"])];
+    /// let results = Git::run_all(commands, 4);
+    /// assert!(!results[0].failed());
+    /// assert!(results[1].failed());
+    /// ```
+    pub fn run_all(commands: Vec<Git>, parallelism: usize) -> Vec<Result<Success, Failure>> {
+        Git::run_all_with(commands, parallelism, |_, _| {})
+    }
+
+    /// Runs every command as `run_all` does, calling `on_done` with the index and result of each as it finishes
+    /// 
+    /// `on_done` is called on the calling thread, in the order commands finish rather than the order given
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{IsFailure, Git};
+    /// let commands = vec![Git::new(vec!["--version"]); 8];
+    /// let total = commands.len();
+    /// let mut finished = 0;
+    /// Git::run_all_with(commands, 2, |index, result| {
+    ///     finished += 1;
+    ///     println!("[{}/{}] command {} exited with {}", finished, total, index, result.code());
+    /// });
+    /// ```
+    pub fn run_all_with<F>(commands: Vec<Git>, parallelism: usize, mut on_done: F) -> Vec<Result<Success, Failure>>
+    where
+        F: FnMut(usize, &Result<Success, Failure>),
+    {
+        let workers = parallelism.max(1).min(commands.len());
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<Option<Result<Success, Failure>>> = vec![None; commands.len()];
+
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let (commands, next) = (&commands, &next);
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(command) = commands.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, command.run())).is_err() {
+                        break;
+                    }
+                });
+            }
+            // the workers hold the only senders left, so this ends once they all have
+            drop(sender);
+
+            for (index, result) in receiver {
+                on_done(index, &result);
+                results[index] = Some(result);
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|x| x.expect("every command is run exactly once"))
+            .collect()
+    }
+
+    /// runs to completion with stdout and stderr captured, whatever the exit code
+    /// 
+    /// only fails if the output passes `max_output`
+    pub(crate) fn output(&self) -> Result<Output, Failure> {
+        if self.dry_run {
+            return executor::into_output(Ok(Success {
+                stdout: Some(self.to_command_string()),
+                code: 0,
+            }));
+        }
+        let ctx = self.context();
+        let trace = self.trace();
+        let output = match &self.executor {
+            Some(executor) => {
+                let lossy = ExecContext {
+                    encoding: OutputEncoding::Lossy,
+                    ..ctx
+                };
+                match executor.0.execute("git", &self.argv(), &lossy) {
+                    Err(failure) if matches!(failure.kind, FailureKind::OutputLimitExceeded(_)) => Err(failure),
+                    result => executor::into_output(result),
+                }
+            }
+            None => executor::system_output("git", &self.argv(), &ctx),
+        };
+        if let Some(trace) = trace {
+            match &output {
+                Ok(output) => trace.finish(exit_code(output.status).0, output.stderr.len()),
+                Err(failure) => trace.finish(failure.code, failure.stderr.as_ref().map_or(0, |x| x.len())),
+            }
+        }
+        output
+    }
+}
+
+/// A reader over the stdout of a running git process - see `Git::reader`
+#[derive(Debug)]
+pub struct GitReader {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl GitReader {
+    /// waits for git to exit - stdout should be read to the end first, or git may block on it
+    pub(crate) fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.child.wait()
+    }
+}
+
+impl Read for GitReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for GitReader {
+    fn drop(&mut self) {
+        // killing an exited process only errors, so this is safe either way
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// renders the command with `to_command_string`
+impl fmt::Display for Git {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_command_string())
+    }
+}
+
+/// Builds the `Command` git would be run with - program, arguments, working directory, environment and process
+/// group - without running it
+/// 
+/// An escape hatch for anything `Git` doesn't expose. stdio is left at `Command`'s defaults, so any `Git::stdin` data
+/// is not carried over, and the executor is ignored
+/// # Examples
+/// ```rust
+/// use std::process::{Command, Stdio};
+/// use rsgit::Git;
+/// let mut cmd = Command::from(&Git::new(vec!["--version"]));
+/// let output = cmd.stderr(Stdio::null()).output().unwrap();
+/// assert!(output.stdout.starts_with(b"git version"));
+/// ```
+impl From<&Git> for Command {
+    fn from(git: &Git) -> Command {
+        git.command()
+    }
+}
+
+/// see `From<&Git>`
+impl From<Git> for Command {
+    fn from(git: Git) -> Command {
+        git.command()
+    }
+}
+
+/// quotes `arg` for a POSIX shell - left alone if it is only safe characters, otherwise in single quotes, with any
+/// single quote inside written as `'\''`
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// reads a pipe to the end on its own thread, so neither stdout nor stderr can fill up and block git
+fn read_to_end<R: Read + Send + 'static>(mut pipe: R) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// copies `pipe` to `to` a line at a time as it arrives, returning everything copied
+/// 
+/// A failed write stops the copying, but not the reading, so git never blocks on a full pipe
+fn copy_lines<R: Read, W: Write>(pipe: Option<R>, mut to: W) -> Vec<u8> {
+    let Some(pipe) = pipe else {
+        return Vec::new();
+    };
+    let mut pipe = io::BufReader::new(pipe);
+    let mut all = Vec::new();
+    let mut copying = true;
+    loop {
+        let start = all.len();
+        match pipe.read_until(b'\n', &mut all) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                if copying {
+                    copying = to.write_all(&all[start..]).and_then(|_| to.flush()).is_ok();
+                }
+            }
+        }
+    }
+    all
+}
+
+/// A git process started by `Git::spawn`
+/// 
+/// Dropping the handle while git is still running kills and reaps it, unless `kill_on_drop(false)` was set, in
+/// which case git is left to finish on its own. With `Git::with_process_group`, its whole group is killed
+#[derive(Debug)]
+pub struct RunningGit {
+    child: Child,
+    stdout: Option<JoinHandle<Vec<u8>>>,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+    killed: bool,
+    kill_on_drop: bool,
+    process_group: bool,
+}
+
+impl RunningGit {
+    /// the process id of git
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Sets whether dropping the handle kills git if it is still running - on by default
+    pub fn kill_on_drop(mut self, kill: bool) -> RunningGit {
+        self.kill_on_drop = kill;
+        self
+    }
+
+    /// Checks whether git has exited, without blocking
+    pub fn try_wait(&mut self) -> Result<bool, Failure> {
+        self.child
+            .try_wait()
+            .map(|status| status.is_some())
+            .map_err(|err| Failure::new(FailureKind::Io, format!("failed to check on git: {}", err)))
+    }
+
+    /// Kills git and reaps it, so `wait` then returns straight away with a `FailureKind::Killed` failure
+    /// 
+    /// Killing a process which has already exited does nothing
+    pub fn kill(&mut self) -> Result<(), Failure> {
+        if self.try_wait()? {
+            return Ok(());
+        }
+        self.child
+            .kill()
+            .and_then(|_| self.child.wait())
+            .map_err(|err| Failure::new(FailureKind::Io, format!("failed to kill git: {}", err)))?;
+        self.killed = true;
+        Ok(())
+    }
+
+    /// Kills git and every process it started, then reaps git - see `Git::with_process_group`
+    /// 
+    /// On unix, the group is sent `SIGKILL`. On Windows, the process tree is terminated with `taskkill /T /F`, as
+    /// there are no signals. Without `with_process_group`, this is the same as `kill`
+    pub fn kill_group(&mut self) -> Result<(), Failure> {
+        if !self.process_group {
+            return self.kill();
+        }
+        if self.try_wait()? {
+            return Ok(());
+        }
+        kill_process_group(self.child.id())
+            .and_then(|_| self.child.wait())
+            .map_err(|err| Failure::new(FailureKind::Io, format!("failed to kill git: {}", err)))?;
+        self.killed = true;
+        Ok(())
+    }
+
+    /// Waits for git to exit, returning its output as `run` would
+    /// 
+    /// If git was killed, the `Failure` has `FailureKind::Killed`, and on unix a code of `128 + signal`
+    pub fn wait(mut self) -> Result<Success, Failure> {
+        let status = self
+            .child
+            .wait()
+            .map_err(|err| Failure::new(FailureKind::Io, format!("failed to wait for git: {}", err)))?;
+
+        let (code, kind) = exit_code(status);
+        if self.killed || kind == FailureKind::Killed {
+            // anything git started may still hold the pipes open, so don't wait on them
+            return Err(Failure {
+                stderr: None,
+                stdout: None,
+                code,
+                kind: FailureKind::Killed,
+            });
+        }
+
+        let collect = |pipe: Option<JoinHandle<Vec<u8>>>| {
+            pipe.and_then(|x| x.join().ok()).unwrap_or_default()
+        };
+        let output = Output {
+            status,
+            stdout: collect(self.stdout.take()),
+            stderr: collect(self.stderr.take()),
+        };
+        if status.success() {
             Ok(Success {
-                stdout: Some(String::from_utf8(output.stdout).unwrap_or("".to_string())),
-                code: output.status.code().unwrap_or(0)
+                stdout: Some(executor::lossy(output.stdout)),
+                code,
             })
         } else {
-            Err(Failure {
-                stderr: Some(String::from_utf8(output.stderr).unwrap_or("".to_string())),
-                stdout: Some(String::from_utf8(output.stdout).unwrap_or("".to_string())),
-                code: output.status.code().unwrap_or(1)
-            })
+            Err(Failure::from_output(output))
+        }
+    }
+}
+
+impl Drop for RunningGit {
+    fn drop(&mut self) {
+        if self.kill_on_drop && !self.killed {
+            let _ = self.kill_group();
+            let _ = self.child.kill();
+            let _ = self.child.wait();
         }
     }
 }
 
+/// a program which always fails, for `GIT_ASKPASS` in `Git::no_prompt` - Git for Windows finds its own on `PATH`, and
+/// if it can't, the failure to run it is just as good
+#[cfg(unix)]
+const FALSE: &str = "/bin/false";
+#[cfg(not(unix))]
+const FALSE: &str = "false";
+
+#[cfg(unix)]
+fn new_process_group(cmd: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    cmd.process_group(0);
+}
+
+#[cfg(windows)]
+fn new_process_group(cmd: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn new_process_group(_: &mut Command) {}
+
+/// sends `SIGKILL` to the process group `pid` leads
+#[cfg(unix)]
+fn kill_process_group(pid: u32) -> io::Result<()> {
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    const SIGKILL: i32 = 9;
+
+    let group = i32::try_from(pid).map_err(io::Error::other)?;
+    // a negative pid signals every process in that group. Safety: `kill` takes no pointers
+    if unsafe { kill(-group, SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// terminates `pid` and all its descendants
+#[cfg(windows)]
+fn kill_process_group(pid: u32) -> io::Result<()> {
+    let status = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("taskkill exited with {}", status)))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn kill_process_group(_: u32) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "process groups are not supported"))
+}
+
+/// the exit code, and whether git exited or was killed
+/// 
+/// On unix, a process ended by a signal has no code, so it gets `128 + signal` as a shell would give it. A process
+/// stopped rather than ended has neither, which only `waitpid` with `WUNTRACED` reports, so `-1` is never expected
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> (i32, FailureKind) {
+    use std::os::unix::process::ExitStatusExt;
+    match (status.code(), status.signal()) {
+        (Some(code), _) => (code, FailureKind::Exit),
+        (None, Some(signal)) => (128 + signal, FailureKind::Killed),
+        (None, None) => (-1, FailureKind::Killed),
+    }
+}
+
+/// the exit code - there are no signals, and a killed process exits with the code it was given
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> (i32, FailureKind) {
+    (status.code().unwrap_or(1), FailureKind::Exit)
+}
+
 pub trait Run {
     /// run a command straight from a object
     fn run(self) -> Result<Success, Failure>;
@@ -0,0 +1,180 @@
+use crate::{Failure, Repo};
+
+/// options for `Repo::describe`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DescribeOptions {
+    /// consider lightweight tags too, not only annotated ones (`--tags`)
+    pub tags: bool,
+
+    /// consider any ref, such as branches, not only tags (`--all`)
+    pub all: bool,
+
+    /// fall back to the abbreviated SHA when no tag is found, rather than failing (`--always`)
+    pub always: bool,
+
+    /// always give the `-<count>-g<sha>` suffix, even exactly on a tag (`--long`)
+    pub long: bool,
+
+    /// abbreviate the SHA to this many hex digits, or leave it out with 0 (`--abbrev`)
+    pub abbrev: Option<usize>,
+
+    /// only consider tags matching this glob, eg `v*` (`--match`)
+    pub match_pattern: Option<String>,
+
+    /// ignore tags matching this glob, eg `*-rc*` (`--exclude`)
+    pub exclude_pattern: Option<String>,
+}
+
+impl Repo {
+    /// Names `HEAD` after the most recent tag reachable from it, eg `v1.2.3-4-gabcdef0` for 4 commits past
+    /// `v1.2.3`, using `describe`
+    /// 
+    /// Fails if no tag is found, unless `always` is set
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{DescribeOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = DescribeOptions { tags: true, always: true, ..Default::default() };
+    /// println!("building {}", repo.describe(opts).unwrap());
+    /// ```
+    pub fn describe(&self, opts: DescribeOptions) -> Result<String, Failure> {
+        let mut cmd = self.git(vec!["describe"]);
+        if opts.tags {
+            cmd = cmd.arg("--tags");
+        }
+        if opts.all {
+            cmd = cmd.arg("--all");
+        }
+        if opts.always {
+            cmd = cmd.arg("--always");
+        }
+        if opts.long {
+            cmd = cmd.arg("--long");
+        }
+        if let Some(abbrev) = opts.abbrev {
+            cmd = cmd.arg(format!("--abbrev={}", abbrev));
+        }
+        if let Some(pattern) = &opts.match_pattern {
+            cmd = cmd.arg(format!("--match={}", pattern));
+        }
+        if let Some(pattern) = &opts.exclude_pattern {
+            cmd = cmd.arg(format!("--exclude={}", pattern));
+        }
+
+        let output = cmd.run()?;
+        Ok(output.stdout.unwrap_or_default().trim().to_string())
+    }
+
+    /// The version of the most recent tag reachable from `HEAD`, eg `1.2.3` from `v1.2.3` or `v1.2.3-4-gabcdef0`
+    /// 
+    /// Lightweight tags are considered too. A leading `v` is dropped, and the rest must be a semantic version - a
+    /// pre-release such as `1.2.3-rc.1` is kept. Only built with the `semver` feature
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let version = repo.describe_version().unwrap();
+    /// println!("cargo:rustc-env=VERSION_MAJOR={}", version.major);
+    /// ```
+    #[cfg(feature = "semver")]
+    pub fn describe_version(&self) -> Result<semver::Version, Failure> {
+        let opts = DescribeOptions {
+            tags: true,
+            long: true,
+            ..Default::default()
+        };
+        let described = self.describe(opts)?;
+        let tag = strip_suffix(&described);
+        let version = tag.strip_prefix('v').unwrap_or(tag);
+        semver::Version::parse(version)
+            .map_err(|err| Failure::parse(format!("{} is not a semantic version: {}", tag, err)))
+    }
+}
+
+/// drops the `-<count>-g<sha>` which `describe --long` adds to the tag
+#[cfg(any(test, feature = "semver"))]
+fn strip_suffix(described: &str) -> &str {
+    let mut parts = described.rsplitn(3, '-');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(sha), Some(count), Some(tag))
+            if sha.starts_with('g') && !count.is_empty() && count.bytes().all(|x| x.is_ascii_digit()) =>
+        {
+            tag
+        }
+        _ => described,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_strip_suffix() {
+        assert_eq!(strip_suffix("v1.2.3-4-gabcdef0"), "v1.2.3");
+        assert_eq!(strip_suffix("v1.2.3-rc.1-0-gabcdef0"), "v1.2.3-rc.1");
+        assert_eq!(strip_suffix("v1.2.3-rc.1"), "v1.2.3-rc.1");
+        assert_eq!(strip_suffix("v1.2.3"), "v1.2.3");
+    }
+
+    #[test]
+    fn test_describe() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let first = fixture.commit_all("initial");
+        assert!(fixture.repo.describe(DescribeOptions::default()).is_err());
+        let always = DescribeOptions {
+            always: true,
+            abbrev: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(fixture.repo.describe(always).unwrap(), first[..12]);
+
+        fixture.repo.git(vec!["tag", "v1.0.0"]).run().unwrap();
+        fixture.repo.git(vec!["tag", "-a", "-m", "release", "release-1"]).run().unwrap();
+        fixture.write("a.txt", "b\n");
+        let second = fixture.commit_all("second");
+
+        // only the annotated tag, without `tags`
+        assert_eq!(
+            fixture.repo.describe(DescribeOptions::default()).unwrap(),
+            format!("release-1-1-g{}", &second[..7])
+        );
+        let lightweight = DescribeOptions {
+            tags: true,
+            match_pattern: Some("v*".to_string()),
+            abbrev: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(fixture.repo.describe(lightweight).unwrap(), "v1.0.0");
+        let excluded = DescribeOptions {
+            tags: true,
+            exclude_pattern: Some("release-*".to_string()),
+            long: true,
+            ..Default::default()
+        };
+        assert!(fixture.repo.describe(excluded).unwrap().starts_with("v1.0.0-1-g"));
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn test_describe_version() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        fixture.repo.git(vec!["tag", "v1.2.3"]).run().unwrap();
+        assert_eq!(fixture.repo.describe_version().unwrap(), semver::Version::new(1, 2, 3));
+
+        fixture.write("a.txt", "b\n");
+        fixture.commit_all("second");
+        fixture.repo.git(vec!["tag", "2.0.0-rc.1"]).run().unwrap();
+        fixture.write("a.txt", "c\n");
+        fixture.commit_all("third");
+        let version = fixture.repo.describe_version().unwrap();
+        assert_eq!(version, semver::Version::parse("2.0.0-rc.1").unwrap());
+
+        fixture.repo.git(vec!["tag", "nightly"]).run().unwrap();
+        assert!(fixture.repo.describe_version().is_err());
+    }
+}
@@ -0,0 +1,662 @@
+use std::path::{Path, PathBuf};
+
+use crate::clean::unquote;
+use crate::{Failure, Repo};
+
+/// which two trees `Repo::diff_stat` and `Repo::diff_patch` compare
+/// 
+/// with no commits, the working tree is compared with the index, or the index with `HEAD` if `staged` is set. With
+/// only `from_commit`, the working tree (or index) is compared with that commit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffStatOptions {
+    pub from_commit: Option<String>,
+    pub to_commit: Option<String>,
+
+    /// compare the index rather than the working tree (`--cached`)
+    pub staged: bool,
+}
+
+/// per-file line counts from `Repo::diff_stat`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChangeStat {
+    /// the path after the change - the new name for renames
+    pub path: PathBuf,
+    /// lines added, `None` for binary files
+    pub insertions: Option<usize>,
+    /// lines removed, `None` for binary files
+    pub deletions: Option<usize>,
+    pub is_binary: bool,
+}
+
+/// totals over a set of `FileChangeStat`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffSummary {
+    /// Totals `stats`, counting binary files as changed but with no lines
+    pub fn from_stats(stats: &[FileChangeStat]) -> DiffSummary {
+        DiffSummary {
+            files_changed: stats.len(),
+            insertions: DiffSummary::total_insertions(stats),
+            deletions: DiffSummary::total_deletions(stats),
+        }
+    }
+
+    pub fn total_insertions(stats: &[FileChangeStat]) -> usize {
+        stats.iter().filter_map(|x| x.insertions).sum()
+    }
+
+    pub fn total_deletions(stats: &[FileChangeStat]) -> usize {
+        stats.iter().filter_map(|x| x.deletions).sum()
+    }
+}
+
+/// how a file changed - see `Repo::diff_name_status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    /// moved, and maybe edited - see `FileChange::similarity`
+    Renamed,
+    /// copied from another file, and maybe edited - see `FileChange::similarity`
+    Copied,
+    /// the type changed, eg a file became a symlink
+    TypeChanged,
+    /// the path is unmerged
+    Unmerged,
+    /// git could not tell
+    Unknown,
+}
+
+impl ChangeKind {
+    pub(crate) fn parse(letter: char) -> Option<ChangeKind> {
+        match letter {
+            'A' => Some(ChangeKind::Added),
+            'M' => Some(ChangeKind::Modified),
+            'D' => Some(ChangeKind::Deleted),
+            'R' => Some(ChangeKind::Renamed),
+            'C' => Some(ChangeKind::Copied),
+            'T' => Some(ChangeKind::TypeChanged),
+            'U' => Some(ChangeKind::Unmerged),
+            'X' => Some(ChangeKind::Unknown),
+            _ => None,
+        }
+    }
+}
+
+/// a changed file from `Repo::diff_name_status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub status: ChangeKind,
+    /// the path after the change - the new name for renames and copies
+    pub path: PathBuf,
+    /// the path before a rename or copy
+    pub old_path: Option<PathBuf>,
+    /// for renames and copies, how similar the two files are, from 0 to 100
+    pub similarity: Option<u8>,
+}
+
+/// one file's changes from `Repo::diff_patch`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// the path before the change - `None` for an added file
+    pub old_path: Option<PathBuf>,
+    /// the path after the change - `None` for a deleted file
+    pub new_path: Option<PathBuf>,
+    /// git did not diff the contents, so there are no hunks
+    pub is_binary: bool,
+    /// empty for binary files, and for changes only to the mode or name
+    pub hunks: Vec<Hunk>,
+}
+
+/// a run of changed lines, with the context around them, from an `@@ -1,3 +1,4 @@` header on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// a line of a `Hunk`, holding its text with the line ending as it is in the file - so with no `\n` for a last line
+/// marked `\ No newline at end of file`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// unchanged, on both sides
+    Context(String),
+    /// only on the new side
+    Added(String),
+    /// only on the old side
+    Removed(String),
+}
+
+impl DiffLine {
+    /// The line's text, whichever side it is on
+    pub fn text(&self) -> &str {
+        match self {
+            DiffLine::Context(text) | DiffLine::Added(text) | DiffLine::Removed(text) => text,
+        }
+    }
+}
+
+impl Repo {
+    /// Lists the changed files with how each changed, using `diff --name-status -z`
+    /// 
+    /// `range` is anything `diff` takes, eg `HEAD~3`, `main..feature` or `v1.0 v2.0` - with `None`, the working tree is
+    /// compared with the index. Renames are always detected
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{ChangeKind, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// for change in repo.diff_name_status(Some("HEAD~1..HEAD")).unwrap() {
+    ///     if change.status == ChangeKind::Renamed {
+    ///         println!("{:?} -> {}", change.old_path, change.path.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn diff_name_status(&self, range: Option<&str>) -> Result<Vec<FileChange>, Failure> {
+        let mut cmd = self.git(vec!["diff", "--name-status", "-z", "--find-renames", "--no-color"]);
+        cmd = cmd.arg("--end-of-options");
+        if let Some(range) = range {
+            cmd = cmd.args(range.split_whitespace());
+        }
+
+        let output = cmd.arg("--").run()?;
+        parse_name_status(&output.stdout.unwrap_or_default())
+    }
+}
+
+/// `<status>\0<path>\0`, or for renames and copies `<status><score>\0<old>\0<new>\0`
+fn parse_name_status(stdout: &str) -> Result<Vec<FileChange>, Failure> {
+    let mut records = stdout.split('\0');
+    let mut changes = Vec::new();
+    let truncated = || Failure::parse("name-status output was truncated");
+
+    while let Some(status) = records.next() {
+        if status.is_empty() {
+            continue;
+        }
+        let mut letters = status.chars();
+        let kind = letters
+            .next()
+            .and_then(ChangeKind::parse)
+            .ok_or_else(|| Failure::parse(format!("unexpected name-status: {:?}", status)))?;
+        let score = letters.as_str();
+
+        let first = PathBuf::from(records.next().ok_or_else(truncated)?);
+        let change = match kind {
+            ChangeKind::Renamed | ChangeKind::Copied => FileChange {
+                status: kind,
+                path: PathBuf::from(records.next().ok_or_else(truncated)?),
+                old_path: Some(first),
+                similarity: score.parse().ok(),
+            },
+            _ => FileChange {
+                status: kind,
+                path: first,
+                old_path: None,
+                similarity: None,
+            },
+        };
+        changes.push(change);
+    }
+    Ok(changes)
+}
+
+impl Repo {
+    /// Counts inserted and deleted lines per file, using `diff --numstat -z`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{DiffStatOptions, DiffSummary, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = DiffStatOptions {
+    ///     from_commit: Some("v1.0".to_string()),
+    ///     to_commit: Some("HEAD".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let stats = repo.diff_stat(opts).unwrap();
+    /// println!("+{}", DiffSummary::total_insertions(&stats));
+    /// ```
+    pub fn diff_stat(&self, opts: DiffStatOptions) -> Result<Vec<FileChangeStat>, Failure> {
+        let mut cmd = self.git(vec!["diff", "--numstat", "-z", "--no-color"]);
+        if opts.staged {
+            cmd = cmd.arg("--cached");
+        }
+        cmd = cmd.arg("--end-of-options");
+        cmd = cmd.args(opts.from_commit.iter().chain(opts.to_commit.iter()));
+
+        let output = cmd.arg("--").run()?;
+        parse_numstat(&output.stdout.unwrap_or_default())
+    }
+}
+
+/// `<added>\t<deleted>\t<path>\0`, or for renames `<added>\t<deleted>\t\0<old>\0<new>\0`
+fn parse_numstat(stdout: &str) -> Result<Vec<FileChangeStat>, Failure> {
+    let mut records = stdout.split('\0');
+    let mut stats = Vec::new();
+
+    while let Some(record) = records.next() {
+        if record.is_empty() {
+            continue;
+        }
+        let mut fields = record.splitn(3, '\t');
+        let (added, deleted, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(added), Some(deleted), Some(path)) => (added, deleted, path),
+            _ => return Err(Failure::parse(format!("unexpected numstat record: {:?}", record))),
+        };
+
+        let path = if path.is_empty() {
+            // a rename - skip the old path
+            records.next();
+            records
+                .next()
+                .ok_or_else(|| Failure::parse("numstat rename record was truncated"))?
+        } else {
+            path
+        };
+
+        let is_binary = added == "-" && deleted == "-";
+        stats.push(FileChangeStat {
+            path: PathBuf::from(path),
+            insertions: added.parse().ok(),
+            deletions: deleted.parse().ok(),
+            is_binary,
+        });
+    }
+    Ok(stats)
+}
+
+impl Repo {
+    /// Parses the full diff of the trees `opts` picks into each file's hunks, using `diff`
+    /// 
+    /// Renames are detected, and external diff drivers are not used
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{DiffLine, DiffStatOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// for patch in repo.diff_patch(DiffStatOptions::default()).unwrap() {
+    ///     for hunk in &patch.hunks {
+    ///         let added = hunk.lines.iter().filter(|x| matches!(x, DiffLine::Added(_))).count();
+    ///         println!("{:?} line {}: +{}", patch.new_path, hunk.new_start, added);
+    ///     }
+    /// }
+    /// ```
+    pub fn diff_patch(&self, opts: DiffStatOptions) -> Result<Vec<FilePatch>, Failure> {
+        // the prefixes are given, as `diff.noprefix` and `diff.mnemonicPrefix` would change them
+        let mut cmd = self.git(vec![
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--find-renames",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ]);
+        if opts.staged {
+            cmd = cmd.arg("--cached");
+        }
+        cmd = cmd.arg("--end-of-options");
+        cmd = cmd.args(opts.from_commit.iter().chain(opts.to_commit.iter()));
+
+        let output = cmd.arg("--").run()?;
+        parse_patch(&output.stdout.unwrap_or_default())
+    }
+}
+
+/// parses `diff` output - the headers of each file up to its first `@@`, then the hunks, whose lengths are counted
+/// off so that a removed line such as `-- x` can't be mistaken for a `--- x` header
+fn parse_patch(stdout: &str) -> Result<Vec<FilePatch>, Failure> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    // lines left in the current hunk, on the old and new sides
+    let mut remaining: (usize, usize) = (0, 0);
+
+    for line in stdout.split_inclusive('\n') {
+        let text = line.trim_end_matches('\n');
+        let Some(patch) = patches.last_mut() else {
+            if let Some(rest) = text.strip_prefix("diff --git ") {
+                patches.push(new_patch(rest));
+            }
+            continue;
+        };
+
+        if remaining != (0, 0) || text.starts_with('\\') {
+            let hunk = patch
+                .hunks
+                .last_mut()
+                .ok_or_else(|| Failure::parse(format!("diff line outside a hunk: {:?}", text)))?;
+            let body = line.get(1..).unwrap_or_default().to_string();
+            match line.chars().next() {
+                Some(' ') => {
+                    remaining = (remaining.0.saturating_sub(1), remaining.1.saturating_sub(1));
+                    hunk.lines.push(DiffLine::Context(body));
+                }
+                Some('+') => {
+                    remaining.1 = remaining.1.saturating_sub(1);
+                    hunk.lines.push(DiffLine::Added(body));
+                }
+                Some('-') => {
+                    remaining.0 = remaining.0.saturating_sub(1);
+                    hunk.lines.push(DiffLine::Removed(body));
+                }
+                // `\ No newline at end of file`, about the line before
+                Some('\\') => {
+                    if let Some(DiffLine::Context(text) | DiffLine::Added(text) | DiffLine::Removed(text)) =
+                        hunk.lines.last_mut()
+                    {
+                        if text.ends_with('\n') {
+                            text.pop();
+                        }
+                    }
+                }
+                _ => return Err(Failure::parse(format!("unexpected diff line: {:?}", text))),
+            }
+        } else if let Some(rest) = text.strip_prefix("diff --git ") {
+            patches.push(new_patch(rest));
+        } else if let Some(header) = text.strip_prefix("@@ ") {
+            let hunk = parse_hunk_header(header)
+                .ok_or_else(|| Failure::parse(format!("unexpected hunk header: {:?}", text)))?;
+            remaining = (hunk.old_lines, hunk.new_lines);
+            patch.hunks.push(hunk);
+        } else if text.starts_with("new file mode ") {
+            patch.old_path = None;
+        } else if text.starts_with("deleted file mode ") {
+            patch.new_path = None;
+        } else if let Some(path) = text.strip_prefix("rename from ").or(text.strip_prefix("copy from ")) {
+            patch.old_path = Some(unquote(path));
+        } else if let Some(path) = text.strip_prefix("rename to ").or(text.strip_prefix("copy to ")) {
+            patch.new_path = Some(unquote(path));
+        } else if let Some(path) = text.strip_prefix("--- ") {
+            patch.old_path = side_path(path, "a/");
+        } else if let Some(path) = text.strip_prefix("+++ ") {
+            patch.new_path = side_path(path, "b/");
+        } else if text.starts_with("Binary files ") || text == "GIT binary patch" {
+            patch.is_binary = true;
+        }
+    }
+    Ok(patches)
+}
+
+/// a `FilePatch` with the paths from `diff --git a/<old> b/<new>`, where they can be told apart - otherwise they
+/// come from the headers which follow
+fn new_patch(paths: &str) -> FilePatch {
+    let (old_path, new_path) = match split_git_header(paths) {
+        Some((old, new)) => (side_path(old, "a/"), side_path(new, "b/")),
+        None => (None, None),
+    };
+    FilePatch {
+        old_path,
+        new_path,
+        is_binary: false,
+        hunks: Vec::new(),
+    }
+}
+
+/// splits `a/<old> b/<new>`, where either may be quoted - unquoted, it is only split if both are the same path, as
+/// a space could be in either
+fn split_git_header(paths: &str) -> Option<(&str, &str)> {
+    if paths.starts_with('"') {
+        let mut escaped = false;
+        let end = paths.char_indices().skip(1).find_map(|(i, c)| {
+            let found = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            found.then_some(i)
+        })?;
+        return Some((&paths[..=end], paths[end + 1..].strip_prefix(' ')?));
+    }
+    if paths.ends_with('"') {
+        let start = paths.rfind(" \"b/")?;
+        return Some((&paths[..start], &paths[start + 1..]));
+    }
+
+    let mid = paths.len() / 2;
+    let (old, new) = (paths.get(..mid)?, paths.get(mid + 1..)?);
+    (paths.len() % 2 == 1 && old.get(2..) == new.get(2..)).then_some((old, new))
+}
+
+/// the path in a `---` or `+++` line, or half of a `diff --git` line, without its `a/` or `b/` - `None` for
+/// `/dev/null`
+fn side_path(path: &str, prefix: &str) -> Option<PathBuf> {
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = unquote(path);
+    Some(path.strip_prefix(prefix).map(Path::to_path_buf).unwrap_or(path))
+}
+
+/// `-<start>[,<lines>] +<start>[,<lines>] @@ <context>`, where a missing line count means 1
+fn parse_hunk_header(header: &str) -> Option<Hunk> {
+    let mut ranges = header.split(' ');
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, lines)) => Some((start.parse().ok()?, lines.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(ranges.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(ranges.next()?.strip_prefix('+')?)?;
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_parse_numstat() {
+        let stats = parse_numstat("-\t-\tbin\x001\t0\t\x00a\x00c\x003\t2\tsrc/lib.rs\x00").unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                FileChangeStat {
+                    path: PathBuf::from("bin"),
+                    insertions: None,
+                    deletions: None,
+                    is_binary: true,
+                },
+                FileChangeStat {
+                    path: PathBuf::from("c"),
+                    insertions: Some(1),
+                    deletions: Some(0),
+                    is_binary: false,
+                },
+                FileChangeStat {
+                    path: PathBuf::from("src/lib.rs"),
+                    insertions: Some(3),
+                    deletions: Some(2),
+                    is_binary: false,
+                },
+            ]
+        );
+        assert_eq!(
+            DiffSummary::from_stats(&stats),
+            DiffSummary {
+                files_changed: 3,
+                insertions: 4,
+                deletions: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        let stdout = "diff --git a/bin.dat b/bin.dat\n\
+                      index c1b0730..8352675 100644\n\
+                      Binary files a/bin.dat and b/bin.dat differ\n\
+                      diff --git a/keep.txt b/moved here.txt\n\
+                      similarity index 79%\n\
+                      rename from keep.txt\n\
+                      rename to moved here.txt\n\
+                      index b2f931a..6f980e9 100644\n\
+                      --- a/keep.txt\n\
+                      +++ b/moved here.txt\n\
+                      @@ -2,3 +2,3 @@ one\n \
+                      two\n\
+                      --- three\n\
+                      +3\n \
+                      four\n\
+                      diff --git a/run.sh b/run.sh\n\
+                      old mode 100644\n\
+                      new mode 100755\n\
+                      diff --git \"a/tab\\there\" \"b/tab\\there\"\n\
+                      new file mode 100644\n\
+                      index 0000000..817f660\n\
+                      --- /dev/null\n\
+                      +++ \"b/tab\\there\"\n\
+                      @@ -0,0 +1 @@\n\
+                      +c\n\
+                      \\ No newline at end of file\n";
+        let patches = parse_patch(stdout).unwrap();
+        let paths: Vec<_> = patches.iter().map(|x| (x.old_path.clone(), x.new_path.clone())).collect();
+        let path = |x: &str| Some(PathBuf::from(x));
+        assert_eq!(
+            paths,
+            vec![
+                (path("bin.dat"), path("bin.dat")),
+                (path("keep.txt"), path("moved here.txt")),
+                (path("run.sh"), path("run.sh")),
+                (None, path("tab\there")),
+            ]
+        );
+
+        assert!(patches[0].is_binary);
+        assert!(patches[0].hunks.is_empty());
+        assert_eq!(
+            patches[1].hunks,
+            vec![Hunk {
+                old_start: 2,
+                old_lines: 3,
+                new_start: 2,
+                new_lines: 3,
+                lines: vec![
+                    DiffLine::Context("two\n".to_string()),
+                    DiffLine::Removed("-- three\n".to_string()),
+                    DiffLine::Added("3\n".to_string()),
+                    DiffLine::Context("four\n".to_string()),
+                ],
+            }]
+        );
+        assert!(!patches[2].is_binary);
+        assert!(patches[2].hunks.is_empty());
+        let hunk = &patches[3].hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines), (0, 0, 1, 1));
+        assert_eq!(hunk.lines, vec![DiffLine::Added("c".to_string())]);
+    }
+
+    #[test]
+    fn test_diff_patch() {
+        let fixture = Fixture::new();
+        fixture.write("keep.txt", "one\ntwo\nthree\nfour\nfive\n");
+        fixture.write("tail.txt", "a\nb\n");
+        fixture.write("gone.txt", "gone\n");
+        let first = fixture.commit_all("first");
+        fixture.repo.mv("keep.txt", "moved.txt").unwrap();
+        fixture.write("moved.txt", "one\ntwo\nthree\nfour\n5\n");
+        fixture.write("tail.txt", "a\nc");
+        std::fs::remove_file(fixture.repo.path().join("gone.txt")).unwrap();
+        std::fs::write(fixture.repo.path().join("bin.dat"), b"\0\x01\x02").unwrap();
+        let second = fixture.commit_all("second");
+
+        let opts = DiffStatOptions {
+            from_commit: Some(first),
+            to_commit: Some(second),
+            ..Default::default()
+        };
+        let patches = fixture.repo.diff_patch(opts).unwrap();
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0].old_path, None);
+        assert_eq!(patches[0].new_path, Some(PathBuf::from("bin.dat")));
+        assert!(patches[0].is_binary);
+        assert_eq!(patches[1].new_path, None);
+        assert_eq!(patches[1].hunks[0].lines, vec![DiffLine::Removed("gone\n".to_string())]);
+        assert_eq!(patches[2].old_path, Some(PathBuf::from("keep.txt")));
+        assert_eq!(patches[2].new_path, Some(PathBuf::from("moved.txt")));
+        let lines = &patches[2].hunks[0].lines;
+        assert_eq!(
+            lines[lines.len() - 2..],
+            [DiffLine::Removed("five\n".to_string()), DiffLine::Added("5\n".to_string())]
+        );
+        let texts: Vec<&str> = patches[3].hunks[0].lines.iter().map(DiffLine::text).collect();
+        assert_eq!(texts, ["a\n", "b\n", "c"]);
+
+        assert!(fixture.repo.diff_patch(DiffStatOptions::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_name_status() {
+        let fixture = Fixture::new();
+        fixture.write("keep.txt", "one\ntwo\nthree\nfour\n");
+        fixture.write("gone.txt", "gone\n");
+        fixture.write("edit.txt", "before\n");
+        let first = fixture.commit_all("first");
+        fixture.repo.mv("keep.txt", "moved.txt").unwrap();
+        std::fs::remove_file(fixture.repo.path().join("gone.txt")).unwrap();
+        fixture.write("edit.txt", "after\n");
+        fixture.write("new.txt", "new\n");
+        fixture.commit_all("second");
+
+        let mut changes = fixture.repo.diff_name_status(Some(&format!("{}..HEAD", first))).unwrap();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        let change = |status, path: &str, old_path: Option<&str>, similarity| FileChange {
+            status,
+            path: PathBuf::from(path),
+            old_path: old_path.map(PathBuf::from),
+            similarity,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change(ChangeKind::Modified, "edit.txt", None, None),
+                change(ChangeKind::Deleted, "gone.txt", None, None),
+                change(ChangeKind::Renamed, "moved.txt", Some("keep.txt"), Some(100)),
+                change(ChangeKind::Added, "new.txt", None, None),
+            ]
+        );
+
+        assert!(fixture.repo.diff_name_status(None).unwrap().is_empty());
+        fixture.write("edit.txt", "again\n");
+        let unstaged = fixture.repo.diff_name_status(None).unwrap();
+        assert_eq!(unstaged, vec![change(ChangeKind::Modified, "edit.txt", None, None)]);
+        assert_eq!(fixture.repo.diff_name_status(Some("HEAD~1 HEAD")).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_diff_stat() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\ntwo\nthree\n");
+        let first = fixture.commit_all("first");
+        fixture.write("a.txt", "one\n2\nthree\nfour\n");
+        fixture.write("b.txt", "new\n");
+        let second = fixture.commit_all("second");
+
+        let opts = DiffStatOptions {
+            from_commit: Some(first),
+            to_commit: Some(second),
+            ..Default::default()
+        };
+        let stats = fixture.repo.diff_stat(opts).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].path, PathBuf::from("a.txt"));
+        assert_eq!((stats[0].insertions, stats[0].deletions), (Some(2), Some(1)));
+        assert_eq!(DiffSummary::total_insertions(&stats), 3);
+
+        fixture.write("b.txt", "changed\n");
+        assert_eq!(fixture.repo.diff_stat(DiffStatOptions::default()).unwrap().len(), 1);
+        let staged = DiffStatOptions {
+            staged: true,
+            ..Default::default()
+        };
+        assert!(fixture.repo.diff_stat(staged).unwrap().is_empty());
+    }
+}
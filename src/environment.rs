@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+
+/// A set of `GIT_*` environment variables, to put in place for a while with `apply`
+/// 
+/// Each variable is either set to a value, or `None` to be unset. Useful in tests, for commits with a fixed author,
+/// committer and date, and so a fixed SHA. The process environment is shared by every thread, so the variables are
+/// also seen by git run from any tests alongside
+/// # Examples
+/// ```rust
+/// use rsgit::GitEnvironment;
+/// let env = GitEnvironment::builder()
+///     .author("Test User", "test@example.com")
+///     .committer("Test User", "test@example.com")
+///     .date("2024-01-01T00:00:00Z")
+///     .build();
+/// {
+///     let _guard = env.apply();
+///     assert_eq!(std::env::var("GIT_AUTHOR_DATE").unwrap(), "2024-01-01T00:00:00Z");
+/// }
+/// // the variables are back as they were
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitEnvironment {
+    pub vars: HashMap<String, Option<String>>,
+}
+
+impl GitEnvironment {
+    /// Reads every `GIT_*` variable from the process environment
+    /// 
+    /// Variables which are not valid UTF-8 are skipped
+    pub fn capture() -> GitEnvironment {
+        let vars = env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(key, _)| key.starts_with("GIT_"))
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        GitEnvironment { vars }
+    }
+
+    /// Starts an empty environment, which changes nothing until variables are added
+    pub fn builder() -> GitEnvironmentBuilder {
+        GitEnvironmentBuilder::default()
+    }
+
+    /// Sets and unsets the variables in the process environment, until the returned guard is dropped
+    /// 
+    /// Only the variables listed are touched - others, including other `GIT_*` variables, are left alone
+    pub fn apply(&self) -> EnvironmentGuard {
+        let mut previous = HashMap::new();
+        for (key, value) in &self.vars {
+            previous.insert(key.clone(), env::var_os(key));
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+        EnvironmentGuard { previous }
+    }
+}
+
+/// builds a `GitEnvironment` - see `GitEnvironment::builder`
+#[derive(Debug, Clone, Default)]
+pub struct GitEnvironmentBuilder {
+    vars: HashMap<String, Option<String>>,
+}
+
+impl GitEnvironmentBuilder {
+    /// Sets `key` to `value`
+    pub fn var(mut self, key: &str, value: &str) -> GitEnvironmentBuilder {
+        self.vars.insert(key.to_string(), Some(value.to_string()));
+        self
+    }
+
+    /// Unsets `key`, so a value from the process environment doesn't leak in
+    pub fn unset(mut self, key: &str) -> GitEnvironmentBuilder {
+        self.vars.insert(key.to_string(), None);
+        self
+    }
+
+    /// Sets `GIT_AUTHOR_NAME` and `GIT_AUTHOR_EMAIL`
+    pub fn author(self, name: &str, email: &str) -> GitEnvironmentBuilder {
+        self.var("GIT_AUTHOR_NAME", name).var("GIT_AUTHOR_EMAIL", email)
+    }
+
+    /// Sets `GIT_COMMITTER_NAME` and `GIT_COMMITTER_EMAIL`
+    pub fn committer(self, name: &str, email: &str) -> GitEnvironmentBuilder {
+        self.var("GIT_COMMITTER_NAME", name).var("GIT_COMMITTER_EMAIL", email)
+    }
+
+    /// Sets both `GIT_AUTHOR_DATE` and `GIT_COMMITTER_DATE`, in any format git takes, eg `2024-01-01T00:00:00Z`
+    pub fn date(self, date: &str) -> GitEnvironmentBuilder {
+        self.var("GIT_AUTHOR_DATE", date).var("GIT_COMMITTER_DATE", date)
+    }
+
+    pub fn build(self) -> GitEnvironment {
+        GitEnvironment { vars: self.vars }
+    }
+}
+
+/// puts the environment back as it was before `GitEnvironment::apply`, when dropped
+#[derive(Debug)]
+#[must_use = "the environment is restored as soon as the guard is dropped"]
+pub struct EnvironmentGuard {
+    previous: HashMap<String, Option<OsString>>,
+}
+
+impl Drop for EnvironmentGuard {
+    fn drop(&mut self) {
+        for (key, value) in &self.previous {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // git ignores these, so setting them can't upset tests running alongside
+    const SET: &str = "GIT_RSGIT_TEST_SET";
+    const UNSET: &str = "GIT_RSGIT_TEST_UNSET";
+
+    #[test]
+    fn test_apply_and_restore() {
+        env::set_var(UNSET, "before");
+        let applied = GitEnvironment::builder().var(SET, "value").unset(UNSET).build();
+        {
+            let _guard = applied.apply();
+            assert_eq!(env::var(SET).unwrap(), "value");
+            assert!(env::var_os(UNSET).is_none());
+
+            let captured = GitEnvironment::capture();
+            assert_eq!(captured.vars[SET], Some("value".to_string()));
+            assert!(!captured.vars.contains_key(UNSET));
+            assert!(captured.vars.keys().all(|x| x.starts_with("GIT_")));
+        }
+        assert!(env::var_os(SET).is_none());
+        assert_eq!(env::var(UNSET).unwrap(), "before");
+        env::remove_var(UNSET);
+    }
+
+    #[test]
+    fn test_builder() {
+        let built = GitEnvironment::builder()
+            .author("A", "a@example.com")
+            .committer("C", "c@example.com")
+            .date("2024-01-01T00:00:00Z")
+            .unset("GIT_DIR")
+            .build();
+        assert_eq!(built.vars.len(), 7);
+        assert_eq!(built.vars["GIT_AUTHOR_EMAIL"], Some("a@example.com".to_string()));
+        assert_eq!(built.vars["GIT_COMMITTER_DATE"], Some("2024-01-01T00:00:00Z".to_string()));
+        assert_eq!(built.vars["GIT_DIR"], None);
+    }
+}
@@ -0,0 +1,319 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Failure, FailureKind, Success};
+
+/// everything about a command other than its arguments - see `Executor`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecContext {
+    /// the working directory, or the parent's if `None`
+    pub dir: Option<PathBuf>,
+    /// environment variables set on top of the parent's
+    pub envs: Vec<(OsString, OsString)>,
+    /// bytes fed to stdin, which is closed afterwards - stdin is null if `None`
+    pub stdin: Option<Vec<u8>>,
+    /// how stdout is decoded on success
+    pub encoding: OutputEncoding,
+    /// the most output, stdout and stderr together, to hold before failing with
+    /// `FailureKind::OutputLimitExceeded` - unlimited if `None`
+    pub max_output: Option<usize>,
+}
+
+/// How `Git::run` turns git's stdout into a `String` - set with `Git::encoding`
+/// 
+/// stderr, and the output of a command which failed, are always decoded lossily, so git's message is never lost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// invalid UTF-8 is replaced with `U+FFFD`
+    #[default]
+    Lossy,
+    /// invalid UTF-8 fails with `FailureKind::InvalidUtf8`, which holds the raw bytes
+    Strict,
+    /// stdout is not decoded at all, and is `None` - for output only `Git::run_bytes` should read
+    Bytes,
+}
+
+/// Executor - runs a command to completion, capturing its output
+/// 
+/// `Git::run` and `Git::run_bytes`, and so every `Repo` method, go through an executor, which is `SystemExecutor`
+/// unless another is set with `Git::with_executor` or `Repo::with_executor`. `stream` and `reader` always run the
+/// real git, as they hand its output straight to the caller
+/// 
+/// See `testing::MockExecutor` for one which never runs anything
+pub trait Executor: Send + Sync {
+    /// runs `program` with `args`, returning `Success` for a zero exit code and `Failure` otherwise
+    /// 
+    /// `args` are `OsString`s, as paths need not be valid UTF-8
+    fn execute(&self, program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Success, Failure>;
+}
+
+/// The default `Executor` - runs a real process, with stdout and stderr captured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemExecutor;
+
+impl Executor for SystemExecutor {
+    fn execute(&self, program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Success, Failure> {
+        from_output(system_output(program, args, ctx)?, ctx.encoding)
+    }
+}
+
+/// a `Success` with stdout decoded according to `encoding` for a zero exit code, and a `Failure` otherwise
+pub(crate) fn from_output(output: Output, encoding: OutputEncoding) -> Result<Success, Failure> {
+    if output.status.success() {
+        Ok(Success {
+            stdout: decode(output.stdout, encoding)?,
+            code: output.status.code().unwrap_or(0),
+        })
+    } else {
+        Err(Failure::from_output(output))
+    }
+}
+
+/// decodes stdout according to `encoding`
+pub(crate) fn decode(bytes: Vec<u8>, encoding: OutputEncoding) -> Result<Option<String>, Failure> {
+    match encoding {
+        OutputEncoding::Lossy => Ok(Some(lossy(bytes))),
+        OutputEncoding::Strict => String::from_utf8(bytes).map(Some).map_err(|err| {
+            let message = format!("git's output is not valid UTF-8: {}", err.utf8_error());
+            Failure::new(FailureKind::InvalidUtf8(err.into_bytes()), message)
+        }),
+        OutputEncoding::Bytes => Ok(None),
+    }
+}
+
+/// `String::from_utf8_lossy`, without copying output which is already valid
+pub(crate) fn lossy(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
+/// an `Arc<dyn Executor>` which `Git` and `Repo` can hold and still derive their traits
+/// 
+/// two handles are only equal if they share the same executor
+#[derive(Clone)]
+pub(crate) struct ExecutorHandle(pub(crate) Arc<dyn Executor>);
+
+impl fmt::Debug for ExecutorHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExecutorHandle(..)")
+    }
+}
+
+impl PartialEq for ExecutorHandle {
+    fn eq(&self, other: &ExecutorHandle) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ExecutorHandle {}
+
+/// builds the `Command` for `program` - stdio is left to the caller
+pub(crate) fn command(program: &str, args: &[OsString], ctx: &ExecContext) -> Command {
+    let mut out = Command::new(program);
+    out.args(args);
+    if let Some(dir) = &ctx.dir {
+        out.current_dir(dir);
+    }
+    for (key, value) in &ctx.envs {
+        out.env(key, value);
+    }
+    out
+}
+
+/// writes the stdin buffer on its own thread, so a full stdout pipe can't deadlock us
+pub(crate) fn feed_stdin(ctx: &ExecContext, child: &mut Child) {
+    if let (Some(data), Some(mut pipe)) = (ctx.stdin.clone(), child.stdin.take()) {
+        thread::spawn(move || {
+            let _ = pipe.write_all(&data);
+        });
+    }
+}
+
+/// how long to wait for the rest of the output once `system_output` has killed git
+const KILLED_GRACE: Duration = Duration::from_millis(100);
+
+/// runs to completion with stdout and stderr captured, whatever the exit code
+/// 
+/// only fails if the output passes `ctx.max_output`, once the process has been killed
+pub(crate) fn system_output(program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Output, Failure> {
+    let mut out = command(program, args, ctx);
+    out.stdin(if ctx.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = out.spawn().expect("Failed to execute `git`");
+    feed_stdin(ctx, &mut child);
+    let Some(limit) = ctx.max_output else {
+        return Ok(child.wait_with_output().expect("Failed to execute `git`"));
+    };
+
+    let total = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let mut readers = 0;
+    if let Some(pipe) = child.stdout.take() {
+        read_limited(pipe, false, limit, total.clone(), sender.clone());
+        readers += 1;
+    }
+    if let Some(pipe) = child.stderr.take() {
+        read_limited(pipe, true, limit, total, sender);
+        readers += 1;
+    }
+
+    let (mut stdout, mut stderr, mut exceeded) = (Vec::new(), Vec::new(), false);
+    for _ in 0..readers {
+        // once git is killed, a process it started, such as ssh or a hook, may still hold the other pipe open, so
+        // that reader is only given a moment, and left behind if it hasn't finished
+        let received = if exceeded {
+            receiver.recv_timeout(KILLED_GRACE).ok()
+        } else {
+            receiver.recv().ok()
+        };
+        let Some((is_stderr, buf, over)) = received else {
+            break;
+        };
+        if over && !exceeded {
+            // the other pipe may never close while git runs
+            exceeded = true;
+            let _ = child.kill();
+        }
+        if is_stderr {
+            stderr = buf;
+        } else {
+            stdout = buf;
+        }
+    }
+    let status = child.wait().expect("Failed to execute `git`");
+
+    let output = Output { status, stdout, stderr };
+    if exceeded {
+        let mut failure = Failure::from_output(output);
+        failure.kind = FailureKind::OutputLimitExceeded(limit);
+        return Err(failure);
+    }
+    Ok(output)
+}
+
+/// reads a pipe on its own thread until it closes, or until `total` - shared by stdout and stderr - passes `limit`
+/// 
+/// sends whether it came from stderr, what was read up to the limit, and whether the limit was passed
+fn read_limited<R: Read + Send + 'static>(
+    mut pipe: R,
+    is_stderr: bool,
+    limit: usize,
+    total: Arc<AtomicUsize>,
+    done: Sender<(bool, Vec<u8>, bool)>,
+) {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let mut chunk = [0; 8192];
+        let mut over = false;
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => {
+                    let room = limit.saturating_sub(total.fetch_add(read, Ordering::SeqCst));
+                    buf.extend_from_slice(&chunk[..read.min(room)]);
+                    if read > room {
+                        over = true;
+                        break;
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+        let _ = done.send((is_stderr, buf, over));
+    });
+}
+
+/// turns an executor's result back into an `Output`, for the callers which need stderr whatever the exit code
+#[cfg(any(unix, windows))]
+pub(crate) fn into_output(result: Result<Success, Failure>) -> Result<Output, Failure> {
+    Ok(match result {
+        Ok(success) => success.into(),
+        Err(failure) => failure.into(),
+    })
+}
+
+/// turns an executor's result back into an `Output`, for the callers which need stderr whatever the exit code
+/// 
+/// Only a success can be turned back here, as only a successful exit status can be built - a failure is returned
+/// as it is
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn into_output(result: Result<Success, Failure>) -> Result<Output, Failure> {
+    let success = result?;
+    Ok(Output {
+        status: std::process::ExitStatus::default(),
+        stdout: success.stdout.unwrap_or_default().into_bytes(),
+        stderr: Vec::new(),
+    })
+}
+
+/// Rebuilds an `Output` from a `Success`, for code built around `std::process::Command`
+/// 
+/// This is lossy - stderr is always empty, as `Success` doesn't keep it, stdout is empty if it wasn't captured, as
+/// with `stream`, and output which wasn't valid UTF-8 has already been decoded lossily. Only on unix and windows,
+/// where an exit status can be built from a code
+/// # Examples
+/// ```rust
+/// use std::process::Output;
+/// use rsgit::Git;
+/// let output: Output = Git::new(vec!["--version"]).run().unwrap().into();
+/// assert!(output.status.success());
+/// ```
+#[cfg(any(unix, windows))]
+impl From<Success> for Output {
+    fn from(success: Success) -> Output {
+        Output {
+            status: exit_status(success.code),
+            stdout: success.stdout.unwrap_or_default().into_bytes(),
+            stderr: Vec::new(),
+        }
+    }
+}
+
+/// Rebuilds an `Output` from a `Failure`, for code built around `std::process::Command`
+/// 
+/// Lossy in the same ways as for `Success`, except that stderr is kept. A failure killed by a signal gets the signal
+/// back on unix, and a code which didn't come from git, such as -1, is truncated to 8 bits there, as exit codes are.
+/// Only on unix and windows
+#[cfg(any(unix, windows))]
+impl From<Failure> for Output {
+    fn from(failure: Failure) -> Output {
+        let status = match failure.signal() {
+            #[cfg(unix)]
+            Some(signal) => killed_status(signal),
+            _ => exit_status(failure.code),
+        };
+        Output {
+            status,
+            stdout: failure.stdout.unwrap_or_default().into_bytes(),
+            stderr: failure.stderr.unwrap_or_default().into_bytes(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> std::process::ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(unix)]
+fn killed_status(signal: i32) -> std::process::ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(signal & 0x7f)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> std::process::ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(code as u32)
+}
@@ -0,0 +1,31 @@
+//! scratch repositories for the crate's own tests, built on `testing::TempRepo`
+
+use crate::testing::TempRepo;
+use crate::Repo;
+
+pub(crate) use crate::testing::TempDir;
+
+/// an initialized repository with an identity configured
+pub(crate) struct Fixture {
+    pub(crate) repo: Repo,
+    temp: TempRepo,
+}
+
+impl Fixture {
+    pub(crate) fn new() -> Fixture {
+        let temp = TempRepo::new();
+        Fixture {
+            repo: temp.repo().clone(),
+            temp,
+        }
+    }
+
+    pub(crate) fn write(&self, path: &str, contents: &str) {
+        self.temp.write_file(path, contents);
+    }
+
+    /// stages everything and commits, returning the new SHA
+    pub(crate) fn commit_all(&self, message: &str) -> String {
+        self.temp.commit_all(message)
+    }
+}
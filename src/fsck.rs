@@ -0,0 +1,281 @@
+use crate::{Failure, Git, Repo};
+
+/// how serious an `FsckIssue` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// not a problem, eg a dangling object left behind by a rebase
+    Info,
+    /// something git tolerates, but which is not well formed
+    Warning,
+    /// the repository is missing or has corrupt data
+    Error,
+}
+
+/// a problem reported by `Repo::fsck`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsckIssue {
+    pub severity: Severity,
+    /// what was found, eg `dangling`, `missing`, or git's message id such as `badDate`
+    pub kind: String,
+    /// the SHA of the object concerned, where there is one
+    pub object: Option<String>,
+    /// the line git printed
+    pub message: String,
+}
+
+/// options for `Repo::fsck_with_options`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsckOptions {
+    /// treat more kinds of malformed object as errors (`--strict`)
+    pub strict: bool,
+    /// only check that every object reachable is present, skipping blob contents (`--connectivity-only`)
+    pub connectivity_only: bool,
+    /// leave dangling objects out (`--no-dangling`)
+    pub no_dangling: bool,
+    /// report every unreachable object, not just the dangling ones (`--unreachable`)
+    pub unreachable: bool,
+}
+
+/// an error-level problem from `Repo::fsck_with_options`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsckError {
+    /// the object concerned - empty for errors about no particular object
+    pub sha: String,
+    /// what was found, as for `FsckIssue::kind`
+    pub kind: String,
+    /// the line git printed
+    pub message: String,
+}
+
+/// everything `Repo::fsck_with_options` found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsckResult {
+    /// the issues with `Severity::Error`
+    pub errors: Vec<FsckError>,
+    /// every issue, including warnings and dangling objects
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckResult {
+    /// whether no errors were found - warnings and dangling objects don't count
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Repo {
+    /// Checks the object database, using `fsck --no-progress`
+    /// 
+    /// git exits non-zero when it finds errors, but those are reported as issues rather than a `Failure`, which is
+    /// only returned if fsck printed nothing it could parse
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, Severity};
+    /// let repo = Repo::open(".").unwrap();
+    /// for issue in repo.fsck().unwrap() {
+    ///     if issue.severity == Severity::Error {
+    ///         eprintln!("{}", issue.message);
+    ///     }
+    /// }
+    /// ```
+    pub fn fsck(&self) -> Result<Vec<FsckIssue>, Failure> {
+        self.run_fsck(self.git(vec!["fsck", "--no-progress"]))
+    }
+
+    /// Checks the object database as `fsck` does, with `opts`, separating out the errors
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{FsckOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = FsckOptions {
+    ///     connectivity_only: true,
+    ///     ..Default::default()
+    /// };
+    /// let result = repo.fsck_with_options(opts).unwrap();
+    /// for error in &result.errors {
+    ///     eprintln!("{} {}: {}", error.kind, error.sha, error.message);
+    /// }
+    /// ```
+    pub fn fsck_with_options(&self, opts: FsckOptions) -> Result<FsckResult, Failure> {
+        let mut cmd = self.git(vec!["fsck", "--no-progress"]);
+        if opts.strict {
+            cmd = cmd.arg("--strict");
+        }
+        if opts.connectivity_only {
+            cmd = cmd.arg("--connectivity-only");
+        }
+        if opts.no_dangling {
+            cmd = cmd.arg("--no-dangling");
+        }
+        if opts.unreachable {
+            cmd = cmd.arg("--unreachable");
+        }
+
+        let issues = self.run_fsck(cmd)?;
+        let errors = issues
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .map(|x| FsckError {
+                sha: x.object.clone().unwrap_or_default(),
+                kind: x.kind.clone(),
+                message: x.message.clone(),
+            })
+            .collect();
+        Ok(FsckResult { errors, issues })
+    }
+
+    fn run_fsck(&self, cmd: Git) -> Result<Vec<FsckIssue>, Failure> {
+        let output = cmd.output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        let issues: Vec<FsckIssue> = stdout
+            .lines()
+            .chain(stderr.lines())
+            .filter_map(parse_fsck_line)
+            .collect();
+        if !output.status.success() && issues.is_empty() {
+            return Err(Failure::from_output(output));
+        }
+        Ok(issues)
+    }
+}
+
+/// parses one line of fsck's output, skipping anything which isn't about an object
+fn parse_fsck_line(line: &str) -> Option<FsckIssue> {
+    let line = line.trim();
+    let issue = |severity: Severity, kind: &str, object: Option<&str>| FsckIssue {
+        severity,
+        kind: kind.to_string(),
+        object: object.map(|x| x.to_string()),
+        message: line.to_string(),
+    };
+
+    // `error in <type> <sha>: <msg-id>: <message>`, and the same for warnings
+    for (prefix, severity) in [("error in ", Severity::Error), ("warning in ", Severity::Warning)] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            let (object, detail) = rest.split_once(": ")?;
+            let sha = object.split_whitespace().nth(1);
+            let kind = detail.split_once(": ").map(|(id, _)| id).unwrap_or(prefix.trim());
+            return Some(issue(severity, kind, sha));
+        }
+    }
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        // `dangling <type> <sha>`, `unreachable <type> <sha>`
+        [kind @ ("dangling" | "unreachable"), _, sha] => Some(issue(Severity::Info, kind, Some(sha))),
+        // `missing <type> <sha>`
+        ["missing", _, sha] => Some(issue(Severity::Error, "missing", Some(sha))),
+        // `broken link from <type> <sha>`, followed by an indented `to <type> <sha>`
+        ["broken", "link", "from", _, sha] => Some(issue(Severity::Error, "broken link", Some(sha))),
+        // `error: ...` with no object, eg a corrupt pack
+        ["error:", ..] => Some(issue(Severity::Error, "error", None)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_parse_fsck_sample() {
+        let sample = "\
+Checking object directories
+dangling commit 117419c7e0c61e34e3870a69f566ee799a11a5ac
+warning in tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904: zeroPaddedFilemode: contains zero-padded file modes
+missing blob 587be6b4c3f93f93c489c0111bba5596147a26cb
+notice: HEAD points to an unborn branch (master)
+";
+        let issues: Vec<FsckIssue> = sample.lines().filter_map(parse_fsck_line).collect();
+        assert_eq!(issues.len(), 3);
+
+        assert_eq!(
+            issues[0],
+            FsckIssue {
+                severity: Severity::Info,
+                kind: "dangling".to_string(),
+                object: Some("117419c7e0c61e34e3870a69f566ee799a11a5ac".to_string()),
+                message: "dangling commit 117419c7e0c61e34e3870a69f566ee799a11a5ac".to_string(),
+            }
+        );
+        assert_eq!(issues[1].severity, Severity::Warning);
+        assert_eq!(issues[1].kind, "zeroPaddedFilemode");
+        assert_eq!(issues[1].object.as_deref(), Some("4b825dc642cb6eb9a060e54bf8d69288fbee4904"));
+        assert_eq!(issues[2].severity, Severity::Error);
+        assert_eq!(issues[2].kind, "missing");
+    }
+
+    #[test]
+    fn test_fsck_with_options() {
+        let fixture = Fixture::new();
+        let result = fixture.repo.fsck_with_options(FsckOptions::default()).unwrap();
+        assert!(result.is_ok());
+        assert!(result.issues.is_empty());
+
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        fixture
+            .repo
+            .git(vec!["hash-object", "-w", "--stdin"])
+            .stdin("orphan\n")
+            .run()
+            .unwrap();
+
+        let result = fixture.repo.fsck_with_options(FsckOptions::default()).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(result.issues.len(), 1);
+        let quiet = FsckOptions {
+            no_dangling: true,
+            strict: true,
+            ..Default::default()
+        };
+        assert!(fixture.repo.fsck_with_options(quiet).unwrap().issues.is_empty());
+
+        let blob = fixture.repo.rev_parse("HEAD:a.txt").unwrap();
+        let loose = fixture.repo.path().join(".git/objects").join(&blob[..2]).join(&blob[2..]);
+        std::fs::remove_file(loose).unwrap();
+        let result = fixture.repo.fsck_with_options(FsckOptions::default()).unwrap();
+        assert!(!result.is_ok());
+        assert_eq!(result.errors[0].sha, blob);
+        assert_eq!(result.errors[0].kind, "missing");
+    }
+
+    #[test]
+    fn test_fsck() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        assert_eq!(fixture.repo.fsck().unwrap(), vec![]);
+
+        // a commit no ref points at
+        let tree = fixture.repo.rev_parse("HEAD^{tree}").unwrap();
+        let output = fixture
+            .repo
+            .git(vec!["commit-tree", "-m", "dangling", &tree])
+            .run()
+            .unwrap();
+        let dangling = output.stdout.unwrap().trim().to_string();
+
+        let issues = fixture.repo.fsck().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, "dangling");
+        assert_eq!(issues[0].object, Some(dangling));
+
+        // fsck exits non-zero for a missing object, which is still reported as an issue
+        let blob = fixture.repo.rev_parse("HEAD:a.txt").unwrap();
+        let loose = fixture
+            .repo
+            .path()
+            .join(".git/objects")
+            .join(&blob[..2])
+            .join(&blob[2..]);
+        std::fs::remove_file(loose).unwrap();
+        let issues = fixture.repo.fsck().unwrap();
+        assert!(issues.iter().any(|x| x.severity == Severity::Error
+            && x.kind == "missing"
+            && x.object.as_deref() == Some(blob.as_str())));
+    }
+}
@@ -0,0 +1,1001 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, FailureKind, Git, GitReader, Repo};
+
+/// `log --format` for `Commit` - each record starts with `\x1e`, and every field ends with `\x1f`
+/// 
+/// anything git prints after the fields, such as `--name-status` output, follows the last `\x1f`
+pub(crate) const COMMIT_FORMAT: &str =
+    "--format=%x1e%H%x1f%P%x1f%an%x1f%ae%x1f%aI%x1f%at%x1f%cn%x1f%ce%x1f%cI%x1f%ct%x1f%s%x1f%b%x1f";
+
+/// `COMMIT_FORMAT`, with names and emails mapped through `.mailmap`
+pub(crate) const MAILMAP_COMMIT_FORMAT: &str =
+    "--format=%x1e%H%x1f%P%x1f%aN%x1f%aE%x1f%aI%x1f%at%x1f%cN%x1f%cE%x1f%cI%x1f%ct%x1f%s%x1f%b%x1f";
+
+/// the number of fields in `COMMIT_FORMAT`
+const COMMIT_FIELDS: usize = 12;
+
+/// a parsed commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub sha: String,
+    /// parent SHAs - empty for a root commit, several for a merge
+    pub parents: Vec<String>,
+
+    pub author_name: String,
+    pub author_email: String,
+    /// strict ISO 8601, eg `2024-01-31T12:00:00+00:00`
+    pub author_date: String,
+    /// seconds since the unix epoch
+    pub author_timestamp: i64,
+
+    pub committer_name: String,
+    pub committer_email: String,
+    /// strict ISO 8601
+    pub committer_date: String,
+    /// seconds since the unix epoch
+    pub committer_timestamp: i64,
+
+    /// the first line of the message
+    pub subject: String,
+    /// the rest of the message, after the blank line
+    pub body: String,
+}
+
+/// options for `Repo::file_history`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHistoryOptions {
+    /// stop after this many commits (`--max-count`)
+    pub max_count: Option<usize>,
+
+    /// only commits newer than this date (`--since`)
+    pub since: Option<String>,
+
+    /// include merge commits, which are skipped by default
+    pub include_merges: bool,
+}
+
+/// a commit which touched a file, with the file's name at that commit - see `Repo::file_history`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHistoryEntry {
+    pub commit: Commit,
+
+    /// the path of the file in this commit
+    pub path: PathBuf,
+
+    /// when this commit renamed the file, the path it had before
+    pub previous_path: Option<PathBuf>,
+}
+
+/// options for `Repo::line_history`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineHistoryOptions {
+    /// stop after this many commits (`--max-count`)
+    pub max_count: Option<usize>,
+
+    /// only commits newer than this date (`--since`)
+    pub since: Option<String>,
+
+    /// revision to start from - defaults to `HEAD`
+    pub rev: Option<String>,
+}
+
+/// a commit which touched a range of lines - see `Repo::line_history`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineHistoryEntry {
+    pub commit: Commit,
+
+    /// the patch git printed for the range in this commit, starting at `diff --git`
+    pub diff: String,
+}
+
+/// options for `Repo::rev_list`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevListOptions {
+    /// revision or range to walk, eg `main..feature` - defaults to `HEAD`
+    pub range: Option<String>,
+
+    /// stop after this many commits (`--max-count`)
+    pub max_count: Option<usize>,
+
+    /// only commits newer than this date (`--since`)
+    pub since: Option<String>,
+
+    /// only commits older than this date (`--until`)
+    pub until: Option<String>,
+
+    /// only commits whose author matches this pattern (`--author`)
+    pub author: Option<String>,
+
+    /// walk every ref, rather than `HEAD` (`--all`) - `range` is walked as well if set
+    pub all: bool,
+
+    /// only follow the first parent of merges (`--first-parent`)
+    pub first_parent: bool,
+
+    /// only commits touching these paths
+    pub paths: Vec<PathBuf>,
+}
+
+/// the SHAs listed by `Repo::rev_list`, read from git as they are produced
+/// 
+/// dropping the iterator early stops git
+#[derive(Debug)]
+pub struct RevListIter {
+    reader: BufReader<GitReader>,
+    done: bool,
+}
+
+impl Iterator for RevListIter {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        if self.done {
+            return None;
+        }
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => {
+                // git only reports a bad revision through its exit code
+                self.done = true;
+                match self.reader.get_mut().wait() {
+                    Ok(status) if status.success() => None,
+                    Ok(status) => Some(Err(io::Error::other(format!(
+                        "git rev-list failed: {}",
+                        status
+                    )))),
+                    Err(err) => Some(Err(err)),
+                }
+            }
+            Ok(_) => Some(Ok(line.trim_end().to_string())),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl Repo {
+    /// Lists the SHAs of the commits matching `opts`, newest first
+    /// 
+    /// SHAs are read lazily, so taking a few from a long history does not wait for the rest
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, RevListOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = RevListOptions {
+    ///     author: Some("werdl".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// for sha in repo.rev_list(opts).unwrap().take(10) {
+    ///     println!("{}", sha.unwrap());
+    /// }
+    /// ```
+    pub fn rev_list(&self, opts: RevListOptions) -> Result<RevListIter, Failure> {
+        let mut cmd = self.git(vec!["rev-list"]);
+        if let Some(max_count) = opts.max_count {
+            cmd = cmd.arg(format!("--max-count={}", max_count));
+        }
+        let cmd = walk_args(cmd, &opts);
+
+        let reader = cmd.quiet(true).reader().map_err(|err| {
+            Failure::new(FailureKind::Io, format!("failed to run git rev-list: {}", err))
+        })?;
+        Ok(RevListIter {
+            reader: BufReader::new(reader),
+            done: false,
+        })
+    }
+
+    /// The number of commits reachable from `range`, eg `main..feature`, using `rev-list --count`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let ahead = repo.commit_count("origin/main..HEAD").unwrap();
+    /// ```
+    pub fn commit_count(&self, range: &str) -> Result<u64, Failure> {
+        let output = self
+            .git(vec!["rev-list", "--count", "--end-of-options", range])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        stdout
+            .trim()
+            .parse()
+            .map_err(|_| Failure::parse(format!("unexpected count: {:?}", stdout)))
+    }
+}
+
+/// adds the commit limiting of `opts`, other than `max_count`, then the range and paths - shared by `rev-list` and
+/// `log`
+fn walk_args(mut cmd: Git, opts: &RevListOptions) -> Git {
+    if let Some(since) = &opts.since {
+        cmd = cmd.arg(format!("--since={}", since));
+    }
+    if let Some(until) = &opts.until {
+        cmd = cmd.arg(format!("--until={}", until));
+    }
+    if let Some(author) = &opts.author {
+        cmd = cmd.arg(format!("--author={}", author));
+    }
+    if opts.first_parent {
+        cmd = cmd.arg("--first-parent");
+    }
+    if opts.all {
+        cmd = cmd.arg("--all");
+    }
+    cmd = cmd.arg("--end-of-options");
+    match &opts.range {
+        Some(range) => cmd = cmd.arg(range),
+        None if !opts.all => cmd = cmd.arg("HEAD"),
+        None => {}
+    }
+    cmd.arg("--").args_os(&opts.paths)
+}
+
+/// history read a page at a time, from `Repo::log_pages`
+/// 
+/// Each page is a separate `log` call, skipping the commits already read, so if the history changes between pages,
+/// such as by a new commit or a force push, later pages are of the new history, and may repeat or miss commits. A
+/// page shorter than the page size is taken as the end, and no more calls are made. Also an iterator over every
+/// commit, fetching pages as they are needed
+#[derive(Debug, Clone)]
+pub struct LogPager {
+    repo: Repo,
+    opts: RevListOptions,
+    page_size: usize,
+    use_mailmap: bool,
+    /// commits read so far, to `--skip`
+    read: usize,
+    done: bool,
+    /// the rest of the current page, for the iterator
+    buffered: std::vec::IntoIter<Commit>,
+}
+
+impl LogPager {
+    /// Maps author and committer names and emails through `.mailmap` - see `Repo::mailmap_resolve`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, RevListOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// for commit in repo.log_pages(RevListOptions::default(), 50).use_mailmap(true) {
+    ///     println!("{}", commit.unwrap().author_email);
+    /// }
+    /// ```
+    pub fn use_mailmap(mut self, use_mailmap: bool) -> LogPager {
+        self.use_mailmap = use_mailmap;
+        self
+    }
+
+    /// The next page of commits, newest first - empty once the history has run out
+    pub fn next_page(&mut self) -> Result<Vec<Commit>, Failure> {
+        let wanted = match self.opts.max_count {
+            Some(max_count) => self.page_size.min(max_count.saturating_sub(self.read)),
+            None => self.page_size,
+        };
+        if self.done || wanted == 0 {
+            self.done = true;
+            return Ok(Vec::new());
+        }
+
+        let cmd = self.repo.git(vec![
+            "log".to_string(),
+            if self.use_mailmap { MAILMAP_COMMIT_FORMAT } else { COMMIT_FORMAT }.to_string(),
+            format!("--skip={}", self.read),
+            format!("--max-count={}", wanted),
+        ]);
+        let output = walk_args(cmd, &self.opts).run().inspect_err(|_| self.done = true)?;
+        let stdout = output.stdout.unwrap_or_default();
+        let page: Vec<Commit> = parse_commits(&stdout)?.into_iter().map(|(commit, _)| commit).collect();
+
+        self.read += page.len();
+        if page.len() < wanted || self.opts.max_count == Some(self.read) {
+            self.done = true;
+        }
+        Ok(page)
+    }
+}
+
+impl Iterator for LogPager {
+    type Item = Result<Commit, Failure>;
+
+    fn next(&mut self) -> Option<Result<Commit, Failure>> {
+        if let Some(commit) = self.buffered.next() {
+            return Some(Ok(commit));
+        }
+        match self.next_page() {
+            Ok(page) => {
+                self.buffered = page.into_iter();
+                self.buffered.next().map(Ok)
+            }
+            Err(failure) => Some(Err(failure)),
+        }
+    }
+}
+
+impl Repo {
+    /// Reads the commits `opts` picks a page of `page_size` at a time, using `log --skip --max-count`
+    /// 
+    /// Nothing is run until the first page is asked for. `opts.max_count` caps the total over every page
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, RevListOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// let mut pager = repo.log_pages(RevListOptions::default(), 50);
+    /// for commit in pager.next_page().unwrap() {
+    ///     println!("{} {}", &commit.sha[..7], commit.subject);
+    /// }
+    /// // the user scrolled down
+    /// let more = pager.next_page().unwrap();
+    /// ```
+    pub fn log_pages(&self, opts: RevListOptions, page_size: usize) -> LogPager {
+        LogPager {
+            repo: self.clone(),
+            opts,
+            page_size,
+            use_mailmap: false,
+            read: 0,
+            done: false,
+            buffered: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Repo {
+    /// The commit `HEAD` points at, parsed in a single `log` call
+    /// 
+    /// Fails in a repository with no commits yet
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let head = repo.head_commit().unwrap();
+    /// println!("built from {} ({}) at {}", head.sha, head.subject, head.committer_timestamp);
+    /// ```
+    pub fn head_commit(&self) -> Result<Commit, Failure> {
+        self.show_commit("HEAD")
+    }
+
+    /// The commit `rev` resolves to, or `None` if it does not resolve to a commit
+    /// 
+    /// Annotated tags are peeled to the commit they point at
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// if let Some(commit) = repo.commit_info("v1.0").unwrap() {
+    ///     println!("v1.0 is {} by {}", commit.sha, commit.author_name);
+    /// }
+    /// ```
+    pub fn commit_info(&self, rev: &str) -> Result<Option<Commit>, Failure> {
+        // `--quiet` makes an unknown revision exit 1, leaving 128 for real errors
+        let output = self
+            .git(vec!["rev-parse", "--verify", "--quiet", "--end-of-options"])
+            .arg(format!("{}^{{commit}}", rev))
+            .run();
+        match output {
+            Ok(output) => self
+                .show_commit(output.stdout.unwrap_or_default().trim())
+                .map(Some),
+            Err(failure) if failure.code == 1 => Ok(None),
+            Err(failure) => Err(failure),
+        }
+    }
+
+    /// The most recent commit reachable from `HEAD` which touched `path`, or `None` if it has no history
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// if let Some(commit) = repo.last_commit(Path::new("README.md")).unwrap() {
+    ///     println!("last changed by {} at {}", commit.author_name, commit.author_date);
+    /// }
+    /// ```
+    pub fn last_commit(&self, path: &Path) -> Result<Option<Commit>, Failure> {
+        let output = self
+            .git(vec!["log", "-1", COMMIT_FORMAT, "--"])
+            .arg_os(path)
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        Ok(parse_commits(&stdout)?.into_iter().next().map(|(commit, _)| commit))
+    }
+
+    fn show_commit(&self, rev: &str) -> Result<Commit, Failure> {
+        let output = self
+            .git(vec!["log", "-1", COMMIT_FORMAT, "--end-of-options", rev, "--"])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        parse_commits(&stdout)?
+            .into_iter()
+            .next()
+            .map(|(commit, _)| commit)
+            .ok_or_else(|| Failure::parse(format!("no commit found for {:?}", rev)))
+    }
+}
+
+impl Repo {
+    /// Lists the commits which touched `path`, newest first, following it through renames
+    /// 
+    /// Each entry carries the name the file had in that commit, using `log --follow --name-status`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{FileHistoryOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// for entry in repo.file_history("src/lib.rs", FileHistoryOptions::default()).unwrap() {
+    ///     println!("{} {} {}", entry.commit.sha, entry.path.display(), entry.commit.subject);
+    /// }
+    /// ```
+    pub fn file_history<P: AsRef<Path>>(
+        &self,
+        path: P,
+        opts: FileHistoryOptions,
+    ) -> Result<Vec<FileHistoryEntry>, Failure> {
+        let mut cmd = self.git(vec!["log", "--follow", "--name-status", "-z", COMMIT_FORMAT]);
+        if let Some(max_count) = opts.max_count {
+            cmd = cmd.arg(format!("--max-count={}", max_count));
+        }
+        if let Some(since) = &opts.since {
+            cmd = cmd.arg(format!("--since={}", since));
+        }
+        if !opts.include_merges {
+            cmd = cmd.arg("--no-merges");
+        }
+        let output = cmd.arg("--").arg_os(path.as_ref()).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+
+        let mut entries = Vec::new();
+        for (commit, rest) in parse_commits(&stdout)? {
+            // `<status>\0<path>\0`, or `R<score>\0<old>\0<new>\0` for renames and copies
+            let fields: Vec<&str> = rest
+                .trim_start_matches(['\0', '\n'])
+                .split('\0')
+                .filter(|x| !x.is_empty())
+                .collect();
+
+            let (path, previous_path) = match fields.as_slice() {
+                [status, old, new, ..] if status.starts_with(['R', 'C']) => {
+                    (PathBuf::from(new), Some(PathBuf::from(old)))
+                }
+                [_, current, ..] => (PathBuf::from(current), None),
+                // merges have no name-status of their own
+                _ => (
+                    entries
+                        .last()
+                        .map(|x: &FileHistoryEntry| x.path.clone())
+                        .unwrap_or_else(|| path.as_ref().to_path_buf()),
+                    None,
+                ),
+            };
+            entries.push(FileHistoryEntry {
+                commit,
+                path,
+                previous_path,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+impl Repo {
+    /// Shorthand for `file_history` with default options - every commit which touched `path`,
+    /// following renames
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for entry in repo.log_follow("src/lib.rs").unwrap() {
+    ///     if let Some(previous) = entry.previous_path {
+    ///         println!("renamed from {} in {}", previous.display(), entry.commit.sha);
+    ///     }
+    /// }
+    /// ```
+    pub fn log_follow<P: AsRef<Path>>(&self, path: P) -> Result<Vec<FileHistoryEntry>, Failure> {
+        self.file_history(path, FileHistoryOptions::default())
+    }
+}
+
+impl Repo {
+    /// Lists the commits which changed lines `start_line..=end_line` of `path`, newest first,
+    /// using `log -L`
+    /// 
+    /// Line numbers are 1-based and refer to the file as of `opts.rev`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{LineHistoryOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// for entry in repo.line_history("src/lib.rs", 10, 20, LineHistoryOptions::default()).unwrap() {
+    ///     println!("{} {}", entry.commit.sha, entry.commit.subject);
+    ///     println!("{}", entry.diff);
+    /// }
+    /// ```
+    pub fn line_history<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start_line: usize,
+        end_line: usize,
+        opts: LineHistoryOptions,
+    ) -> Result<Vec<LineHistoryEntry>, Failure> {
+        let mut range = OsString::from(format!("-L{},{}:", start_line, end_line));
+        range.push(path.as_ref());
+        self.line_log(range, opts)
+    }
+
+    /// Lists the commits which changed the function `funcname` in `path`, newest first,
+    /// using `log -L :funcname:path`
+    /// 
+    /// `funcname` is a regex matched against the function headers git finds, which depend on the
+    /// file's `diff` attribute
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{LineHistoryOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let history = repo
+    ///     .line_history_funcname("src/repo.rs", "fn open", LineHistoryOptions::default())
+    ///     .unwrap();
+    /// ```
+    pub fn line_history_funcname<P: AsRef<Path>>(
+        &self,
+        path: P,
+        funcname: &str,
+        opts: LineHistoryOptions,
+    ) -> Result<Vec<LineHistoryEntry>, Failure> {
+        let mut range = OsString::from(format!("-L:{}:", funcname));
+        range.push(path.as_ref());
+        self.line_log(range, opts)
+    }
+
+    fn line_log(
+        &self,
+        range: OsString,
+        opts: LineHistoryOptions,
+    ) -> Result<Vec<LineHistoryEntry>, Failure> {
+        let mut cmd = self.git(vec!["log", "--no-color", COMMIT_FORMAT]).arg_os(range);
+        if let Some(max_count) = opts.max_count {
+            cmd = cmd.arg(format!("--max-count={}", max_count));
+        }
+        if let Some(since) = &opts.since {
+            cmd = cmd.arg(format!("--since={}", since));
+        }
+        let rev = opts.rev.unwrap_or_else(|| "HEAD".to_string());
+
+        let output = cmd.args(vec!["--end-of-options", &rev]).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+
+        Ok(parse_commits(&stdout)?
+            .into_iter()
+            .map(|(commit, rest)| LineHistoryEntry {
+                commit,
+                diff: rest.trim_start_matches('\n').to_string(),
+            })
+            .collect())
+    }
+}
+
+/// parses `COMMIT_FORMAT` records, returning each commit with whatever git printed after it
+pub(crate) fn parse_commits(stdout: &str) -> Result<Vec<(Commit, &str)>, Failure> {
+    stdout
+        .split('\x1e')
+        .filter(|record| !record.is_empty())
+        .map(|record| {
+            let fields: Vec<&str> = record.splitn(COMMIT_FIELDS + 1, '\x1f').collect();
+            if fields.len() != COMMIT_FIELDS + 1 {
+                return Err(Failure::parse(format!("unexpected log record: {:?}", record)));
+            }
+            let timestamp = |field: &str| {
+                field
+                    .parse::<i64>()
+                    .map_err(|_| Failure::parse(format!("unexpected timestamp: {:?}", field)))
+            };
+
+            let commit = Commit {
+                sha: fields[0].to_string(),
+                parents: fields[1].split_whitespace().map(|x| x.to_string()).collect(),
+                author_name: fields[2].to_string(),
+                author_email: fields[3].to_string(),
+                author_date: fields[4].to_string(),
+                author_timestamp: timestamp(fields[5])?,
+                committer_name: fields[6].to_string(),
+                committer_email: fields[7].to_string(),
+                committer_date: fields[8].to_string(),
+                committer_timestamp: timestamp(fields[9])?,
+                subject: fields[10].to_string(),
+                body: fields[11].trim_end().to_string(),
+            };
+            Ok((commit, fields[COMMIT_FIELDS]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::CommitOptions;
+
+    fn rev_list(fixture: &Fixture, opts: RevListOptions) -> Vec<String> {
+        fixture.repo.rev_list(opts).unwrap().map(|x| x.unwrap()).collect()
+    }
+
+    fn commit_as(fixture: &Fixture, author: &str, message: &str) -> String {
+        let opts = CommitOptions {
+            allow_empty: true,
+            author: Some(author.to_string()),
+            ..Default::default()
+        };
+        fixture.repo.commit(message, opts).unwrap().sha
+    }
+
+    #[test]
+    fn test_rev_list_author() {
+        let fixture = Fixture::new();
+        let first = commit_as(&fixture, "Alice <alice@example.com>", "one");
+        let second = commit_as(&fixture, "Bob <bob@example.com>", "two");
+        let third = commit_as(&fixture, "Alice <alice@example.com>", "three");
+
+        let alice = RevListOptions {
+            author: Some("alice@example.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, alice), vec![third.clone(), first]);
+
+        let bob = RevListOptions {
+            author: Some("Bob".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, bob), vec![second.clone()]);
+
+        let limited = RevListOptions {
+            max_count: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, limited), vec![third, second]);
+
+        let nobody = RevListOptions {
+            author: Some("Carol".to_string()),
+            ..Default::default()
+        };
+        assert!(rev_list(&fixture, nobody).is_empty());
+    }
+
+    #[test]
+    fn test_rev_list_paths_and_first_parent() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let base = fixture.commit_all("a");
+        fixture.write("b.txt", "b\n");
+        let other = fixture.commit_all("b");
+
+        let paths = RevListOptions {
+            paths: vec![PathBuf::from("a.txt")],
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, paths), vec![base.clone()]);
+
+        fixture.repo.git(vec!["checkout", "-q", "-b", "side", &base]).run().unwrap();
+        let side = fixture.commit_all("side");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.repo.git(vec!["merge", "-q", "--no-edit", "side"]).run().unwrap();
+        let merge = fixture.repo.rev_parse("HEAD").unwrap();
+
+        let first_parent = RevListOptions {
+            first_parent: true,
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, first_parent), vec![merge, other, base]);
+        assert!(rev_list(&fixture, RevListOptions::default()).contains(&side));
+
+        let all = RevListOptions {
+            all: true,
+            ..Default::default()
+        };
+        assert_eq!(rev_list(&fixture, all).len(), 4);
+    }
+
+    #[test]
+    fn test_rev_list_is_lazy() {
+        let fixture = Fixture::new();
+        let mut import = String::new();
+        for i in 0..5000 {
+            import.push_str(&format!(
+                "commit refs/heads/master\ncommitter T <t@example.com> {} +0000\ndata 0\n\n",
+                1_700_000_000 + i
+            ));
+        }
+        fixture.repo.git(vec!["fast-import", "--quiet"]).stdin(import).run().unwrap();
+        assert_eq!(fixture.repo.commit_count("HEAD").unwrap(), 5000);
+        assert_eq!(fixture.repo.commit_count("HEAD~10..HEAD").unwrap(), 10);
+
+        let first: Vec<String> = fixture
+            .repo
+            .rev_list(RevListOptions::default())
+            .unwrap()
+            .take(2)
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(first[0], fixture.repo.rev_parse("HEAD").unwrap());
+        assert_eq!(first[1], fixture.repo.rev_parse("HEAD~").unwrap());
+
+        let missing = RevListOptions {
+            range: Some("no-such-branch".to_string()),
+            ..Default::default()
+        };
+        let results: Vec<_> = fixture.repo.rev_list(missing).unwrap().collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn test_head_commit_and_commit_info() {
+        let fixture = Fixture::new();
+        let opts = CommitOptions {
+            allow_empty: true,
+            author: Some("Alice <alice@example.com>".to_string()),
+            date: Some("2024-01-31T12:00:00+00:00".to_string()),
+            ..Default::default()
+        };
+        let first = fixture.repo.commit("first\n\nthe body", opts).unwrap().sha;
+        let second = fixture.commit_all("second");
+
+        let head = fixture.repo.head_commit().unwrap();
+        assert_eq!(head.sha, second);
+        assert_eq!(head.parents, vec![first.clone()]);
+        assert_eq!(head.subject, "second");
+
+        fixture.repo.git(vec!["tag", "-a", "-m", "v1", "v1", &first]).run().unwrap();
+        let commit = fixture.repo.commit_info("v1").unwrap().unwrap();
+        assert_eq!(commit.sha, first);
+        assert_eq!(commit.author_name, "Alice");
+        assert_eq!(commit.author_email, "alice@example.com");
+        assert_eq!(commit.author_date, "2024-01-31T12:00:00+00:00");
+        assert_eq!(commit.author_timestamp, 1_706_702_400);
+        assert_eq!(commit.subject, "first");
+        assert_eq!(commit.body, "the body");
+        assert!(commit.parents.is_empty());
+
+        assert_eq!(fixture.repo.commit_info("not-a-rev").unwrap(), None);
+        let tree = format!("{}^{{tree}}", second);
+        assert_eq!(fixture.repo.commit_info(&tree).unwrap(), None);
+    }
+
+    #[test]
+    fn test_last_commit() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.write("b.txt", "b\n");
+        let first = fixture.commit_all("first");
+        fixture.write("a.txt", "changed\n");
+        let second = fixture.commit_all("second");
+        fixture.commit_all("unrelated");
+
+        let a = fixture.repo.last_commit(Path::new("a.txt")).unwrap().unwrap();
+        assert_eq!(a.sha, second);
+        assert_eq!(a.subject, "second");
+        let b = fixture.repo.last_commit(Path::new("b.txt")).unwrap().unwrap();
+        assert_eq!(b.sha, first);
+
+        fixture.write("untracked.txt", "new\n");
+        assert_eq!(fixture.repo.last_commit(Path::new("untracked.txt")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_log_follow_renamed_twice() {
+        let fixture = Fixture::new();
+        fixture.write("first.txt", "one\ntwo\nthree\nfour\n");
+        fixture.commit_all("create");
+        fixture.repo.mv("first.txt", "second.txt").unwrap();
+        fixture.commit_all("rename once");
+        fixture.repo.mv("second.txt", "third.txt").unwrap();
+        fixture.commit_all("rename twice");
+
+        let paths: Vec<_> = fixture
+            .repo
+            .log_follow("third.txt")
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.commit.subject, x.path, x.previous_path))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    "rename twice".to_string(),
+                    PathBuf::from("third.txt"),
+                    Some(PathBuf::from("second.txt"))
+                ),
+                (
+                    "rename once".to_string(),
+                    PathBuf::from("second.txt"),
+                    Some(PathBuf::from("first.txt"))
+                ),
+                ("create".to_string(), PathBuf::from("first.txt"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_history() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\ntwo\nthree\nfour\nfive\n");
+        let created = fixture.commit_all("create");
+        fixture.write("a.txt", "one\ntwo\nTHREE\nfour\nfive\n");
+        let first = fixture.commit_all("first edit");
+        fixture.write("a.txt", "ONE\ntwo\nTHREE\nfour\nfive\n");
+        fixture.commit_all("unrelated edit");
+        fixture.write("a.txt", "ONE\ntwo\nThree!\nfour\nfive\n");
+        let second = fixture.commit_all("second edit");
+
+        let history = fixture
+            .repo
+            .line_history("a.txt", 3, 3, LineHistoryOptions::default())
+            .unwrap();
+        let shas: Vec<_> = history.iter().map(|x| x.commit.sha.clone()).collect();
+        assert_eq!(shas, vec![second, first, created]);
+
+        assert_eq!(history[0].commit.subject, "second edit");
+        assert!(history[0].diff.starts_with("diff --git"));
+        assert!(history[0].diff.contains("-THREE\n+Three!"));
+        assert!(history[1].diff.contains("-three\n+THREE"));
+
+        let limited = LineHistoryOptions {
+            max_count: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(fixture.repo.line_history("a.txt", 3, 3, limited).unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_line_history_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fixture = Fixture::new();
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        std::fs::write(fixture.repo.path().join(name), "one\ntwo\n").unwrap();
+        let created = fixture.commit_all("create");
+        std::fs::write(fixture.repo.path().join(name), "one\nTWO\n").unwrap();
+        let edited = fixture.commit_all("edit");
+
+        let history = fixture
+            .repo
+            .line_history(name, 2, 2, LineHistoryOptions::default())
+            .unwrap();
+        let shas: Vec<_> = history.iter().map(|x| x.commit.sha.clone()).collect();
+        assert_eq!(shas, vec![edited, created]);
+    }
+
+    #[test]
+    fn test_line_history_funcname() {
+        let fixture = Fixture::new();
+        fixture.write(".gitattributes", "*.rs diff=rust\n");
+        fixture.write("lib.rs", "fn alpha() {\n    1;\n}\n\nfn beta() {\n    2;\n}\n");
+        fixture.commit_all("create");
+        fixture.write("lib.rs", "fn alpha() {\n    1;\n}\n\nfn beta() {\n    3;\n}\n");
+        let changed = fixture.commit_all("change beta");
+        fixture.write("lib.rs", "fn alpha() {\n    4;\n}\n\nfn beta() {\n    3;\n}\n");
+        fixture.commit_all("change alpha");
+
+        let history = fixture
+            .repo
+            .line_history_funcname("lib.rs", "beta", LineHistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].commit.sha, changed);
+        assert!(history[0].diff.contains("+    3;"));
+    }
+
+    #[test]
+    fn test_file_history_follows_renames() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "one\ntwo\nthree\nfour\n");
+        let created = fixture.commit_all("create");
+        fixture.repo.mv("a.txt", "b.txt").unwrap();
+        let renamed = fixture.commit_all("rename");
+        fixture.write("b.txt", "one\ntwo\nthree\nfour\nfive\n");
+        let modified = fixture.commit_all("modify\n\nwith a body");
+        fixture.write("unrelated.txt", "x\n");
+        fixture.commit_all("unrelated");
+
+        let history = fixture
+            .repo
+            .file_history("b.txt", FileHistoryOptions::default())
+            .unwrap();
+        let summary: Vec<_> = history
+            .iter()
+            .map(|x| (x.commit.sha.clone(), x.path.clone(), x.previous_path.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (modified, PathBuf::from("b.txt"), None),
+                (renamed.clone(), PathBuf::from("b.txt"), Some(PathBuf::from("a.txt"))),
+                (created, PathBuf::from("a.txt"), None),
+            ]
+        );
+
+        let latest = &history[0].commit;
+        assert_eq!(latest.subject, "modify");
+        assert_eq!(latest.body, "with a body");
+        assert_eq!(latest.parents, vec![renamed]);
+        assert_eq!(latest.author_name, "Test User");
+        assert!(latest.author_timestamp > 0);
+
+        let limited = FileHistoryOptions {
+            max_count: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(fixture.repo.file_history("b.txt", limited).unwrap().len(), 1);
+    }
+
+    /// `count` commits in `COMMIT_FORMAT`, numbered from `first`
+    fn log_records(first: usize, count: usize) -> String {
+        (first..first + count)
+            .map(|x| {
+                format!(
+                    "\x1e{:040x}\x1f\x1fA\x1fa@example.com\x1f2024-01-01T00:00:00+00:00\x1f1704067200\x1f\
+                     A\x1fa@example.com\x1f2024-01-01T00:00:00+00:00\x1f1704067200\x1fcommit {}\x1f\x1f",
+                    x, x
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_log_pages_calls() {
+        use crate::testing::MockExecutor;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_success(["rev-parse", "--git-dir"], ".git\n")
+            .on_success(["log", "--skip=0", "--max-count=10"], log_records(0, 10))
+            .on_success(["log", "--skip=10", "--max-count=10"], log_records(10, 10))
+            .on_success(["log", "--skip=20", "--max-count=10"], log_records(20, 5));
+        let repo = Repo::open_with_executor("/not/a/real/repo", mock.clone()).unwrap();
+        let log_calls = || mock.calls().iter().filter(|x| x.args[0] == "log").count();
+
+        let mut pager = repo.log_pages(RevListOptions::default(), 10);
+        let sizes: Vec<usize> = (0..4).map(|_| pager.next_page().unwrap().len()).collect();
+        assert_eq!(sizes, [10, 10, 5, 0]);
+        assert_eq!(log_calls(), 3);
+
+        let subjects: Vec<String> = repo
+            .log_pages(RevListOptions::default(), 10)
+            .map(|x| x.unwrap().subject)
+            .collect();
+        assert_eq!(subjects.len(), 25);
+        assert_eq!(subjects[24], "commit 24");
+        assert_eq!(log_calls(), 6);
+    }
+
+    #[test]
+    fn test_log_pages() {
+        let fixture = Fixture::new();
+        let shas: Vec<String> = (0..5).map(|x| commit_as(&fixture, "A <a@example.com>", &x.to_string())).collect();
+
+        let mut pager = fixture.repo.log_pages(RevListOptions::default(), 2);
+        let page = pager.next_page().unwrap();
+        assert_eq!(page.iter().map(|x| &x.sha).collect::<Vec<_>>(), [&shas[4], &shas[3]]);
+        assert_eq!(pager.map(|x| x.unwrap().subject).collect::<Vec<_>>(), ["2", "1", "0"]);
+
+        let opts = RevListOptions {
+            max_count: Some(3),
+            ..Default::default()
+        };
+        let mut pager = fixture.repo.log_pages(opts, 2);
+        assert_eq!(pager.next_page().unwrap().len(), 2);
+        assert_eq!(pager.next_page().unwrap().len(), 1);
+        assert!(pager.next_page().unwrap().is_empty());
+
+        let bad = RevListOptions {
+            range: Some("no-such-branch".to_string()),
+            ..Default::default()
+        };
+        let mut pager = fixture.repo.log_pages(bad, 2);
+        assert!(pager.next().unwrap().is_err());
+        assert!(pager.next().is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{Failure, FailureKind, Repo};
+
+/// who commits are made as, from `Repo::identity`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+}
+
+/// which config file `Repo::set_identity` writes to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ConfigScope {
+    /// the repository's own `.git/config` (`--local`)
+    #[default]
+    Local,
+    /// the user's `~/.gitconfig` (`--global`)
+    Global,
+    /// the machine's config, usually `/etc/gitconfig` (`--system`)
+    System,
+}
+
+impl ConfigScope {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            ConfigScope::Local => "--local",
+            ConfigScope::Global => "--global",
+            ConfigScope::System => "--system",
+        }
+    }
+}
+
+impl Repo {
+    /// The identity commits would be authored as, using `var GIT_AUTHOR_IDENT`
+    /// 
+    /// This is what git itself would use, so `GIT_AUTHOR_NAME` and `GIT_AUTHOR_EMAIL` override the config. Fails
+    /// with `FailureKind::MissingIdentity` if there isn't one
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let me = repo.identity().unwrap();
+    /// println!("committing as {} <{}>", me.name, me.email);
+    /// ```
+    pub fn identity(&self) -> Result<Identity, Failure> {
+        self.var_ident("GIT_AUTHOR_IDENT")
+    }
+
+    /// Sets `user.name` and `user.email` in the config file `scope` picks
+    pub fn set_identity(&self, name: &str, email: &str, scope: ConfigScope) -> Result<(), Failure> {
+        for (key, value) in [("user.name", name), ("user.email", email)] {
+            self.git(vec!["config", scope.as_arg(), key, value]).run()?;
+        }
+        Ok(())
+    }
+
+    /// Checks that both an author and a committer identity are set, so that committing won't fail for the lack of
+    /// one
+    /// 
+    /// Fails with `FailureKind::MissingIdentity` if either is missing - git's own failure at commit time is an
+    /// exit code of 128
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{FailureKind, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// if let Err(failure) = repo.ensure_identity() {
+    ///     if failure.kind == FailureKind::MissingIdentity {
+    ///         eprintln!("set user.name and user.email first");
+    ///     }
+    /// }
+    /// ```
+    pub fn ensure_identity(&self) -> Result<(), Failure> {
+        self.var_ident("GIT_AUTHOR_IDENT")?;
+        self.var_ident("GIT_COMMITTER_IDENT")?;
+        Ok(())
+    }
+
+    fn var_ident(&self, var: &str) -> Result<Identity, Failure> {
+        let output = self
+            .git(vec!["var", var])
+            .env("LC_ALL", "C")
+            .run()
+            .map_err(classify_identity)?;
+        let stdout = output.stdout.unwrap_or_default();
+        parse_ident(stdout.trim_end())
+            .ok_or_else(|| Failure::parse(format!("unexpected ident: {:?}", stdout)))
+    }
+}
+
+/// marks failures caused by an unset name or email as `FailureKind::MissingIdentity`
+pub(crate) fn classify_identity(mut failure: Failure) -> Failure {
+    let stderr = failure.stderr.as_deref().unwrap_or_default();
+    if stderr.contains("Please tell me who you are")
+        || stderr.contains("empty ident name")
+        || stderr.contains("unable to auto-detect email address")
+    {
+        failure.kind = FailureKind::MissingIdentity;
+    }
+    failure
+}
+
+/// splits `Name <email> <timestamp> <tz>` - working from the right, as only the name is free text
+fn parse_ident(ident: &str) -> Option<Identity> {
+    let mut parts = ident.rsplitn(3, ' ');
+    let (_tz, _timestamp, person) = (parts.next()?, parts.next()?, parts.next()?);
+    let person = person.strip_suffix('>')?;
+    let (name, email) = person.rsplit_once('<')?;
+    Some(Identity {
+        name: name.trim_end().to_string(),
+        email: email.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::CommitOptions;
+
+    #[test]
+    fn test_parse_ident() {
+        let ident = |name: &str, email: &str| Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        assert_eq!(
+            parse_ident("Test User <test@example.com> 1700000000 +0000"),
+            Some(ident("Test User", "test@example.com"))
+        );
+        assert_eq!(
+            parse_ident("O'Brien, Jr. (ops) <ops+ci@example.com> 1700000000 -0530"),
+            Some(ident("O'Brien, Jr. (ops)", "ops+ci@example.com"))
+        );
+        assert_eq!(parse_ident(" <> 0 +0000"), Some(ident("", "")));
+        assert_eq!(parse_ident("no email 1700000000 +0000"), None);
+    }
+
+    #[test]
+    fn test_identity() {
+        let fixture = Fixture::new();
+        assert_eq!(fixture.repo.identity().unwrap().email, "test@example.com");
+
+        // no identity, and no guessing one from the hostname
+        fixture.repo.git(vec!["config", "--unset", "user.name"]).run().unwrap();
+        fixture.repo.git(vec!["config", "--unset", "user.email"]).run().unwrap();
+        fixture.repo.git(vec!["config", "user.useConfigOnly", "true"]).run().unwrap();
+        let failure = fixture.repo.ensure_identity().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::MissingIdentity);
+        assert_eq!(fixture.repo.identity().unwrap_err().kind, FailureKind::MissingIdentity);
+        let opts = CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        let failure = fixture.repo.commit("no one", opts.clone()).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::MissingIdentity);
+
+        fixture
+            .repo
+            .set_identity("Ada Lovelace", "ada@example.com", ConfigScope::Global)
+            .unwrap();
+        fixture.repo.ensure_identity().unwrap();
+        assert_eq!(
+            fixture.repo.identity().unwrap(),
+            Identity {
+                name: "Ada Lovelace".to_string(),
+                email: "ada@example.com".to_string(),
+            }
+        );
+        fixture.repo.commit("someone", opts).unwrap();
+        assert_eq!(fixture.repo.head_commit().unwrap().author_name, "Ada Lovelace");
+
+        // the local config wins over the global
+        fixture.repo.set_identity("Local", "local@example.com", ConfigScope::Local).unwrap();
+        assert_eq!(fixture.repo.identity().unwrap().name, "Local");
+    }
+}
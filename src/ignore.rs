@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use crate::repo::{path_bytes, path_from_bytes};
+use crate::{Failure, Repo};
+
+impl Repo {
+    /// Returns the subset of `paths` which git would ignore
+    /// 
+    /// All paths are checked in a single `check-ignore --stdin -z` call, honouring `.gitignore` files at every level,
+    /// `.git/info/exclude` and `core.excludesFile`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let ignored = repo.check_ignore(&["target/debug", "src/lib.rs"]).unwrap();
+    /// ```
+    pub fn check_ignore<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<PathBuf>, Failure> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // raw bytes both ways, so paths which aren't UTF-8 survive
+        let mut input = Vec::new();
+        for path in paths {
+            input.extend(path_bytes(path.as_ref())?);
+            input.push(b'\0');
+        }
+
+        // exit code 1 means none of the paths are ignored
+        let stdout = match self
+            .git(vec!["check-ignore", "--stdin", "-z"])
+            .stdin(input)
+            .run_bytes()
+        {
+            Ok(stdout) => stdout,
+            Err(failure) if failure.code == 1 => return Ok(Vec::new()),
+            Err(failure) => return Err(failure),
+        };
+
+        Ok(stdout
+            .split(|x| *x == b'\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| path_from_bytes(path.to_vec()))
+            .collect())
+    }
+
+    /// Checks whether git would ignore a single path
+    pub fn is_ignored<P: AsRef<Path>>(&self, path: P) -> Result<bool, Failure> {
+        Ok(!self.check_ignore(&[path])?.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_check_ignore() {
+        let fixture = Fixture::new();
+        fixture.write(".gitignore", "*.log\nbuild/\n");
+        fixture.write("nested/.gitignore", "*.tmp\n");
+        fixture.write("excludes", "*.bak\n");
+        fixture
+            .repo
+            .git(vec!["config", "core.excludesFile", "excludes"])
+            .run()
+            .unwrap();
+
+        let paths = [
+            "app.log",
+            "build/out.o",
+            "nested/x.tmp",
+            "x.tmp",
+            "notes.bak",
+            "src/main.rs",
+        ];
+        let ignored = fixture.repo.check_ignore(&paths).unwrap();
+        assert_eq!(
+            ignored,
+            vec![
+                PathBuf::from("app.log"),
+                PathBuf::from("build/out.o"),
+                PathBuf::from("nested/x.tmp"),
+                PathBuf::from("notes.bak"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let fixture = Fixture::new();
+        fixture.write(".gitignore", "*.log\n");
+
+        assert!(fixture.repo.is_ignored("debug.log").unwrap());
+        assert!(!fixture.repo.is_ignored("main.rs").unwrap());
+        assert!(fixture.repo.check_ignore::<&str>(&[]).unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_ignore_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fixture = Fixture::new();
+        fixture.write(".gitignore", "*.log\n");
+        let ignored = Path::new(OsStr::from_bytes(b"caf\xe9.log"));
+        let kept = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+        assert_eq!(fixture.repo.check_ignore(&[ignored, kept]).unwrap(), vec![ignored.to_path_buf()]);
+    }
+}
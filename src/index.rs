@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, Repo};
+
+/// an entry in the index, as listed by `Repo::read_index`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// the file mode, eg `0o100644` for a regular file or `0o160000` for a submodule
+    pub mode: u32,
+    pub sha: String,
+    /// 0 normally - during a conflict, 1 is the common ancestor, 2 ours and 3 theirs
+    pub stage: u8,
+    /// relative to the root of the repository
+    pub path: PathBuf,
+}
+
+impl Repo {
+    /// Lists every entry in the index, using `ls-files --stage -z`
+    /// 
+    /// A conflicted path has an entry for each side which has it, rather than one at stage 0
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for entry in repo.read_index().unwrap() {
+    ///     println!("{:o} {} {}\t{}", entry.mode, entry.sha, entry.stage, entry.path.display());
+    /// }
+    /// ```
+    pub fn read_index(&self) -> Result<Vec<IndexEntry>, Failure> {
+        let output = self.git(vec!["ls-files", "--stage", "--full-name", "-z"]).run()?;
+        output
+            .stdout
+            .unwrap_or_default()
+            .split('\0')
+            .filter(|record| !record.is_empty())
+            .map(parse_index_entry)
+            .collect()
+    }
+
+    /// Lists the paths whose staged contents differ from `HEAD` - new and modified files, but not deletions or
+    /// conflicts, as neither has a stage 0 entry
+    pub fn staged_files(&self) -> Result<Vec<PathBuf>, Failure> {
+        let output = self
+            .git(vec!["diff", "--cached", "--name-only", "--no-renames", "-z"])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        let changed: Vec<&Path> = stdout.split('\0').filter(|x| !x.is_empty()).map(Path::new).collect();
+
+        Ok(self
+            .read_index()?
+            .into_iter()
+            .filter(|entry| entry.stage == 0 && changed.contains(&entry.path.as_path()))
+            .map(|entry| entry.path)
+            .collect())
+    }
+
+    /// Checks whether `path`, relative to the root of the repository, is in `staged_files`
+    pub fn is_file_staged<P: AsRef<Path>>(&self, path: P) -> Result<bool, Failure> {
+        let path = path.as_ref();
+        Ok(self.staged_files()?.iter().any(|x| x == path))
+    }
+}
+
+/// parses `<mode> <sha> <stage>\t<path>`
+fn parse_index_entry(record: &str) -> Result<IndexEntry, Failure> {
+    let invalid = || Failure::parse(format!("unexpected ls-files output: {:?}", record));
+    let (meta, path) = record.split_once('\t').ok_or_else(invalid)?;
+    let fields: Vec<&str> = meta.split(' ').collect();
+    let [mode, sha, stage] = fields.as_slice() else {
+        return Err(invalid());
+    };
+
+    Ok(IndexEntry {
+        mode: u32::from_str_radix(mode, 8).map_err(|_| invalid())?,
+        sha: sha.to_string(),
+        stage: stage.parse().map_err(|_| invalid())?,
+        path: PathBuf::from(path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_read_index_and_staged_files() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.write("dir/b.txt", "b\n");
+        fixture.commit_all("initial");
+
+        let index = fixture.repo.read_index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].mode, 0o100644);
+        assert_eq!(index[0].sha, fixture.repo.rev_parse("HEAD:a.txt").unwrap());
+        assert_eq!(index[0].stage, 0);
+        assert_eq!(index[1].path, PathBuf::from("dir/b.txt"));
+        assert!(fixture.repo.staged_files().unwrap().is_empty());
+
+        fixture.write("a.txt", "changed\n");
+        fixture.write("c.txt", "new\n");
+        fixture.write("dir/b.txt", "unstaged\n");
+        fixture.repo.git(vec!["add", "a.txt", "c.txt"]).run().unwrap();
+        assert_eq!(
+            fixture.repo.staged_files().unwrap(),
+            vec![PathBuf::from("a.txt"), PathBuf::from("c.txt")]
+        );
+        assert!(fixture.repo.is_file_staged("c.txt").unwrap());
+        assert!(!fixture.repo.is_file_staged("dir/b.txt").unwrap());
+    }
+
+    #[test]
+    fn test_read_index_conflict_stages() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "base\n");
+        fixture.commit_all("base");
+        fixture.repo.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        fixture.write("a.txt", "theirs\n");
+        fixture.commit_all("theirs");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.write("a.txt", "ours\n");
+        fixture.commit_all("ours");
+        assert!(fixture.repo.git(vec!["merge", "feature"]).run().is_err());
+
+        let index = fixture.repo.read_index().unwrap();
+        let stages: Vec<u8> = index.iter().map(|x| x.stage).collect();
+        assert_eq!(stages, vec![1, 2, 3]);
+        assert!(index.iter().all(|x| x.path == Path::new("a.txt")));
+        assert_eq!(index[1].sha, fixture.repo.rev_parse("master:a.txt").unwrap());
+        assert_eq!(index[2].sha, fixture.repo.rev_parse("feature:a.txt").unwrap());
+
+        assert!(fixture.repo.staged_files().unwrap().is_empty());
+        assert!(!fixture.repo.is_file_staged("a.txt").unwrap());
+    }
+}
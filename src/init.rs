@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use crate::{Failure, Git, Repo};
+
+/// `init --initial-branch` arrived in git 2.28
+const INITIAL_BRANCH: (u32, u32, u32) = (2, 28, 0);
+
+/// options for `init`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    /// create a repository with no working tree (`--bare`)
+    pub bare: bool,
+
+    /// the name of the first branch, rather than `init.defaultBranch` or git's default (`--initial-branch`)
+    pub initial_branch: Option<String>,
+}
+
+/// Creates a repository at `path`, creating the directory if needed, and returns it
+/// 
+/// A bare repository is opened with `Repo::open_bare`. Before git 2.28, which has no `--initial-branch`, `HEAD` is
+/// pointed at the initial branch afterwards instead. Running this on an existing repository is safe, and leaves its
+/// history alone
+/// # Examples
+/// ```rust,no_run
+/// use rsgit::{init, InitOptions};
+/// let opts = InitOptions { initial_branch: Some("main".to_string()), ..Default::default() };
+/// let repo = init("my-project", opts).unwrap();
+/// ```
+pub fn init<P: AsRef<Path>>(path: P, opts: InitOptions) -> Result<Repo, Failure> {
+    let modern = Git::installed_version()? >= INITIAL_BRANCH;
+    Git::new(init_args(&opts, modern))
+        .env("LC_ALL", "C")
+        .arg("--")
+        .arg_os(path.as_ref())
+        .run()?;
+
+    let repo = if opts.bare {
+        Repo::open_bare(&path)?
+    } else {
+        Repo::open(&path)?
+    };
+    if let (Some(branch), false) = (&opts.initial_branch, modern) {
+        repo.symbolic_ref_set("HEAD", &format!("refs/heads/{}", branch))?;
+    }
+    Ok(repo)
+}
+
+impl Repo {
+    /// Creates a repository at `path`, with a working tree unless `bare`, and returns it - see `init` for more
+    /// options
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::create("/srv/git/project.git", true).unwrap();
+    /// ```
+    pub fn create<P: AsRef<Path>>(path: P, bare: bool) -> Result<Repo, Failure> {
+        let opts = InitOptions {
+            bare,
+            ..Default::default()
+        };
+        init(path, opts)
+    }
+}
+
+/// `init`, with `--initial-branch` only if `modern`
+fn init_args(opts: &InitOptions, modern: bool) -> Vec<String> {
+    let mut args = vec!["init".to_string(), "--quiet".to_string()];
+    if opts.bare {
+        args.push("--bare".to_string());
+    }
+    if let (Some(branch), true) = (&opts.initial_branch, modern) {
+        args.push(format!("--initial-branch={}", branch));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempDir;
+    use crate::FailureKind;
+
+    fn head(repo: &Repo) -> String {
+        repo.symbolic_ref_get("HEAD").unwrap()
+    }
+
+    #[test]
+    fn test_init_args() {
+        let opts = InitOptions {
+            bare: true,
+            initial_branch: Some("main".to_string()),
+        };
+        assert_eq!(init_args(&opts, true), ["init", "--quiet", "--bare", "--initial-branch=main"]);
+        assert_eq!(init_args(&opts, false), ["init", "--quiet", "--bare"]);
+        assert_eq!(init_args(&InitOptions::default(), true), ["init", "--quiet"]);
+    }
+
+    #[test]
+    fn test_init() {
+        let dir = TempDir::new();
+        let opts = InitOptions {
+            initial_branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        let repo = init(dir.path().join("new repo"), opts).unwrap();
+        assert_eq!(repo.path(), dir.path().join("new repo"));
+        assert!(repo.path().join(".git").is_dir());
+        assert_eq!(head(&repo), "refs/heads/main");
+
+        let bare = InitOptions {
+            bare: true,
+            initial_branch: Some("trunk".to_string()),
+        };
+        let repo = init(dir.path().join("bare.git"), bare).unwrap();
+        let output = repo.git(vec!["rev-parse", "--is-bare-repository"]).run().unwrap();
+        assert_eq!(output.stdout.unwrap().trim(), "true");
+        assert_eq!(head(&repo), "refs/heads/trunk");
+        let failure = repo.is_dirty().unwrap_err();
+        assert!(matches!(failure.kind, FailureKind::BareRepository));
+    }
+
+    #[test]
+    fn test_create() {
+        let dir = TempDir::new();
+        let repo = Repo::create(dir.path().join("work"), false).unwrap();
+        assert!(repo.path().join(".git").is_dir());
+        assert!(!repo.is_dirty().unwrap());
+
+        let bare = Repo::create(dir.path().join("bare.git"), true).unwrap();
+        assert!(bare.path().join("HEAD").is_file());
+        // no working tree to be dirty
+        assert!(bare.is_dirty().is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, Repo};
+
+/// what sort of repository a `Repo` is, from `Repo::kind`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoKind {
+    /// an ordinary repository, with its own working tree
+    Normal,
+    /// a repository with no working tree
+    Bare,
+    /// a linked working tree, made by `worktree add` - holds the path of the main working tree
+    WorkTree { main_path: PathBuf },
+    /// a submodule checked out inside another repository - holds the superproject's working tree
+    SubModule { parent_path: PathBuf },
+}
+
+impl Repo {
+    /// Checks whether the repository has no working tree, using `rev-parse --is-bare-repository`
+    pub fn is_bare(&self) -> Result<bool, Failure> {
+        let output = self.git(vec!["rev-parse", "--is-bare-repository"]).run()?;
+        Ok(output.stdout.unwrap_or_default().trim() == "true")
+    }
+
+    /// Checks whether any linked working trees have been added to the repository with `worktree add`
+    pub fn has_worktrees(&self) -> Result<bool, Failure> {
+        let output = self.git(vec!["worktree", "list", "--porcelain"]).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        Ok(stdout.lines().filter(|x| x.starts_with("worktree ")).count() > 1)
+    }
+
+    /// Works out what sort of repository this is, with a single `rev-parse` - and for a linked working tree, a
+    /// `worktree list` to find the main one
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, RepoKind};
+    /// let repo = Repo::open(".").unwrap();
+    /// if let RepoKind::SubModule { parent_path } = repo.kind().unwrap() {
+    ///     println!("a submodule of {}", parent_path.display());
+    /// }
+    /// ```
+    pub fn kind(&self) -> Result<RepoKind, Failure> {
+        let output = self
+            .git(vec![
+                "rev-parse",
+                "--is-bare-repository",
+                "--absolute-git-dir",
+                "--git-common-dir",
+                "--show-superproject-working-tree",
+            ])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        let lines: Vec<&str> = stdout.lines().collect();
+        // the superproject line is left out entirely outside a submodule
+        let (bare, git_dir, common_dir, superproject) = match lines.as_slice() {
+            [bare, git_dir, common_dir] => (bare, git_dir, common_dir, None),
+            [bare, git_dir, common_dir, superproject] => (bare, git_dir, common_dir, Some(superproject)),
+            _ => return Err(Failure::parse(format!("unexpected rev-parse output: {:?}", stdout))),
+        };
+
+        if *bare == "true" {
+            return Ok(RepoKind::Bare);
+        }
+        // relative to the working directory, so resolve both before comparing
+        if canonical(&self.path().join(git_dir)) != canonical(&self.path().join(common_dir)) {
+            return Ok(RepoKind::WorkTree {
+                main_path: self.main_worktree()?,
+            });
+        }
+        Ok(match superproject {
+            Some(parent) => RepoKind::SubModule {
+                parent_path: PathBuf::from(parent),
+            },
+            None => RepoKind::Normal,
+        })
+    }
+
+    /// the first working tree `worktree list` gives, which is always the main one
+    fn main_worktree(&self) -> Result<PathBuf, Failure> {
+        let output = self.git(vec!["worktree", "list", "--porcelain"]).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        stdout
+            .lines()
+            .find_map(|x| x.strip_prefix("worktree "))
+            .map(PathBuf::from)
+            .ok_or_else(|| Failure::parse(format!("unexpected worktree list output: {:?}", stdout)))
+    }
+}
+
+/// `path` with symlinks resolved, or as is if that fails
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::testing::TempRepo;
+
+    #[test]
+    fn test_kind_normal_and_worktree() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        assert_eq!(fixture.repo.kind().unwrap(), RepoKind::Normal);
+        assert!(!fixture.repo.is_bare().unwrap());
+        assert!(!fixture.repo.has_worktrees().unwrap());
+
+        let linked = fixture.repo.path().with_file_name("linked");
+        fixture
+            .repo
+            .git(vec!["worktree", "add", "-q", "-b", "linked"])
+            .arg_os(&linked)
+            .run()
+            .unwrap();
+        assert!(fixture.repo.has_worktrees().unwrap());
+        assert_eq!(fixture.repo.kind().unwrap(), RepoKind::Normal);
+
+        let worktree = Repo::open(&linked).unwrap();
+        assert!(worktree.has_worktrees().unwrap());
+        let RepoKind::WorkTree { main_path } = worktree.kind().unwrap() else {
+            panic!("expected a linked worktree");
+        };
+        assert_eq!(canonical(&main_path), canonical(fixture.repo.path()));
+    }
+
+    #[test]
+    fn test_kind_bare_and_submodule() {
+        let upstream = TempRepo::new();
+        upstream.write_file("lib.txt", "lib\n");
+        upstream.commit_all("initial");
+        let remote = upstream.into_bare_remote();
+        assert!(remote.repo().is_bare().unwrap());
+        assert_eq!(remote.repo().kind().unwrap(), RepoKind::Bare);
+
+        let fixture = Fixture::new();
+        fixture
+            .repo
+            .git(vec!["submodule", "add", "-q", &remote.url(), "lib"])
+            .with_global_config("protocol.file.allow", "always")
+            .run()
+            .unwrap();
+        let submodule = Repo::open(fixture.repo.path().join("lib")).unwrap();
+        let RepoKind::SubModule { parent_path } = submodule.kind().unwrap() else {
+            panic!("expected a submodule");
+        };
+        assert_eq!(canonical(&parent_path), canonical(fixture.repo.path()));
+        assert_eq!(fixture.repo.kind().unwrap(), RepoKind::Normal);
+    }
+}
@@ -1,5 +1,105 @@
 include!("core.rs");
 
+mod apply;
+mod archive;
+mod attributes;
+mod bisect;
+mod branches;
+mod bundle;
+mod checkout;
+mod clean;
+mod clone;
+mod commit;
+mod conflicts;
+mod describe;
+mod diff;
+mod environment;
+mod executor;
+mod fsck;
+mod history;
+mod identity;
+mod ignore;
+mod index;
+mod init;
+mod layout;
+mod mailmap;
+mod maintenance;
+mod merge;
+mod notes;
+mod objects;
+mod pathspec;
+mod range_diff;
+mod refs;
+mod remotes;
+mod repo;
+mod reset;
+mod rerere;
+mod retry;
+mod sequence;
+mod shortlog;
+mod signature;
+mod sparse;
+mod stash;
+mod staging;
+mod status;
+mod tags;
+mod trace;
+mod tracking;
+mod trailers;
+mod version;
+
+pub mod testing;
+
+pub use apply::ApplyOptions;
+pub use archive::ArchiveFormat;
+pub use attributes::{AttributeResult, AttributeValue};
+pub use bisect::BisectState;
+pub use branches::BranchOptions;
+pub use bundle::BundleInfo;
+pub use checkout::{RestoreOptions, SwitchOptions};
+pub use clean::CleanOptions;
+pub use clone::CloneOptions;
+pub use commit::{CommitOptions, CommitResult};
+pub use conflicts::{Conflict, ConflictSide};
+pub use describe::DescribeOptions;
+pub use diff::{
+    ChangeKind, DiffLine, DiffStatOptions, DiffSummary, FileChange, FileChangeStat, FilePatch, Hunk,
+};
+pub use environment::{EnvironmentGuard, GitEnvironment, GitEnvironmentBuilder};
+pub use executor::{ExecContext, Executor, OutputEncoding, SystemExecutor};
+pub use fsck::{FsckError, FsckIssue, FsckOptions, FsckResult, Severity};
+pub use history::{
+    Commit, FileHistoryEntry, FileHistoryOptions, LineHistoryEntry, LineHistoryOptions, LogPager, RevListIter,
+    RevListOptions,
+};
+pub use identity::{ConfigScope, Identity};
+pub use index::IndexEntry;
+pub use init::{init, InitOptions};
+pub use layout::RepoKind;
+pub use mailmap::{Mailmap, MailmapEntry};
+pub use maintenance::{GcOptions, GcResult, MaintenanceTask, ObjectCounts};
+pub use merge::{MergeOptions, MergeResult, RebaseInstruction, RebasePlan, RebaseResult};
+pub use objects::{GitObject, ObjectKind, ObjectStream, TreeEntry};
+pub use pathspec::PathSpec;
+pub use range_diff::{CommitRange, RangeDiffEntry, RangeDiffStatus};
+pub use refs::{RefField, RefInfo, RefsOptions};
+pub use repo::Repo;
+pub use rerere::RerereResult;
+pub use reset::ResetMode;
+pub use retry::{Backoff, RetryOutcome, RetryPolicy};
+pub use sequence::SequenceResult;
+pub use shortlog::AuthorSummary;
+pub use signature::{SignatureInfo, SignatureValidity, TrustLevel};
+pub use sparse::{SparseCheckoutManager, SparseCheckoutPattern};
+pub use staging::{AddOptions, RmOptions};
+pub use tags::TagOptions;
+pub use trace::ExecRecord;
+pub use tracking::TrackingInfo;
+pub use trailers::CommitTrailer;
+pub use version::{git_version, require_version};
+
+#[cfg(test)]
+mod fixture;
 
 #[cfg(test)]
 mod tests {
@@ -26,4 +126,644 @@ mod tests {
         assert!(result.stdout().is_none(), "Expected None, got Some");
         assert_eq!(result.code(), 0, "Expected exit code 0, got {}", result.code());
     }
+
+    #[test]
+    fn test_git_stream_quiet() {
+        // would print "git: 'not-a-command' is not a git command" without quiet
+        let result = Git::new(vec!["not-a-command"]).quiet(true).stream();
+
+        assert!(result.failed());
+        assert_eq!(result.code(), 1);
+        assert!(result.stderr().is_none());
+    }
+
+    #[test]
+    fn test_git_interactive() {
+        let result = Git::new(vec!["--version"]).interactive().run();
+        assert!(!result.failed());
+        assert!(result.stdout().is_none());
+        assert!(Git::new(vec!["--version"]).interactive().run_bytes().unwrap().is_empty());
+
+        let failure = Git::new(vec!["not-a-command"]).quiet(true).interactive().run();
+        assert!(failure.failed());
+        assert!(failure.stderr().is_none());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        let cases = [
+            ("plain", "plain"),
+            ("--format=%H", "--format=%H"),
+            ("a b", "'a b'"),
+            ("it's", r"'it'\''s'"),
+            ("--format=%H %s", "'--format=%H %s'"),
+            ("", "''"),
+            ("$HOME", "'$HOME'"),
+            ("\"quoted\"", "'\"quoted\"'"),
+            ("*.rs", "'*.rs'"),
+            ("HEAD~1", "'HEAD~1'"),
+            ("line\nbreak", "'line\nbreak'"),
+            ("'", r"''\'''"),
+        ];
+        for (arg, quoted) in cases {
+            assert_eq!(shell_quote(arg), quoted, "quoting {:?}", arg);
+        }
+    }
+
+    #[test]
+    fn test_git_dry_run() {
+        let cmd = Git::new(vec!["commit", "-m", "it's $5", ""]);
+        let rendered = r"git commit -m 'it'\''s $5' ''";
+        assert_eq!(cmd.to_command_string(), rendered);
+        assert_eq!(cmd.to_string(), rendered);
+
+        let cmd = Git::new(vec!["log", "--oneline", "--"]).arg("path with spaces");
+        assert_eq!(format!("{}", cmd), "git log --oneline -- 'path with spaces'");
+        assert_eq!(format!("[{}] {}", cmd.to_argv0().to_string_lossy(), "x"), "[git] x");
+
+        // would fail if it ran
+        let dry = Git::new(vec!["not-a-command"]).dry_run(true);
+        assert_eq!(dry.run().stdout().unwrap(), "git not-a-command");
+        assert_eq!(dry.stream().code(), 0);
+        assert_eq!(dry.run_bytes().unwrap(), b"git not-a-command");
+        // as the status checks such as `Repo::verify_tag` see it
+        let output = dry.output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"git not-a-command");
+    }
+
+    #[test]
+    fn test_and_then_git() {
+        let result = Git::new(vec!["--version"])
+            .run()
+            .and_then_git(|success| {
+                assert!(success.stdout.unwrap().starts_with("git version"));
+                Git::new(vec!["not-a-command"]).run()
+            });
+        assert!(result.failed());
+
+        let skipped = Git::new(vec!["not-a-command"])
+            .run()
+            .and_then_git(|_| panic!("should not run after a failure"));
+        assert!(skipped.failed());
+    }
+
+    #[test]
+    fn test_or_else_git() {
+        let recovered = Git::new(vec!["not-a-command"])
+            .run()
+            .or_else_git(|failure| {
+                assert_ne!(failure.code, 0);
+                Git::new(vec!["--version"]).run()
+            });
+        assert!(!recovered.failed());
+
+        let untouched = Git::new(vec!["--version"])
+            .run()
+            .or_else_git(|_| panic!("should not run after a success"));
+        assert!(!untouched.failed());
+    }
+
+    #[test]
+    fn test_git_chain() {
+        let result = git_chain!(
+            Git::new(vec!["--version"]).run(),
+            Git::new(vec!["--version"]).run(),
+        );
+        assert!(!result.failed());
+
+        let mut ran_last = false;
+        let result = git_chain!(
+            Git::new(vec!["--version"]).run(),
+            Git::new(vec!["not-a-command"]).run(),
+            {
+                ran_last = true;
+                Git::new(vec!["--version"]).run()
+            }
+        );
+        assert!(result.failed());
+        assert!(!ran_last);
+    }
+
+    #[test]
+    fn test_run_all() {
+        let commands: Vec<Git> = (0..20)
+            .map(|i| {
+                if i % 5 == 0 {
+                    Git::new(vec!["--version"])
+                } else {
+                    Git::new(vec!["-c", &format!("batch.index={}", i), "config", "batch.index"])
+                }
+            })
+            .collect();
+
+        let mut finished = Vec::new();
+        let results = Git::run_all_with(commands, 4, |index, result| {
+            assert!(!result.failed());
+            finished.push(index);
+        });
+        assert_eq!(results.len(), 20);
+        for (i, result) in results.iter().enumerate() {
+            assert!(!result.failed());
+            let expected = if i % 5 == 0 { "git version".to_string() } else { format!("{}\n", i) };
+            assert!(result.stdout().unwrap().starts_with(&expected));
+        }
+        finished.sort();
+        assert_eq!(finished, (0..20).collect::<Vec<_>>());
+
+        let results = Git::run_all(vec![Git::new(vec!["--version"]); 20], 4);
+        assert!(results.iter().all(|x| !x.failed()));
+        assert!(Git::run_all(vec![], 0).is_empty());
+        assert!(Git::run_all(vec![Git::new(vec!["not-a-command"])], 0)[0].failed());
+    }
+
+    #[test]
+    fn test_run_all_parallelism() {
+        use std::ffi::OsString;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// tracks how many commands are running at once
+        #[derive(Default)]
+        struct Counting {
+            running: AtomicUsize,
+            most: AtomicUsize,
+        }
+
+        impl Executor for Counting {
+            fn execute(&self, _: &str, _: &[OsString], _: &ExecContext) -> Result<Success, Failure> {
+                let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.most.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(Success { stdout: None, code: 0 })
+            }
+        }
+
+        let counting = Arc::new(Counting::default());
+        let commands = vec![Git::new(vec!["status"]).with_executor(counting.clone()); 20];
+        assert!(Git::run_all(commands, 4).iter().all(|x| !x.failed()));
+        let most = counting.most.load(Ordering::SeqCst);
+        assert!(most <= 4 && most > 1, "ran {} at once", most);
+    }
+
+    #[test]
+    fn test_spawn_and_wait() {
+        let running = Git::new(vec!["hash-object", "--stdin"]).stdin("hello\n").spawn().unwrap();
+        assert!(running.pid() > 0);
+        let success = running.wait().unwrap();
+        assert_eq!(success.stdout.unwrap(), "ce013625030ba8dba906f756967f9e9ca394464a\n");
+
+        let failure = Git::new(vec!["not-a-command"]).spawn().unwrap().wait().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert!(failure.stderr.unwrap().contains("not a git command"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_and_kill() {
+        use std::time::{Duration, Instant};
+
+        let slow = Git::new(vec!["-c", "alias.slow=!sleep 10", "slow"]);
+        let start = Instant::now();
+        let mut running = slow.spawn().unwrap();
+        assert!(!running.try_wait().unwrap());
+        running.kill().unwrap();
+        assert!(running.try_wait().unwrap());
+        // killing again is harmless
+        running.kill().unwrap();
+
+        let result = running.wait();
+        assert!(result.was_killed());
+        let failure = result.unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Killed);
+        assert_eq!(failure.code, 137);
+        assert_eq!(failure.signal(), Some(9));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // dropped while running, which kills it
+        let running = slow.spawn().unwrap();
+        let pid = running.pid();
+        drop(running);
+        assert!(!Path::new(&format!("/proc/{}", pid)).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_termination() {
+        // the alias's shell kills git, its parent, with SIGTERM
+        let suicide = Git::new(vec!["-c", "alias.die=!kill -TERM $PPID", "die"]);
+
+        let result = suicide.run();
+        assert!(result.was_killed());
+        assert_eq!(result.code(), 143);
+        assert_eq!(result.unwrap_err().signal(), Some(15));
+
+        let streamed = suicide.quiet(true).stream();
+        assert!(streamed.was_killed());
+        assert_eq!(streamed.code(), 143);
+
+        let exited = Git::new(vec!["not-a-command"]).run();
+        assert!(!exited.was_killed());
+        assert_eq!(exited.unwrap_err().signal(), None);
+        assert!(!Git::new(vec!["--version"]).run().was_killed());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_spawn_and_kill_group() {
+        use std::time::{Duration, Instant};
+
+        let dir = crate::testing::TempDir::new();
+        let pid_file = dir.path().join("pid");
+        // the shell git starts for the alias records its pid, then waits as `sleep`
+        let alias = format!("alias.slow=!echo $$ > '{}'; exec sleep 30", pid_file.display());
+        let mut running = Git::new(vec!["-c", &alias, "slow"]).with_process_group().spawn().unwrap();
+
+        let start = Instant::now();
+        let helper = loop {
+            if let Some(pid) = std::fs::read_to_string(&pid_file).ok().filter(|x| x.ends_with('\n')) {
+                break pid.trim().to_string();
+            }
+            assert!(start.elapsed() < Duration::from_secs(10), "the alias never started");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        running.kill_group().unwrap();
+        assert_eq!(running.wait().unwrap_err().kind, FailureKind::Killed);
+
+        // the helper is reaped by init, so give it a moment
+        let alive = || {
+            std::fs::read_to_string(format!("/proc/{}/stat", helper))
+                .is_ok_and(|stat| !stat.contains(") Z "))
+        };
+        while alive() {
+            assert!(start.elapsed() < Duration::from_secs(10), "the helper outlived its group");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_tee() {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let success = Git::new(vec!["--version"]).tee_to(&mut out, &mut err).unwrap();
+        assert_eq!(success.code, 0);
+        assert_eq!(success.stdout.unwrap().as_bytes(), out.as_slice());
+        assert!(out.starts_with(b"git version"));
+        assert!(err.is_empty());
+
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let failure = Git::new(vec!["both"])
+            .with_global_config("alias.both", "!printf 'one\\ntwo\\n'; echo oops >&2; exit 3")
+            .tee_to(&mut out, &mut err)
+            .unwrap_err();
+        assert_eq!(failure.code, 3);
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert_eq!(out, b"one\ntwo\n");
+        assert_eq!(err, b"oops\n");
+        assert_eq!(failure.stdout.unwrap(), "one\ntwo\n");
+        assert_eq!(failure.stderr.unwrap(), "oops\n");
+    }
+
+    #[test]
+    fn test_run_interactive() {
+        let fixture = fixture::Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+
+        // the editor waits on stdin, which is at end of file straight away
+        let failure = fixture
+            .repo
+            .git(vec!["commit"])
+            .env("GIT_EDITOR", "read line || exit 1; true")
+            .stdin_mode(IoMode::Null)
+            .stderr_mode(IoMode::Piped)
+            .run_interactive()
+            .unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert!(failure.stderr.unwrap().contains("problem with the editor"));
+        assert_eq!(failure.stdout, Some(String::new()));
+
+        let path = fixture.repo.path().join("a.txt");
+        let hashed = fixture
+            .repo
+            .git(vec!["hash-object", "--stdin"])
+            .stdin_mode(IoMode::File(path.clone()))
+            .run_interactive()
+            .unwrap();
+        assert_eq!(hashed.stdout.unwrap().trim(), fixture.repo.rev_parse(":a.txt").unwrap());
+
+        let out = fixture.repo.path().join("out.txt");
+        let inherited = Git::new(vec!["--version"])
+            .stdout_mode(IoMode::File(out.clone()))
+            .run_inherited()
+            .unwrap();
+        assert_eq!(inherited.stdout, None);
+        assert!(std::fs::read_to_string(&out).unwrap().starts_with("git version"));
+
+        let missing = Git::new(vec!["--version"])
+            .stdin_mode(IoMode::File(fixture.repo.path().join("missing")))
+            .run_inherited()
+            .unwrap_err();
+        assert_eq!(missing.kind, FailureKind::Io);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_into_output() {
+        let success = Git::new(vec!["--version"]).run().unwrap();
+        let stdout = success.stdout.clone().unwrap();
+        let output = std::process::Output::from(success);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(output.stdout, stdout.as_bytes());
+        assert!(output.stderr.is_empty());
+
+        let failure = Git::new(vec!["not-a-command"]).run().unwrap_err();
+        let stderr = failure.stderr.clone().unwrap();
+        let output = std::process::Output::from(failure);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(output.stderr, stderr.as_bytes());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            let killed = Failure {
+                stderr: None,
+                stdout: None,
+                code: 137,
+                kind: FailureKind::Killed,
+            };
+            let output = std::process::Output::from(killed);
+            assert_eq!(output.status.code(), None);
+            assert_eq!(output.status.signal(), Some(9));
+        }
+    }
+
+    #[test]
+    fn test_into_bytes() {
+        let success = Git::new(vec!["--version"]).run();
+        let stdout = success.stdout().unwrap();
+        assert_eq!(success.into_stdout_bytes().unwrap(), stdout.as_bytes());
+        assert_eq!(Git::new(vec!["--version"]).run().into_stderr_bytes(), None);
+
+        let failure = Git::new(vec!["not-a-command"]).run();
+        let stderr = failure.stderr().unwrap();
+        assert_eq!(failure.clone().into_stdout_bytes(), Some(Vec::new()));
+        assert_eq!(failure.into_stderr_bytes().unwrap(), stderr.as_bytes());
+
+        let failure = Git::new(vec!["not-a-command"]).run().unwrap_err();
+        assert_eq!(failure.into_stderr_bytes().unwrap(), stderr.as_bytes());
+        let dry = Git::new(vec!["--version"]).dry_run(true).run().unwrap();
+        assert_eq!(dry.into_stdout_bytes().unwrap(), b"git --version");
+    }
+
+    #[test]
+    fn test_success_failure_conversions() {
+        let success = Git::new(vec!["--version"]).run().unwrap();
+        assert_eq!(success.clone().into_result(), Ok(success.clone()));
+        let failure = success.clone().to_failure(Some("unwanted".to_string()));
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert_eq!((failure.code, failure.stdout.clone()), (0, success.stdout.clone()));
+        assert_eq!(failure.stderr.as_deref(), Some("unwanted"));
+        assert_eq!(failure.clone().to_success(), success);
+        assert!(failure.into_result().is_err());
+
+        let failure = Git::new(vec!["not-a-command"]).run().unwrap_err();
+        let code = failure.code;
+        let promoted = failure.to_success();
+        assert_eq!(promoted.code, code);
+        assert_eq!(promoted.stdout, Some(String::new()));
+    }
+
+    #[test]
+    fn test_both() {
+        let failure = Git::new(vec!["not-a-command"]).run();
+        let (stdout, stderr) = failure.both();
+        assert_eq!(stdout, Some(String::new()));
+        assert!(stderr.unwrap().contains("not-a-command"));
+        assert_eq!(Git::new(vec!["--version"]).stream().both(), (None, None));
+    }
+
+    #[test]
+    fn test_is_failure_defaults() {
+        // an implementor outside the crate only needs the original four methods
+        struct Exited(i32);
+        impl IsFailure for Exited {
+            fn failed(&self) -> bool {
+                self.0 != 0
+            }
+            fn code(&self) -> i32 {
+                self.0
+            }
+            fn stdout(&self) -> Option<String> {
+                None
+            }
+            fn stderr(&self) -> Option<String> {
+                None
+            }
+        }
+        assert!(!Exited(137).was_killed());
+        assert_eq!(Exited(1).both(), (None, None));
+    }
+
+    #[test]
+    fn test_map_output() {
+        let success = Git::new(vec!["--version"]).run().map_stdout(|x| x.trim().to_uppercase());
+        assert!(success.stdout().unwrap().starts_with("GIT VERSION"));
+        assert!(!success.stdout().unwrap().ends_with('\n'));
+        let unchanged = Git::new(vec!["--version"]).run();
+        assert_eq!(unchanged.clone().map_stderr(|_| "replaced".to_string()), unchanged);
+
+        let failure = Git::new(vec!["not-a-command"])
+            .run()
+            .map_stderr(|x| x.lines().next().unwrap_or_default().to_string())
+            .map_stdout(|_| "replaced".to_string());
+        assert_eq!(failure.stdout().unwrap(), "replaced");
+        assert!(!failure.stderr().unwrap().contains('\n'));
+        assert_eq!(failure.unwrap_err().kind, FailureKind::Exit);
+
+        // nothing captured, so nothing to map
+        let streamed = Git::new(vec!["--version"]).stream().map_stdout(|_| "replaced".to_string());
+        assert_eq!(streamed.stdout(), None);
+    }
+
+    #[test]
+    fn test_global_args() {
+        let cmd = Git::new(vec!["config", "--get"])
+            .with_global_config("rsgit.test", "a b")
+            .with_global_arg("--no-pager")
+            .arg("rsgit.test");
+        assert_eq!(
+            cmd.to_command_string(),
+            "git -c 'rsgit.test=a b' --no-pager config --get rsgit.test"
+        );
+        assert_eq!(cmd.run().stdout().unwrap(), "a b\n");
+
+        // `version` rejects options it doesn't know, so this only passes if `--no-pager` comes first
+        let version = Git::new(vec!["version"]).with_global_arg("--no-pager").run();
+        assert!(version.stdout().unwrap().starts_with("git version"));
+        let args: Vec<_> = std::process::Command::from(&cmd).get_args().map(|x| x.to_owned()).collect();
+        assert_eq!(args[..4], ["-c", "rsgit.test=a b", "--no-pager", "config"]);
+    }
+
+    #[test]
+    fn test_into_command() {
+        let fixture = fixture::Fixture::new();
+        let git = fixture
+            .repo
+            .git(vec!["config", "--get", "user.name"])
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "user.name")
+            .env("GIT_CONFIG_VALUE_0", "From Env");
+
+        let cmd = std::process::Command::from(&git);
+        assert_eq!(cmd.get_program(), "git");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["config", "--get", "user.name"]);
+        assert_eq!(cmd.get_current_dir(), Some(fixture.repo.path()));
+
+        let output = std::process::Command::from(git).output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"From Env\n");
+    }
+
+    #[test]
+    fn test_output_encoding() {
+        let fixture = fixture::Fixture::new();
+        let latin1 = b"caf\xe9\n";
+        std::fs::write(fixture.repo.path().join("latin1.txt"), latin1).unwrap();
+        let sha = fixture.repo.git(vec!["hash-object", "-w", "latin1.txt"]).run().unwrap();
+        let cat = fixture.repo.git(vec!["cat-file", "-p", sha.stdout.unwrap().trim()]);
+
+        // lossy by default, rather than discarding everything
+        assert_eq!(cat.run().stdout().unwrap(), "caf\u{fffd}\n");
+        assert_eq!(cat.clone().encoding(OutputEncoding::Lossy).run().stdout().unwrap(), "caf\u{fffd}\n");
+
+        let failure = cat.clone().encoding(OutputEncoding::Strict).run().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::InvalidUtf8(latin1.to_vec()));
+        let err = failure.utf8_error().unwrap();
+        assert_eq!((err.valid_up_to(), err.error_len()), (3, Some(1)));
+        assert!(failure.stderr.unwrap().contains("not valid UTF-8"));
+        let valid = Git::new(vec!["--version"]).encoding(OutputEncoding::Strict).run();
+        assert!(valid.stdout().unwrap().starts_with("git version"));
+
+        let bytes = cat.clone().encoding(OutputEncoding::Bytes);
+        assert_eq!(bytes.run().unwrap().stdout, None);
+        assert_eq!(bytes.run_bytes().unwrap(), latin1);
+
+        // failures keep git's message whatever the encoding
+        let failure = bytes.arg("--bogus").run().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert!(failure.utf8_error().is_none());
+        assert!(!failure.stderr.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_git_reader() {
+        use std::io::Read;
+
+        let fixture = fixture::Fixture::new();
+        fixture.write("a.txt", "hello\n");
+        fixture.commit_all("initial");
+
+        let mut reader = fixture
+            .repo
+            .git(vec!["archive", "--format=tar", "HEAD"])
+            .reader()
+            .unwrap();
+        let mut header = [0u8; 512];
+        reader.read_exact(&mut header).unwrap();
+        assert_eq!(&header[257..262], b"ustar");
+
+        // dropped part way through the archive
+        drop(reader);
+    }
+
+    #[test]
+    fn test_index_file() {
+        let fixture = fixture::Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        fixture.write("b.txt", "b\n");
+        let index = fixture.repo.path().join(".git").join("index");
+        let before = std::fs::read(&index).unwrap();
+
+        // run from outside the repository, so only the environment finds it
+        let scratch = fixture::TempDir::new();
+        let git = |args: Vec<&str>| {
+            Git::new(args)
+                .current_dir(scratch.path())
+                .git_dir(fixture.repo.path().join(".git"))
+                .work_tree(fixture.repo.path())
+                .index_file(scratch.path().join("index"))
+        };
+        git(vec!["add", "b.txt"]).run().unwrap();
+        let tree = git(vec!["write-tree"]).run().stdout().unwrap();
+        let listed = git(vec!["ls-tree", "--name-only", tree.trim()]).run().stdout().unwrap();
+        assert_eq!(listed, "b.txt\n");
+
+        assert_eq!(std::fs::read(&index).unwrap(), before);
+        assert!(fixture.repo.git(vec!["diff", "--cached", "--quiet"]).run().is_ok());
+    }
+
+    #[test]
+    fn test_max_output() {
+        let fixture = fixture::Fixture::new();
+        // far more than a pipe holds, so git is still writing when it is killed
+        fixture.write("big.txt", &"0123456789\n".repeat(100_000));
+        fixture.commit_all("initial");
+        let cat = |max_output: usize| fixture.repo.git(vec!["cat-file", "blob", "HEAD:big.txt"]).max_output(max_output);
+
+        let failure = cat(1000).run().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::OutputLimitExceeded(1000));
+        assert_eq!(failure.stdout.unwrap(), "0123456789\n".repeat(100)[..1000]);
+        let failure = cat(50).run_bytes().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::OutputLimitExceeded(50));
+
+        assert_eq!(cat(1_100_000).run_bytes().unwrap().len(), 1_100_000);
+        assert!(fixture.repo.git(vec!["cat-file", "blob", "HEAD:missing"]).max_output(10).run().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_output_lingering_child() {
+        // the alias's shell outlives git, and a `sleep` it leaves behind keeps stderr open
+        let flood = "alias.flood=!sleep 10 & yes | head -c 100000";
+        let start = std::time::Instant::now();
+        let failure = Git::new(vec!["-c", flood, "flood"]).max_output(1000).run().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::OutputLimitExceeded(1000));
+        assert_eq!(failure.stdout.unwrap().len(), 1000);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_no_prompt() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // a server which wants a password for everything
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/private.git", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"private\"\r\n\
+                      Content-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+
+        let fixture = fixture::Fixture::new();
+        let started = std::time::Instant::now();
+        let failure = fixture
+            .repo
+            .git(vec!["fetch", &url])
+            .no_prompt()
+            .run()
+            .unwrap_err();
+        assert!(started.elapsed() < std::time::Duration::from_secs(30));
+        assert_eq!(failure.kind, FailureKind::Exit);
+        assert!(failure.stderr.unwrap().contains("terminal prompts disabled"));
+    }
 }
@@ -0,0 +1,207 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{Failure, Repo};
+
+/// one line of a `.mailmap`, mapping the identity commits were made with to a person's proper one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailmapEntry {
+    /// the name to show instead, if the line gives one
+    pub proper_name: Option<String>,
+    /// the email to show instead, if the line gives one
+    pub proper_email: Option<String>,
+    /// only commits made with this name match, if the line gives one
+    pub commit_name: Option<String>,
+    /// commits made with this email match
+    pub commit_email: String,
+}
+
+/// the entries of every mailmap file, from `Repo::mailmap_entries`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mailmap {
+    pub entries: Vec<MailmapEntry>,
+    /// a `<file>:<line>: <text>` for each line which couldn't be parsed, and so was skipped
+    pub warnings: Vec<String>,
+}
+
+impl Repo {
+    /// Maps a name and email to the identity `.mailmap` gives it, using `check-mailmap`
+    /// 
+    /// Identities with no mailmap entry come back unchanged
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let (name, email) = repo.mailmap_resolve("alice", "alice@old-laptop.local").unwrap();
+    /// println!("{} <{}>", name, email);
+    /// ```
+    pub fn mailmap_resolve(&self, name: &str, email: &str) -> Result<(String, String), Failure> {
+        let output = self
+            .git(vec!["check-mailmap"])
+            .arg(format!("{} <{}>", name, email))
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        let (name, email) = stdout
+            .trim_end()
+            .strip_suffix('>')
+            .and_then(|x| x.rsplit_once('<'))
+            .ok_or_else(|| Failure::parse(format!("unexpected check-mailmap output: {:?}", stdout)))?;
+        Ok((name.trim_end().to_string(), email.to_string()))
+    }
+
+    /// Reads the entries of the work tree's `.mailmap`, then of the file `mailmap.file` names
+    /// 
+    /// Missing files are skipped, as git skips them. Lines which can't be parsed are skipped too, each with a
+    /// warning, rather than failing. A `mailmap.blob`, which bare repositories read from `HEAD:.mailmap`, isn't
+    /// read
+    pub fn mailmap_entries(&self) -> Result<Mailmap, Failure> {
+        let mut files = vec![self.path().join(".mailmap")];
+        // `--path` expands a leading `~`, and a relative path is relative to the work tree, like git's own
+        let configured = self.git(vec!["config", "--path", "--get", "mailmap.file"]).run();
+        if let Ok(output) = configured {
+            let file = output.stdout.unwrap_or_default().trim_end().to_string();
+            if !file.is_empty() {
+                files.push(self.path().join(file));
+            }
+        }
+
+        let mut mailmap = Mailmap::default();
+        for file in files {
+            if let Ok(text) = fs::read_to_string(&file) {
+                parse_mailmap(&file, &text, &mut mailmap);
+            }
+        }
+        Ok(mailmap)
+    }
+}
+
+fn parse_mailmap(file: &Path, text: &str, mailmap: &mut Mailmap) {
+    for (number, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match parse_entry(trimmed) {
+            Some(entry) => mailmap.entries.push(entry),
+            None => mailmap
+                .warnings
+                .push(format!("{}:{}: {}", file.display(), number + 1, line)),
+        }
+    }
+}
+
+/// parses `Proper Name <proper@email> Commit Name <commit@email>`, where either name, and the proper email, may
+/// be left out - anything after the last email is ignored, as git ignores it
+fn parse_entry(line: &str) -> Option<MailmapEntry> {
+    let (first_name, rest) = line.split_once('<')?;
+    let (first_email, rest) = rest.split_once('>')?;
+    let non_empty = |x: &str| Some(x.trim().to_string()).filter(|x| !x.is_empty());
+
+    match rest.split_once('<') {
+        Some((second_name, rest)) => {
+            let (second_email, _) = rest.split_once('>')?;
+            Some(MailmapEntry {
+                proper_name: non_empty(first_name),
+                proper_email: non_empty(first_email),
+                commit_name: non_empty(second_name),
+                commit_email: second_email.trim().to_string(),
+            })
+        }
+        // with one email, only the name is mapped
+        None => Some(MailmapEntry {
+            proper_name: Some(non_empty(first_name)?),
+            proper_email: None,
+            commit_name: None,
+            commit_email: first_email.trim().to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::{AuthorSummary, CommitOptions, RevListOptions};
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = |proper_name: Option<&str>, proper_email: Option<&str>, commit_name: Option<&str>, email| {
+            MailmapEntry {
+                proper_name: proper_name.map(str::to_string),
+                proper_email: proper_email.map(str::to_string),
+                commit_name: commit_name.map(str::to_string),
+                commit_email: String::from(email),
+            }
+        };
+        assert_eq!(
+            parse_entry("Alice <alice@example.com>"),
+            Some(entry(Some("Alice"), None, None, "alice@example.com"))
+        );
+        assert_eq!(
+            parse_entry("<alice@example.com> <alice@old.example.com>"),
+            Some(entry(None, Some("alice@example.com"), None, "alice@old.example.com"))
+        );
+        assert_eq!(
+            parse_entry("Alice <alice@example.com> alice <alice@old.example.com> # laptop"),
+            Some(entry(Some("Alice"), Some("alice@example.com"), Some("alice"), "alice@old.example.com"))
+        );
+        assert_eq!(parse_entry("<alice@example.com>"), None);
+        assert_eq!(parse_entry("Alice alice@example.com"), None);
+        assert_eq!(parse_entry("Alice <alice@example.com> <unclosed"), None);
+    }
+
+    #[test]
+    fn test_mailmap() {
+        let fixture = Fixture::new();
+        for author in ["Alice <alice@example.com>", "alice <alice@old.example.com>", "Bob <bob@example.com>"] {
+            let opts = CommitOptions {
+                allow_empty: true,
+                author: Some(author.to_string()),
+                ..Default::default()
+            };
+            fixture.repo.commit("commit", opts).unwrap();
+        }
+        fixture.write(
+            ".mailmap",
+            "# people\n\nAlice <alice@example.com> <alice@old.example.com>\nnot an entry\n",
+        );
+
+        assert_eq!(
+            fixture.repo.mailmap_resolve("alice", "alice@old.example.com").unwrap(),
+            ("Alice".to_string(), "alice@example.com".to_string())
+        );
+        assert_eq!(
+            fixture.repo.mailmap_resolve("Bob", "bob@example.com").unwrap(),
+            ("Bob".to_string(), "bob@example.com".to_string())
+        );
+
+        let mailmap = fixture.repo.mailmap_entries().unwrap();
+        assert_eq!(mailmap.entries.len(), 1);
+        assert_eq!(mailmap.entries[0].commit_email, "alice@old.example.com");
+        assert_eq!(mailmap.warnings.len(), 1);
+        assert!(mailmap.warnings[0].ends_with(".mailmap:4: not an entry"));
+
+        let summary = |count: u32, name: &str, email: &str| AuthorSummary {
+            count,
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        assert_eq!(
+            fixture.repo.shortlog(None).unwrap(),
+            vec![summary(2, "Alice", "alice@example.com"), summary(1, "Bob", "bob@example.com")]
+        );
+
+        let emails = |use_mailmap: bool| {
+            let pages = fixture.repo.log_pages(RevListOptions::default(), 10).use_mailmap(use_mailmap);
+            pages.map(|x| x.unwrap().author_email).collect::<Vec<_>>()
+        };
+        assert_eq!(emails(false)[1], "alice@old.example.com");
+        assert_eq!(emails(true)[1], "alice@example.com");
+
+        // a second file, from the config
+        fixture.write("more.mailmap", "Bob <bob@example.com> <bobby@example.com>\n");
+        fixture.repo.git(vec!["config", "mailmap.file", "more.mailmap"]).run().unwrap();
+        assert_eq!(fixture.repo.mailmap_entries().unwrap().entries.len(), 2);
+        assert_eq!(fixture.repo.mailmap_resolve("b", "bobby@example.com").unwrap().0, "Bob");
+    }
+}
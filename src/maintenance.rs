@@ -0,0 +1,326 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::{Failure, Repo};
+
+/// object database statistics from `Repo::count_objects` - all sizes are in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectCounts {
+    /// loose objects
+    pub count: u64,
+    /// disk used by loose objects
+    pub size: u64,
+    /// objects in packs
+    pub in_pack: u64,
+    /// number of packs
+    pub packs: u64,
+    /// disk used by packs
+    pub size_pack: u64,
+    /// loose objects which are also packed, and could be pruned
+    pub prune_packable: u64,
+    /// files in the object database which are neither objects nor packs
+    pub garbage: u64,
+    /// disk used by garbage
+    pub size_garbage: u64,
+}
+
+/// a task for `Repo::maintenance_run` - see `git help maintenance`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    /// writes the commit-graph file for the reachable commits (`commit-graph`)
+    CommitGraph,
+    /// runs `git gc --quiet` (`gc`)
+    GarbageCollection,
+    /// packs and prunes loose objects (`loose-objects`)
+    LooseObjects,
+    /// repacks through a multi-pack-index, a little at a time (`incremental-repack`)
+    IncrementalRepack,
+    /// packs loose refs into `packed-refs` (`pack-refs`)
+    PackRefs,
+    /// fetches every remote into `refs/prefetch/`, leaving the remote-tracking branches alone (`prefetch`)
+    Prefetch,
+}
+
+impl MaintenanceTask {
+    /// every task, in the order `maintenance_run(None)` runs them
+    const ALL: [MaintenanceTask; 6] = [
+        MaintenanceTask::Prefetch,
+        MaintenanceTask::LooseObjects,
+        MaintenanceTask::IncrementalRepack,
+        MaintenanceTask::CommitGraph,
+        MaintenanceTask::PackRefs,
+        MaintenanceTask::GarbageCollection,
+    ];
+
+    /// the name git uses for this task, eg `commit-graph`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceTask::CommitGraph => "commit-graph",
+            MaintenanceTask::GarbageCollection => "gc",
+            MaintenanceTask::LooseObjects => "loose-objects",
+            MaintenanceTask::IncrementalRepack => "incremental-repack",
+            MaintenanceTask::PackRefs => "pack-refs",
+            MaintenanceTask::Prefetch => "prefetch",
+        }
+    }
+
+    /// the first git with this task - `pack-refs` came a release after `git maintenance` itself
+    fn min_version(&self) -> (u32, u32, u32) {
+        match self {
+            MaintenanceTask::PackRefs => (2, 31, 0),
+            _ => MAINTENANCE_VERSION,
+        }
+    }
+}
+
+/// options for `Repo::gc`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcOptions {
+    /// repack everything from scratch, slowly (`--aggressive`)
+    pub aggressive: bool,
+    /// only do anything if there are enough loose objects or packs to be worth it (`--auto`)
+    pub auto: bool,
+    /// prune loose unreachable objects older than this date, eg `now` or `2.weeks.ago` (`--prune`)
+    pub prune: Option<String>,
+}
+
+/// what `Repo::gc` did
+/// 
+/// git prints none of this, so it is measured around the run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcResult {
+    /// loose objects which were packed or pruned
+    pub pruned_objects: usize,
+    /// refs moved into `packed-refs`
+    pub packed_refs: usize,
+    /// how long gc took
+    pub repack_duration: Duration,
+}
+
+/// `git maintenance` first shipped in 2.30
+const MAINTENANCE_VERSION: (u32, u32, u32) = (2, 30, 0);
+
+impl Repo {
+    /// Runs a single maintenance `task`, or every task if `None`, using `maintenance run`
+    /// 
+    /// Fails with `FailureKind::UnsupportedVersion` before git 2.30, or before 2.31 for `PackRefs`, which `None`
+    /// includes
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{MaintenanceTask, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.maintenance_run(Some(MaintenanceTask::CommitGraph)).unwrap();
+    /// ```
+    pub fn maintenance_run(&self, task: Option<MaintenanceTask>) -> Result<(), Failure> {
+        let tasks = match task {
+            Some(task) => vec![task],
+            None => MaintenanceTask::ALL.to_vec(),
+        };
+        let required = tasks.iter().map(|x| x.min_version()).max();
+        self.require_version(required.unwrap_or(MAINTENANCE_VERSION))?;
+        self.git(vec!["maintenance", "run", "--quiet"])
+            .args(tasks.iter().map(|x| format!("--task={}", x.as_str())))
+            .run()?;
+        Ok(())
+    }
+
+    /// Registers the repository for background maintenance and starts the scheduler, using `maintenance start`
+    /// 
+    /// The schedule lives in the user's global config and the system's scheduler, eg cron or systemd. Fails with
+    /// `FailureKind::UnsupportedVersion` before git 2.30
+    pub fn maintenance_start(&self) -> Result<(), Failure> {
+        self.require_version(MAINTENANCE_VERSION)?;
+        self.git(vec!["maintenance", "start"]).run()?;
+        Ok(())
+    }
+
+    /// Stops background maintenance for every repository, using `maintenance stop`
+    /// 
+    /// Repositories stay registered, so `maintenance_start` picks them up again. Fails with
+    /// `FailureKind::UnsupportedVersion` before git 2.30
+    pub fn maintenance_stop(&self) -> Result<(), Failure> {
+        self.require_version(MAINTENANCE_VERSION)?;
+        self.git(vec!["maintenance", "stop"]).run()?;
+        Ok(())
+    }
+}
+
+impl Repo {
+    /// Packs objects and refs and prunes unreachable objects, using `gc --quiet`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{GcOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = GcOptions {
+    ///     prune: Some("now".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let result = repo.gc(opts).unwrap();
+    /// println!("{} loose objects gone in {:?}", result.pruned_objects, result.repack_duration);
+    /// ```
+    pub fn gc(&self, opts: GcOptions) -> Result<GcResult, Failure> {
+        let mut cmd = self.git(vec!["gc", "--quiet"]);
+        if opts.aggressive {
+            cmd = cmd.arg("--aggressive");
+        }
+        if opts.auto {
+            cmd = cmd.arg("--auto");
+        }
+        if let Some(prune) = &opts.prune {
+            cmd = cmd.arg(format!("--prune={}", prune));
+        }
+
+        let loose_before = self.count_objects()?.count;
+        let packed_before = self.packed_ref_count()?;
+        let start = Instant::now();
+        cmd.run()?;
+        let repack_duration = start.elapsed();
+
+        Ok(GcResult {
+            pruned_objects: loose_before.saturating_sub(self.count_objects()?.count) as usize,
+            packed_refs: self.packed_ref_count()?.saturating_sub(packed_before),
+            repack_duration,
+        })
+    }
+
+    /// the number of refs in `packed-refs`, skipping its header and peeled tag lines
+    fn packed_ref_count(&self) -> Result<usize, Failure> {
+        let output = self.git(vec!["rev-parse", "--git-path", "packed-refs"]).run()?;
+        let path = self
+            .path()
+            .join(PathBuf::from(output.stdout.unwrap_or_default().trim()));
+        // a repository with nothing packed yet has no file
+        let contents = fs::read_to_string(path).unwrap_or_default();
+        Ok(contents
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^') && !line.is_empty())
+            .count())
+    }
+
+    /// Counts objects and the disk they use, using `count-objects -v`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let counts = repo.count_objects().unwrap();
+    /// println!("{} loose objects, {} packs", counts.count, counts.packs);
+    /// ```
+    pub fn count_objects(&self) -> Result<ObjectCounts, Failure> {
+        let output = self.git(vec!["count-objects", "-v"]).run()?;
+        let mut counts = ObjectCounts::default();
+
+        for line in output.stdout.unwrap_or_default().lines() {
+            let (key, value) = match line.split_once(": ") {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let value: u64 = value
+                .trim()
+                .parse()
+                .map_err(|_| Failure::parse(format!("unexpected count-objects line: {:?}", line)))?;
+
+            // git reports sizes in KiB
+            match key {
+                "count" => counts.count = value,
+                "size" => counts.size = value * 1024,
+                "in-pack" => counts.in_pack = value,
+                "packs" => counts.packs = value,
+                "size-pack" => counts.size_pack = value * 1024,
+                "prune-packable" => counts.prune_packable = value,
+                "garbage" => counts.garbage = value,
+                "size-garbage" => counts.size_garbage = value * 1024,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_count_objects() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("one");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("two");
+
+        let loose = fixture.repo.count_objects().unwrap();
+        assert!(loose.count >= 6, "{:?}", loose);
+        assert_eq!(loose.packs, 0);
+
+        fixture.repo.git(vec!["gc", "-q"]).run().unwrap();
+        let packed = fixture.repo.count_objects().unwrap();
+        assert_eq!(packed.count, 0, "{:?}", packed);
+        assert_eq!(packed.packs, 1);
+        assert!(packed.in_pack >= 6);
+        assert!(packed.size_pack >= 1024);
+        assert_eq!(packed.size_pack % 1024, 0);
+    }
+
+    #[test]
+    fn test_gc() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("one");
+        fixture.repo.git(vec!["tag", "v1"]).run().unwrap();
+        let loose = fixture.repo.count_objects().unwrap().count as usize;
+
+        let result = fixture.repo.gc(GcOptions::default()).unwrap();
+        assert_eq!(result.pruned_objects, loose);
+        assert_eq!(result.packed_refs, 2);
+        assert_eq!(fixture.repo.count_objects().unwrap().count, 0);
+
+        // nothing left to do
+        let opts = GcOptions {
+            auto: true,
+            ..Default::default()
+        };
+        let again = fixture.repo.gc(opts).unwrap();
+        assert_eq!((again.pruned_objects, again.packed_refs), (0, 0));
+    }
+
+    #[test]
+    fn test_maintenance_run() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("one");
+        // the task writes a split commit-graph
+        let graph = fixture.repo.path().join(".git/objects/info/commit-graphs");
+
+        fixture.repo.maintenance_run(Some(MaintenanceTask::CommitGraph)).unwrap();
+        assert!(graph.exists());
+
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("two");
+        fixture.repo.maintenance_run(None).unwrap();
+        assert_eq!(fixture.repo.count_objects().unwrap().count, 0);
+    }
+
+    #[test]
+    fn test_maintenance_run_version() {
+        use crate::testing::MockExecutor;
+        use crate::FailureKind;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_success(["rev-parse", "--git-dir"], ".git\n")
+            .on_success(["--version"], "git version 2.30.2\n")
+            .on_success(["maintenance"], "");
+        let repo = Repo::open_with_executor("/not/a/real/repo", mock.clone()).unwrap();
+
+        // every task, so `pack-refs` too, which 2.30 doesn't have
+        let failure = repo.maintenance_run(None).unwrap_err();
+        assert!(matches!(failure.kind, FailureKind::UnsupportedVersion { required: (2, 31, 0), .. }));
+        assert!(repo.maintenance_run(Some(MaintenanceTask::PackRefs)).is_err());
+        assert!(mock.calls().iter().all(|x| x.args[0] != "maintenance"));
+
+        repo.maintenance_run(Some(MaintenanceTask::CommitGraph)).unwrap();
+        assert!(mock.calls().iter().any(|x| x.args[0] == "maintenance"));
+    }
+}
@@ -0,0 +1,393 @@
+use std::path::PathBuf;
+
+use crate::{shell_quote, Failure, Git, Repo};
+
+/// options for `Repo::merge`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// always make a merge commit, even when a fast-forward is possible (`--no-ff`)
+    pub no_ff: bool,
+    /// fail rather than make a merge commit (`--ff-only`)
+    pub ff_only: bool,
+    /// the message for the merge commit - git's default is used if `None`
+    pub message: Option<String>,
+}
+
+/// the outcome of `Repo::merge`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeResult {
+    /// the branch was already merged, so nothing changed
+    UpToDate,
+    /// the current branch was moved forward to the merged branch
+    FastForward,
+    /// a merge commit was made - holds its SHA
+    Merged(String),
+    /// the merge stopped with these paths conflicted - resolve and commit, or `merge --abort`
+    Conflicts(Vec<PathBuf>),
+}
+
+/// the outcome of `Repo::rebase`, `rebase_continue` and `rebase_skip`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseResult {
+    /// every commit was replayed
+    Done,
+    /// the rebase stopped with these paths conflicted - resolve and `rebase_continue`, or `rebase_abort`
+    Conflicts(Vec<PathBuf>),
+}
+
+/// one line of the todo list for `Repo::rebase_interactive`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseInstruction {
+    /// keep the commit as it is
+    Pick(String),
+    /// fold the commit into the one before, keeping both messages
+    Squash(String),
+    /// fold the commit into the one before, keeping only the earlier message
+    Fixup(String),
+    /// keep the commit, with this message instead
+    Reword(String, String),
+    /// leave the commit out
+    Drop(String),
+}
+
+/// the todo list for `Repo::rebase_interactive`, oldest commit first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RebasePlan {
+    pub instructions: Vec<RebaseInstruction>,
+}
+
+impl RebasePlan {
+    /// An empty plan
+    pub fn new() -> RebasePlan {
+        RebasePlan::default()
+    }
+
+    /// Adds `RebaseInstruction::Pick`
+    pub fn pick(mut self, sha: &str) -> RebasePlan {
+        self.instructions.push(RebaseInstruction::Pick(sha.to_string()));
+        self
+    }
+
+    /// Adds `RebaseInstruction::Squash`
+    pub fn squash(mut self, sha: &str) -> RebasePlan {
+        self.instructions.push(RebaseInstruction::Squash(sha.to_string()));
+        self
+    }
+
+    /// Adds `RebaseInstruction::Fixup`
+    pub fn fixup(mut self, sha: &str) -> RebasePlan {
+        self.instructions.push(RebaseInstruction::Fixup(sha.to_string()));
+        self
+    }
+
+    /// Adds `RebaseInstruction::Reword`
+    pub fn reword(mut self, sha: &str, message: &str) -> RebasePlan {
+        self.instructions
+            .push(RebaseInstruction::Reword(sha.to_string(), message.to_string()));
+        self
+    }
+
+    /// Adds `RebaseInstruction::Drop`
+    pub fn drop(mut self, sha: &str) -> RebasePlan {
+        self.instructions.push(RebaseInstruction::Drop(sha.to_string()));
+        self
+    }
+
+    /// the lines of the todo list - a reword is a pick, then an `exec` amending the message, since the message
+    /// can't go in the todo list itself, and an editor can't tell which commit it was opened for
+    fn todo(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for instruction in &self.instructions {
+            match instruction {
+                RebaseInstruction::Pick(sha) => lines.push(format!("pick {}", sha)),
+                RebaseInstruction::Squash(sha) => lines.push(format!("squash {}", sha)),
+                RebaseInstruction::Fixup(sha) => lines.push(format!("fixup {}", sha)),
+                RebaseInstruction::Drop(sha) => lines.push(format!("drop {}", sha)),
+                RebaseInstruction::Reword(sha, message) => {
+                    // `%b` turns the escaped newlines back, keeping the exec on one line
+                    let escaped = message.replace('\\', "\\\\").replace('\n', "\\n");
+                    lines.push(format!("pick {}", sha));
+                    lines.push(format!(
+                        "exec printf '%b' {} | git commit --amend --quiet -F -",
+                        shell_quote(&escaped)
+                    ));
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl Repo {
+    /// Merges `branch` into the current branch
+    /// 
+    /// Conflicts are returned as `MergeResult::Conflicts`, leaving the merge in progress, rather than as a
+    /// `Failure`, which is kept for everything else, such as local changes the merge would overwrite
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{MergeOptions, MergeResult, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// match repo.merge("feature", MergeOptions::default()).unwrap() {
+    ///     MergeResult::Conflicts(paths) => println!("{} files to fix up", paths.len()),
+    ///     result => println!("{:?}", result),
+    /// }
+    /// ```
+    pub fn merge(&self, branch: &str, opts: MergeOptions) -> Result<MergeResult, Failure> {
+        let mut cmd = self.git(vec!["merge", "--quiet"]);
+        if opts.no_ff {
+            cmd = cmd.arg("--no-ff");
+        }
+        if opts.ff_only {
+            cmd = cmd.arg("--ff-only");
+        }
+        cmd = match &opts.message {
+            Some(message) => cmd.arg("-m").arg(message),
+            None => cmd.arg("--no-edit"),
+        };
+
+        let before = self.rev_parse("HEAD")?;
+        if let Some(conflicts) = self.run_or_conflicts(cmd.args(vec!["--end-of-options", branch]))? {
+            return Ok(MergeResult::Conflicts(conflicts));
+        }
+
+        let after = self.rev_parse("HEAD")?;
+        if after == before {
+            Ok(MergeResult::UpToDate)
+        } else if self.rev_parse(&format!("{}^{{commit}}", branch))? == after {
+            Ok(MergeResult::FastForward)
+        } else {
+            Ok(MergeResult::Merged(after))
+        }
+    }
+
+    /// Abandons a merge in progress, putting back the state from before it, using `merge --abort`
+    pub fn merge_abort(&self) -> Result<(), Failure> {
+        self.git(vec!["merge", "--abort"]).run()?;
+        Ok(())
+    }
+
+    /// Replays the current branch's commits on top of `upstream`
+    /// 
+    /// Conflicts are returned as `RebaseResult::Conflicts`, leaving the rebase in progress
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{RebaseResult, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// if let RebaseResult::Conflicts(_) = repo.rebase("origin/main").unwrap() {
+    ///     repo.rebase_abort().unwrap();
+    /// }
+    /// ```
+    pub fn rebase(&self, upstream: &str) -> Result<RebaseResult, Failure> {
+        self.run_rebase(self.git(vec!["rebase", "--quiet", "--end-of-options", upstream]))
+    }
+
+    /// Rewrites the commits after `base` following `plan`, using `rebase --interactive`
+    /// 
+    /// The plan replaces the whole todo list, so commits it leaves out are dropped, and it may reorder them. It is
+    /// handed to git through `GIT_SEQUENCE_EDITOR`, and squashed messages are kept as git combines them. Conflicts
+    /// are returned as `RebaseResult::Conflicts`, leaving the rebase in progress, as with `rebase`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{RebasePlan, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let first = repo.rev_parse("HEAD~1").unwrap();
+    /// let second = repo.rev_parse("HEAD").unwrap();
+    /// let plan = RebasePlan::new().reword(&first, "add the parser").fixup(&second);
+    /// repo.rebase_interactive("HEAD~2", plan).unwrap();
+    /// ```
+    pub fn rebase_interactive(&self, base: &str, plan: RebasePlan) -> Result<RebaseResult, Failure> {
+        // run by git as `<editor> "$@"`, so this writes the todo list over the file it is given
+        let lines: Vec<String> = plan.todo().iter().map(|x| shell_quote(x)).collect();
+        let editor = format!("printf '%s\\n' {} >", lines.join(" "));
+        let cmd = self
+            .git(vec!["rebase", "--interactive", "--quiet", "--end-of-options", base])
+            .env("GIT_SEQUENCE_EDITOR", editor);
+        self.run_rebase(cmd)
+    }
+
+    /// Carries on with a stopped rebase once its conflicts are resolved and staged, using `rebase --continue`
+    /// 
+    /// Commit messages are kept as they are, rather than opening an editor
+    pub fn rebase_continue(&self) -> Result<RebaseResult, Failure> {
+        self.run_rebase(self.git(vec!["rebase", "--continue"]))
+    }
+
+    /// Drops the commit a rebase stopped on, and carries on, using `rebase --skip`
+    pub fn rebase_skip(&self) -> Result<RebaseResult, Failure> {
+        self.run_rebase(self.git(vec!["rebase", "--skip"]))
+    }
+
+    /// Abandons a rebase in progress, putting the branch back where it was, using `rebase --abort`
+    pub fn rebase_abort(&self) -> Result<(), Failure> {
+        self.git(vec!["rebase", "--abort"]).run()?;
+        Ok(())
+    }
+
+    fn run_rebase(&self, cmd: Git) -> Result<RebaseResult, Failure> {
+        match self.run_or_conflicts(cmd.env("GIT_EDITOR", "true"))? {
+            Some(conflicts) => Ok(RebaseResult::Conflicts(conflicts)),
+            None => Ok(RebaseResult::Done),
+        }
+    }
+
+    /// runs `cmd`, turning a failure which left unmerged paths into the list of them
+    fn run_or_conflicts(&self, cmd: Git) -> Result<Option<Vec<PathBuf>>, Failure> {
+        let failure = match cmd.run() {
+            Ok(_) => return Ok(None),
+            Err(failure) => failure,
+        };
+        let output = self
+            .git(vec!["diff", "--name-only", "--diff-filter=U", "-z"])
+            .run()?;
+        let conflicts: Vec<PathBuf> = output
+            .stdout
+            .unwrap_or_default()
+            .split('\0')
+            .filter(|x| !x.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if conflicts.is_empty() {
+            Err(failure)
+        } else {
+            Ok(Some(conflicts))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// `master` and `feature` both change `a.txt` from `base`, and `feature` adds `b.txt`
+    fn diverged() -> Fixture {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "base\n");
+        fixture.commit_all("base");
+        fixture.repo.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        fixture.write("a.txt", "feature\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("feature");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.write("a.txt", "master\n");
+        fixture.commit_all("master");
+        fixture
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let fixture = diverged();
+        let result = fixture.repo.merge("feature", MergeOptions::default()).unwrap();
+        assert_eq!(result, MergeResult::Conflicts(vec![PathBuf::from("a.txt")]));
+
+        fixture.repo.merge_abort().unwrap();
+        assert!(fixture.repo.is_clean().unwrap());
+        assert!(fixture.repo.merge("not-a-branch", MergeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_merge_outcomes() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("base");
+        fixture.repo.git(vec!["branch", "feature"]).run().unwrap();
+        fixture.repo.git(vec!["checkout", "-q", "feature"]).run().unwrap();
+        fixture.write("b.txt", "b\n");
+        let feature = fixture.commit_all("feature");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+
+        let opts = MergeOptions {
+            no_ff: true,
+            message: Some("merge feature".to_string()),
+            ..Default::default()
+        };
+        let merged = match fixture.repo.merge("feature", opts).unwrap() {
+            MergeResult::Merged(sha) => sha,
+            result => panic!("expected a merge commit, got {:?}", result),
+        };
+        assert_eq!(fixture.repo.head_commit().unwrap().subject, "merge feature");
+        assert_eq!(fixture.repo.rev_parse("HEAD").unwrap(), merged);
+        assert_eq!(
+            fixture.repo.merge("feature", MergeOptions::default()).unwrap(),
+            MergeResult::UpToDate
+        );
+
+        fixture.repo.git(vec!["checkout", "-q", "-b", "behind", &format!("{}~1", feature)]).run().unwrap();
+        assert_eq!(
+            fixture.repo.merge("feature", MergeOptions::default()).unwrap(),
+            MergeResult::FastForward
+        );
+        assert_eq!(fixture.repo.rev_parse("HEAD").unwrap(), feature);
+    }
+
+    #[test]
+    fn test_rebase_conflicts() {
+        let fixture = diverged();
+        fixture.repo.git(vec!["checkout", "-q", "feature"]).run().unwrap();
+
+        let result = fixture.repo.rebase("master").unwrap();
+        assert_eq!(result, RebaseResult::Conflicts(vec![PathBuf::from("a.txt")]));
+        fixture.repo.rebase_abort().unwrap();
+        assert!(fixture.repo.is_clean().unwrap());
+
+        assert!(matches!(fixture.repo.rebase("master").unwrap(), RebaseResult::Conflicts(_)));
+        fixture.write("a.txt", "both\n");
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+        assert_eq!(fixture.repo.rebase_continue().unwrap(), RebaseResult::Done);
+
+        let head = fixture.repo.head_commit().unwrap();
+        assert_eq!(head.subject, "feature");
+        assert_eq!(head.parents, vec![fixture.repo.rev_parse("master").unwrap()]);
+    }
+
+    #[test]
+    fn test_rebase_plan_todo() {
+        let plan = RebasePlan::new()
+            .pick("aaa")
+            .squash("bbb")
+            .fixup("ccc")
+            .drop("ddd")
+            .reword("eee", "it's\nnew \\o/");
+        assert_eq!(
+            plan.todo(),
+            [
+                "pick aaa",
+                "squash bbb",
+                "fixup ccc",
+                "drop ddd",
+                "pick eee",
+                r"exec printf '%b' 'it'\''s\nnew \\o/' | git commit --amend --quiet -F -",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rebase_interactive() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let base = fixture.commit_all("base");
+        fixture.write("a.txt", "one\n");
+        let one = fixture.commit_all("one");
+        fixture.write("a.txt", "two\n");
+        let two = fixture.commit_all("two");
+        fixture.write("b.txt", "three\n");
+        let three = fixture.commit_all("three");
+
+        let plan = RebasePlan::new().pick(&one).squash(&two).drop(&three);
+        assert_eq!(fixture.repo.rebase_interactive(&base, plan).unwrap(), RebaseResult::Done);
+        let head = fixture.repo.head_commit().unwrap();
+        assert_eq!(head.parents, vec![base.clone()]);
+        assert_eq!(head.subject, "one");
+        assert!(head.body.contains("two"));
+        assert_eq!(std::fs::read_to_string(fixture.repo.path().join("a.txt")).unwrap(), "two\n");
+        assert!(!fixture.repo.path().join("b.txt").exists());
+
+        let squashed = head.sha;
+        let plan = RebasePlan::new().reword(&squashed, "combined\n\nit's one and two");
+        assert_eq!(fixture.repo.rebase_interactive(&base, plan).unwrap(), RebaseResult::Done);
+        let head = fixture.repo.head_commit().unwrap();
+        assert_eq!(head.parents, vec![base]);
+        assert_eq!(head.subject, "combined");
+        assert_eq!(head.body.trim(), "it's one and two");
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Failure, Git, Repo};
+
+impl Repo {
+    /// Reads the note attached to `rev`, or `None` if it has none
+    /// 
+    /// `ref_name` picks the notes ref, eg `review` or `refs/notes/review` - defaults to `refs/notes/commits`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// if let Some(note) = repo.notes_get("HEAD", None).unwrap() {
+    ///     println!("{}", note);
+    /// }
+    /// ```
+    pub fn notes_get(&self, rev: &str, ref_name: Option<&str>) -> Result<Option<String>, Failure> {
+        // a missing note exits 1, a revision which doesn't resolve exits 128
+        match self.notes(ref_name).args(vec!["show", rev]).run() {
+            Ok(output) => Ok(Some(output.stdout.unwrap_or_default())),
+            Err(failure) if failure.code == 1 => Ok(None),
+            Err(failure) => Err(failure),
+        }
+    }
+
+    /// Attaches `message` as the note on `rev`, replacing any note it already has
+    /// 
+    /// `ref_name` is as for `notes_get`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.notes_add("HEAD", "Reviewed-by: A <a@example.com>", None).unwrap();
+    /// ```
+    pub fn notes_add(
+        &self,
+        rev: &str,
+        message: &str,
+        ref_name: Option<&str>,
+    ) -> Result<(), Failure> {
+        self.notes(ref_name)
+            .args(vec!["add", "--force", "--file=-", rev])
+            .stdin(message)
+            .run()?;
+        Ok(())
+    }
+
+    /// Appends `message` to the note on `rev` as a new paragraph, creating the note if there is none
+    /// 
+    /// `ref_name` is as for `notes_get`
+    pub fn notes_append(
+        &self,
+        rev: &str,
+        message: &str,
+        ref_name: Option<&str>,
+    ) -> Result<(), Failure> {
+        self.notes(ref_name)
+            .args(vec!["append", "--file=-", rev])
+            .stdin(message)
+            .run()?;
+        Ok(())
+    }
+
+    fn notes(&self, ref_name: Option<&str>) -> Git {
+        let cmd = self.git(vec!["notes"]);
+        match ref_name {
+            Some(ref_name) => cmd.arg(format!("--ref={}", ref_name)),
+            None => cmd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_notes() {
+        let fixture = Fixture::new();
+        let first = fixture.commit_all("first");
+        fixture.commit_all("second");
+
+        assert_eq!(fixture.repo.notes_get("HEAD", None).unwrap(), None);
+
+        fixture.repo.notes_add("HEAD", "built ok", None).unwrap();
+        assert_eq!(fixture.repo.notes_get("HEAD", None).unwrap().as_deref(), Some("built ok\n"));
+        fixture.repo.notes_append("HEAD", "deployed", None).unwrap();
+        assert_eq!(
+            fixture.repo.notes_get("HEAD", None).unwrap().as_deref(),
+            Some("built ok\n\ndeployed\n")
+        );
+        fixture.repo.notes_add("HEAD", "rebuilt", None).unwrap();
+        assert_eq!(fixture.repo.notes_get("HEAD", None).unwrap().as_deref(), Some("rebuilt\n"));
+
+        assert_eq!(fixture.repo.notes_get(&first, None).unwrap(), None);
+        assert_eq!(fixture.repo.notes_get("HEAD", Some("review")).unwrap(), None);
+        fixture.repo.notes_append(&first, "lgtm", Some("review")).unwrap();
+        assert_eq!(
+            fixture.repo.notes_get(&first, Some("refs/notes/review")).unwrap().as_deref(),
+            Some("lgtm\n")
+        );
+        assert_eq!(fixture.repo.notes_get(&first, None).unwrap(), None);
+
+        assert!(fixture.repo.notes_get("not-a-rev", None).is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use std::io::{self, BufRead, BufReader, Read, Take};
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, FailureKind, GitReader, Repo};
+
+/// the type of a git object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Blob,
+    Tree,
+    /// a commit inside a tree is a submodule
+    Commit,
+    /// an annotated tag
+    Tag,
+}
+
+impl ObjectKind {
+    /// the name git uses for this kind, eg `blob`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectKind::Blob => "blob",
+            ObjectKind::Tree => "tree",
+            ObjectKind::Commit => "commit",
+            ObjectKind::Tag => "tag",
+        }
+    }
+
+    pub(crate) fn parse(name: &str) -> Option<ObjectKind> {
+        match name {
+            "blob" => Some(ObjectKind::Blob),
+            "tree" => Some(ObjectKind::Tree),
+            "commit" => Some(ObjectKind::Commit),
+            "tag" => Some(ObjectKind::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// an object read by `Repo::cat_file`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitObject {
+    pub kind: ObjectKind,
+    /// size of `content` in bytes
+    pub size: u64,
+    /// raw object content - trees are in git's binary format
+    pub content: Vec<u8>,
+}
+
+/// The content of an object, read as git produces it - see `Repo::read_object_stream`
+/// 
+/// Reading stops at the end of the object. Dropping the stream early kills `cat-file` and reaps it
+#[derive(Debug)]
+pub struct ObjectStream {
+    kind: ObjectKind,
+    size: u64,
+    content: Take<BufReader<GitReader>>,
+}
+
+impl ObjectStream {
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    /// the size of the whole object in bytes, as reported by git
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for ObjectStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.content.read(buf)
+    }
+}
+
+/// a single entry of `Repo::ls_tree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// unix mode, eg `0o100644`
+    pub mode: u32,
+    pub kind: ObjectKind,
+    pub sha: String,
+    /// path relative to the repository root
+    pub path: PathBuf,
+}
+
+impl Repo {
+    /// Computes the SHA git would assign to `data`, without writing it to the object database
+    /// 
+    /// `kind` defaults to `ObjectKind::Blob`, other kinds must be validly formatted objects
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let sha = repo.hash_content("hello\n", None).unwrap();
+    /// ```
+    pub fn hash_content<T: AsRef<[u8]>>(
+        &self,
+        data: T,
+        kind: Option<ObjectKind>,
+    ) -> Result<String, Failure> {
+        let output = self
+            .git(vec!["hash-object"])
+            .args(type_arg(kind))
+            .arg("--stdin")
+            .stdin(data)
+            .run()?;
+        Ok(output.stdout.unwrap_or_default().trim().to_string())
+    }
+
+    /// Computes the SHA git would assign to the file at `path`, without writing it to the object database
+    /// 
+    /// git reads the file itself, so nothing is piped through this process
+    pub fn hash_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        kind: Option<ObjectKind>,
+    ) -> Result<String, Failure> {
+        let output = self
+            .git(vec!["hash-object"])
+            .args(type_arg(kind))
+            .arg("--")
+            .arg_os(path.as_ref())
+            .run()?;
+        Ok(output.stdout.unwrap_or_default().trim().to_string())
+    }
+
+    /// Reads any object with its type and size, using `cat-file --batch`
+    /// 
+    /// A revision which does not exist fails with `FailureKind::Parse`, as git itself reports it as `missing`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let object = repo.cat_file("HEAD:README.md").unwrap();
+    /// println!("{:?} of {} bytes", object.kind, object.size);
+    /// ```
+    pub fn cat_file(&self, rev: &str) -> Result<GitObject, Failure> {
+        let output = self
+            .git(vec!["cat-file", "--batch"])
+            .stdin(format!("{}\n", rev))
+            .run_bytes()?;
+
+        let newline = output
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| Failure::parse("cat-file returned no header"))?;
+        let (kind, size) = parse_batch_header(&output[..newline])?;
+
+        let start = newline + 1;
+        let content = output
+            .get(start..start + size as usize)
+            .ok_or_else(|| Failure::parse("cat-file output was truncated"))?
+            .to_vec();
+
+        Ok(GitObject {
+            kind,
+            size,
+            content,
+        })
+    }
+
+    /// Reads an object as `cat_file` does, but streaming its content rather than holding it all in memory
+    /// 
+    /// Only as much is read from git as the caller asks for, so large blobs can be copied straight to a file.
+    /// Unlike most methods, this always runs the real git, whatever executor is set
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let mut stream = repo.read_object_stream("HEAD:assets/video.mp4").unwrap();
+    /// let mut file = File::create("video.mp4").unwrap();
+    /// let copied = std::io::copy(&mut stream, &mut file).unwrap();
+    /// assert_eq!(copied, stream.size());
+    /// ```
+    pub fn read_object_stream(&self, rev: &str) -> Result<ObjectStream, Failure> {
+        let io_failure =
+            |err: io::Error| Failure::new(FailureKind::Io, format!("failed to run git cat-file: {}", err));
+        let reader = self
+            .git(vec!["cat-file", "--batch"])
+            .stdin(format!("{}\n", rev))
+            .quiet(true)
+            .reader()
+            .map_err(io_failure)?;
+
+        let mut reader = BufReader::new(reader);
+        let mut header = Vec::new();
+        reader.read_until(b'\n', &mut header).map_err(io_failure)?;
+        if header.pop() != Some(b'\n') {
+            return Err(Failure::parse("cat-file returned no header"));
+        }
+        let (kind, size) = parse_batch_header(&header)?;
+
+        Ok(ObjectStream {
+            kind,
+            size,
+            content: reader.take(size),
+        })
+    }
+
+    /// Reads the raw content of the blob `sha`, using `cat-file blob`
+    pub fn cat_file_content(&self, sha: &str) -> Result<Vec<u8>, Failure> {
+        self.git(vec!["cat-file", "blob", sha]).run_bytes()
+    }
+
+    /// Looks up the type of the object `sha`, using `cat-file -t`
+    pub fn cat_file_type(&self, sha: &str) -> Result<ObjectKind, Failure> {
+        let output = self.git(vec!["cat-file", "-t", sha]).run()?;
+        let name = output.stdout.unwrap_or_default();
+        ObjectKind::parse(name.trim())
+            .ok_or_else(|| Failure::parse(format!("unknown object type: {:?}", name)))
+    }
+
+    /// Looks up the size in bytes of the object `sha`, using `cat-file -s`
+    pub fn cat_file_size(&self, sha: &str) -> Result<usize, Failure> {
+        let output = self.git(vec!["cat-file", "-s", sha]).run()?;
+        let size = output.stdout.unwrap_or_default();
+        size.trim()
+            .parse()
+            .map_err(|_| Failure::parse(format!("unexpected object size: {:?}", size)))
+    }
+
+    /// Lists the tree at `rev`, optionally limited to `path`
+    /// 
+    /// With `recursive`, subtrees are expanded and only their blobs are returned
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for entry in repo.ls_tree("HEAD", None, true).unwrap() {
+    ///     println!("{} {}", entry.sha, entry.path.display());
+    /// }
+    /// ```
+    pub fn ls_tree(
+        &self,
+        rev: &str,
+        path: Option<&Path>,
+        recursive: bool,
+    ) -> Result<Vec<TreeEntry>, Failure> {
+        let mut cmd = self.git(vec!["ls-tree", "-z", "--full-name"]);
+        if recursive {
+            cmd = cmd.arg("-r");
+        }
+        cmd = cmd.args(vec!["--end-of-options", rev]);
+        if let Some(path) = path {
+            cmd = cmd.arg("--").arg_os(path);
+        }
+
+        let output = cmd.run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        stdout
+            .split('\0')
+            .filter(|record| !record.is_empty())
+            .map(parse_tree_entry)
+            .collect()
+    }
+}
+
+/// `-t <type>` - hash-object has no `--type=` spelling
+fn type_arg(kind: Option<ObjectKind>) -> Vec<&'static str> {
+    match kind {
+        Some(kind) => vec!["-t", kind.as_str()],
+        None => vec![],
+    }
+}
+
+/// parses `<mode> SP <type> SP <sha> TAB <path>`
+fn parse_tree_entry(record: &str) -> Result<TreeEntry, Failure> {
+    let parsed = record.split_once('\t').and_then(|(meta, path)| {
+        let mut fields = meta.split(' ');
+        let mode = u32::from_str_radix(fields.next()?, 8).ok()?;
+        let kind = ObjectKind::parse(fields.next()?)?;
+        let sha = fields.next()?.to_string();
+        Some(TreeEntry {
+            mode,
+            kind,
+            sha,
+            path: PathBuf::from(path),
+        })
+    });
+
+    parsed.ok_or_else(|| Failure::parse(format!("unexpected ls-tree output: {:?}", record)))
+}
+
+/// parses `<sha> <type> <size>` from `cat-file --batch` - a `<rev> missing` header is a `FailureKind::Parse`
+fn parse_batch_header(header: &[u8]) -> Result<(ObjectKind, u64), Failure> {
+    let header = String::from_utf8_lossy(header);
+    let fields: Vec<&str> = header.split(' ').collect();
+    match fields.as_slice() {
+        [_, kind, size] => ObjectKind::parse(kind).zip(size.parse::<u64>().ok()),
+        _ => None,
+    }
+    .ok_or_else(|| Failure::parse(format!("cat-file: {}", header)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::FailureKind;
+
+    #[test]
+    fn test_ls_tree_root() {
+        let fixture = Fixture::new();
+        fixture.write("README", "hello\n");
+        fixture.write("src/main.rs", "fn main() {}\n");
+        fixture.commit_all("initial");
+
+        let entries = fixture.repo.ls_tree("HEAD", None, false).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].path, PathBuf::from("README"));
+        assert_eq!(entries[0].kind, ObjectKind::Blob);
+        assert_eq!(entries[0].mode, 0o100644);
+        assert_eq!(entries[0].sha.len(), 40);
+
+        assert_eq!(entries[1].path, PathBuf::from("src"));
+        assert_eq!(entries[1].kind, ObjectKind::Tree);
+        assert_eq!(entries[1].mode, 0o040000);
+
+        let recursive = fixture.repo.ls_tree("HEAD", None, true).unwrap();
+        let paths: Vec<_> = recursive.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("README"), PathBuf::from("src/main.rs")]);
+
+        let limited = fixture
+            .repo
+            .ls_tree("HEAD", Some(Path::new("src")), true)
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[test]
+    fn test_read_object_stream() {
+        let fixture = Fixture::new();
+        let big: Vec<u8> = (0..1_000_000u32).map(|x| (x % 251) as u8).collect();
+        std::fs::write(fixture.repo.path().join("big.bin"), &big).unwrap();
+        fixture.write("small.txt", "small\n");
+        fixture.commit_all("initial");
+
+        let mut stream = fixture.repo.read_object_stream("HEAD:big.bin").unwrap();
+        assert_eq!(stream.kind(), ObjectKind::Blob);
+        assert_eq!(stream.size(), big.len() as u64);
+        let mut start = [0u8; 16];
+        stream.read_exact(&mut start).unwrap();
+        assert_eq!(start, big[..16]);
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, big[16..]);
+
+        // the stream ends with the object, and drops cleanly part way through
+        let mut stream = fixture.repo.read_object_stream("HEAD:small.txt").unwrap();
+        let mut content = String::new();
+        stream.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "small\n");
+        let mut partial = fixture.repo.read_object_stream("HEAD:big.bin").unwrap();
+        partial.read_exact(&mut start).unwrap();
+        drop(partial);
+
+        let failure = fixture.repo.read_object_stream("HEAD:missing").unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Parse);
+    }
+
+    #[test]
+    fn test_hash_content() {
+        let fixture = Fixture::new();
+
+        // the well-known SHA of the blob "hello\n"
+        let sha = fixture.repo.hash_content("hello\n", None).unwrap();
+        assert_eq!(sha, "ce013625030ba8dba906f756967f9e9ca394464a");
+
+        let blob = fixture
+            .repo
+            .hash_content("hello\n", Some(ObjectKind::Blob))
+            .unwrap();
+        assert_eq!(blob, sha);
+
+        // the empty tree
+        let tree = fixture.repo.hash_content("", Some(ObjectKind::Tree)).unwrap();
+        assert_eq!(tree, "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let fixture = Fixture::new();
+        fixture.write("hello.txt", "hello\n");
+
+        let sha = fixture.repo.hash_file("hello.txt", None).unwrap();
+        assert_eq!(sha, fixture.repo.hash_content("hello\n", None).unwrap());
+
+        assert!(fixture.repo.hash_file("missing.txt", None).is_err());
+    }
+
+    #[test]
+    fn test_cat_file_round_trip() {
+        let fixture = Fixture::new();
+        let data: &[u8] = b"binary \x00\xff data\n";
+
+        let sha = fixture.repo.hash_content(data, None).unwrap();
+        let written = fixture
+            .repo
+            .git(vec!["hash-object", "-w", "--stdin"])
+            .stdin(data)
+            .run()
+            .unwrap();
+        assert_eq!(written.stdout.unwrap().trim(), sha);
+
+        assert_eq!(fixture.repo.cat_file_content(&sha).unwrap(), data);
+        assert_eq!(fixture.repo.cat_file_type(&sha).unwrap(), ObjectKind::Blob);
+        assert_eq!(fixture.repo.cat_file_size(&sha).unwrap(), data.len());
+    }
+
+    #[test]
+    fn test_cat_file() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "line one\nline two\n");
+        fixture.commit_all("initial");
+
+        let object = fixture.repo.cat_file("HEAD:a.txt").unwrap();
+        assert_eq!(object.kind, ObjectKind::Blob);
+        assert_eq!(object.size, 18);
+        assert_eq!(object.content, b"line one\nline two\n");
+
+        let commit = fixture.repo.cat_file("HEAD").unwrap();
+        assert_eq!(commit.kind, ObjectKind::Commit);
+        assert_eq!(commit.size as usize, commit.content.len());
+
+        let missing = fixture.repo.cat_file("HEAD:missing.txt").unwrap_err();
+        assert_eq!(missing.kind, FailureKind::Parse);
+    }
+
+    #[test]
+    fn test_cat_file_type() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        fixture
+            .repo
+            .git(vec!["tag", "-a", "-m", "release", "v1"])
+            .run()
+            .unwrap();
+
+        assert_eq!(fixture.repo.cat_file_type("HEAD").unwrap(), ObjectKind::Commit);
+        assert_eq!(fixture.repo.cat_file_type("HEAD^{tree}").unwrap(), ObjectKind::Tree);
+        assert_eq!(fixture.repo.cat_file_type("v1").unwrap(), ObjectKind::Tag);
+        assert!(fixture.repo.cat_file_content("missing").is_err());
+    }
+
+    #[test]
+    fn test_ls_tree_bad_rev() {
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+
+        let result = fixture.repo.ls_tree("no-such-rev", None, false);
+        assert_eq!(result.unwrap_err().kind, FailureKind::Exit);
+    }
+}
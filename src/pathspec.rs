@@ -0,0 +1,108 @@
+use std::ffi::OsString;
+use std::fmt;
+
+/// a pathspec with magic signatures, eg `:(top,icase)*.md` - see `Git::with_pathspec`
+/// 
+/// magic is applied in the order it was added, and adding the same magic twice has no effect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSpec {
+    pattern: String,
+    magic: Vec<&'static str>,
+}
+
+impl PathSpec {
+    /// Creates a pathspec matching `pattern`, with no magic
+    /// # Examples
+    /// ```rust
+    /// use rsgit::PathSpec;
+    /// let spec = PathSpec::new("**/*.rs").top_level().glob();
+    /// assert_eq!(spec.to_string(), ":(top,glob)**/*.rs");
+    /// ```
+    pub fn new(pattern: &str) -> PathSpec {
+        PathSpec {
+            pattern: pattern.to_string(),
+            magic: Vec::new(),
+        }
+    }
+
+    /// Matches from the root of the working tree, rather than the current directory (`top`)
+    pub fn top_level(self) -> PathSpec {
+        self.with_magic("top")
+    }
+
+    /// Treats the pattern as a shell glob, where `*` does not cross `/` and `**` does (`glob`)
+    pub fn glob(self) -> PathSpec {
+        self.with_magic("glob")
+    }
+
+    /// Matches case-insensitively (`icase`)
+    pub fn icase(self) -> PathSpec {
+        self.with_magic("icase")
+    }
+
+    /// Excludes matching paths from the other pathspecs (`exclude`)
+    pub fn exclude(self) -> PathSpec {
+        self.with_magic("exclude")
+    }
+
+    fn with_magic(mut self, magic: &'static str) -> PathSpec {
+        if !self.magic.contains(&magic) {
+            self.magic.push(magic);
+        }
+        self
+    }
+}
+
+impl fmt::Display for PathSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.magic.is_empty() {
+            write!(f, "{}", self.pattern)
+        } else {
+            write!(f, ":({}){}", self.magic.join(","), self.pattern)
+        }
+    }
+}
+
+impl From<PathSpec> for OsString {
+    fn from(spec: PathSpec) -> OsString {
+        OsString::from(spec.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_pathspec_magic() {
+        assert_eq!(PathSpec::new("src").to_string(), "src");
+        assert_eq!(PathSpec::new("*.md").icase().to_string(), ":(icase)*.md");
+        assert_eq!(
+            PathSpec::new("target").exclude().top_level().exclude().to_string(),
+            ":(exclude,top)target"
+        );
+
+        let spec: OsString = PathSpec::new("**/*.rs").top_level().glob().into();
+        assert_eq!(spec, OsString::from(":(top,glob)**/*.rs"));
+    }
+
+    #[test]
+    fn test_with_pathspec() {
+        let fixture = Fixture::new();
+        fixture.write("README.MD", "");
+        fixture.write("docs/guide.md", "");
+        fixture.write("src/lib.rs", "");
+        fixture.commit_all("files");
+
+        let cmd = fixture
+            .repo
+            .git(vec!["ls-files"])
+            .with_pathspec(PathSpec::new("*.md").icase())
+            .with_pathspec(PathSpec::new("docs").exclude());
+        assert_eq!(cmd.command, vec!["ls-files", "--", ":(icase)*.md", ":(exclude)docs"]);
+
+        let output = cmd.run().unwrap();
+        assert_eq!(output.stdout.unwrap(), "README.MD\n");
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::{Failure, Repo};
+
+/// a series of commits, `from..to` - the commits reachable from `to` but not from `from`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommitRange {
+    pub from: String,
+    pub to: String,
+}
+
+impl CommitRange {
+    pub fn new<F: ToString, T: ToString>(from: F, to: T) -> CommitRange {
+        CommitRange {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CommitRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.from, self.to)
+    }
+}
+
+/// how a commit in one series relates to the other - see `Repo::range_diff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RangeDiffStatus {
+    /// the patch is the same in both series (`=`)
+    Equal,
+    /// the patch was matched, but changed (`!`)
+    Rewritten,
+    /// the patch is only in one series (`<` or `>`)
+    Unmatched,
+}
+
+/// a single line of `Repo::range_diff`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDiffEntry {
+    /// 1-based position in the first series, as git prints it - `None` for a patch only in the second
+    pub left_index: Option<usize>,
+    /// 1-based position in the second series - `None` for a patch only in the first
+    pub right_index: Option<usize>,
+
+    /// from `0.0` to `1.0` - the share of the diff between the two patches which is unchanged
+    /// 
+    /// git does not report a score itself, so this is worked out from the context lines it prints, and is always
+    /// `1.0` for `Equal` and `0.0` for `Unmatched`
+    pub match_quality: f32,
+    pub status: RangeDiffStatus,
+
+    /// the subject of the commit, from the second series where there is one
+    pub subject: String,
+}
+
+impl Repo {
+    /// Matches up the commits of two versions of a patch series, using `range-diff`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{CommitRange, RangeDiffStatus, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let before = CommitRange::new("main", "feature@{1}");
+    /// let after = CommitRange::new("main", "feature");
+    /// for entry in repo.range_diff(&before, &after).unwrap() {
+    ///     if entry.status == RangeDiffStatus::Rewritten {
+    ///         println!("changed: {}", entry.subject);
+    ///     }
+    /// }
+    /// ```
+    pub fn range_diff(
+        &self,
+        range1: &CommitRange,
+        range2: &CommitRange,
+    ) -> Result<Vec<RangeDiffEntry>, Failure> {
+        let output = self
+            .git(vec!["range-diff", "--no-color"])
+            .arg(range1)
+            .arg(range2)
+            .run()?;
+        parse_range_diff(&output.stdout.unwrap_or_default())
+    }
+}
+
+/// parses `<n>:  <sha> <status> <n>:  <sha> <subject>` lines, and the indented diff after `!` lines
+fn parse_range_diff(stdout: &str) -> Result<Vec<RangeDiffEntry>, Failure> {
+    let mut entries: Vec<RangeDiffEntry> = Vec::new();
+    // (unchanged, changed) lines in the diff under the last entry
+    let mut counts = (0usize, 0usize);
+
+    for line in stdout.lines() {
+        if let Some(diff) = line.strip_prefix("    ") {
+            match diff.chars().next() {
+                Some('+') | Some('-') => counts.1 += 1,
+                Some(' ') => counts.0 += 1,
+                _ => {}
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        finish_entry(entries.last_mut(), counts);
+        counts = (0, 0);
+        entries.push(parse_entry(line)?);
+    }
+    finish_entry(entries.last_mut(), counts);
+    Ok(entries)
+}
+
+fn parse_entry(line: &str) -> Result<RangeDiffEntry, Failure> {
+    let error = || Failure::parse(format!("unexpected range-diff line: {:?}", line));
+    let index = |field: &str| -> Result<Option<usize>, Failure> {
+        match field.strip_suffix(':').ok_or_else(error)? {
+            "-" => Ok(None),
+            number => number.parse().map(Some).map_err(|_| error()),
+        }
+    };
+
+    let mut fields = line.split_whitespace();
+    let mut next = || fields.next().ok_or_else(error);
+    let left_index = index(next()?)?;
+    next()?;
+    let status = match next()? {
+        "=" => RangeDiffStatus::Equal,
+        "!" => RangeDiffStatus::Rewritten,
+        "<" | ">" => RangeDiffStatus::Unmatched,
+        _ => return Err(error()),
+    };
+    let right_index = index(next()?)?;
+    next()?;
+    let subject = fields.collect::<Vec<_>>().join(" ");
+
+    Ok(RangeDiffEntry {
+        left_index,
+        right_index,
+        match_quality: match status {
+            RangeDiffStatus::Equal | RangeDiffStatus::Rewritten => 1.0,
+            RangeDiffStatus::Unmatched => 0.0,
+        },
+        status,
+        subject,
+    })
+}
+
+fn finish_entry(entry: Option<&mut RangeDiffEntry>, (unchanged, changed): (usize, usize)) {
+    if let Some(entry) = entry {
+        if entry.status == RangeDiffStatus::Rewritten && unchanged + changed > 0 {
+            entry.match_quality = unchanged as f32 / (unchanged + changed) as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_parse_range_diff() {
+        let stdout = "\
+ 1:  1111111 =  1:  aaaaaaa first patch
+ 2:  2222222 !  2:  bbbbbbb second  patch
+    @@ a.txt
+     @@
+      one
+    --two
+    -+2
+    ++TWO
+     three
+ 3:  3333333 <  -:  ------- dropped
+ -:  ------- > 10:  ccccccc added
+";
+        let entries = parse_range_diff(stdout).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].status, RangeDiffStatus::Equal);
+        assert_eq!((entries[0].left_index, entries[0].right_index), (Some(1), Some(1)));
+        assert_eq!(entries[0].match_quality, 1.0);
+        assert_eq!(entries[0].subject, "first patch");
+
+        assert_eq!(entries[1].status, RangeDiffStatus::Rewritten);
+        assert_eq!(entries[1].match_quality, 0.5);
+
+        assert_eq!(entries[2].status, RangeDiffStatus::Unmatched);
+        assert_eq!((entries[2].left_index, entries[2].right_index), (Some(3), None));
+        assert_eq!(entries[2].match_quality, 0.0);
+
+        assert_eq!((entries[3].left_index, entries[3].right_index), (None, Some(10)));
+        assert_eq!(entries[3].subject, "added");
+
+        assert!(parse_range_diff("nonsense\n").is_err());
+    }
+
+    #[test]
+    fn test_range_diff() {
+        let fixture = Fixture::new();
+        fixture.write("base.txt", "base\n");
+        let base = fixture.commit_all("base");
+        fixture.write("a.txt", "one\n");
+        fixture.commit_all("add a");
+        let lines: Vec<String> = (0..20).map(|x| x.to_string()).collect();
+        fixture.write("b.txt", &lines.join("\n"));
+        fixture.commit_all("add b");
+        fixture.write("c.txt", "c\n");
+        let old = fixture.commit_all("add c");
+
+        fixture.repo.git(vec!["checkout", "-q", "-b", "rewritten", &base]).run().unwrap();
+        fixture.write("a.txt", "one\n");
+        fixture.commit_all("add a");
+        fixture.write("b.txt", &lines.join("\n").replace("10", "ten"));
+        fixture.commit_all("add b");
+        fixture.write("d.txt", "d\n");
+        let new = fixture.commit_all("add d");
+
+        let entries = fixture
+            .repo
+            .range_diff(&CommitRange::new(&base, &old), &CommitRange::new(&base, &new))
+            .unwrap();
+        let statuses: Vec<_> = entries
+            .iter()
+            .map(|x| (x.left_index, x.right_index, x.status))
+            .collect();
+        assert!(statuses.contains(&(Some(1), Some(1), RangeDiffStatus::Equal)));
+        assert!(statuses.contains(&(Some(2), Some(2), RangeDiffStatus::Rewritten)));
+        assert!(statuses.contains(&(Some(3), None, RangeDiffStatus::Unmatched)));
+        assert!(statuses.contains(&(None, Some(3), RangeDiffStatus::Unmatched)));
+
+        let rewritten = entries.iter().find(|x| x.status == RangeDiffStatus::Rewritten).unwrap();
+        assert_eq!(rewritten.subject, "add b");
+        assert!(rewritten.match_quality > 0.0 && rewritten.match_quality < 1.0);
+    }
+}
@@ -0,0 +1,430 @@
+use crate::{Failure, ObjectKind, Repo};
+
+/// a field `Repo::refs` can read for each ref, from `for-each-ref`'s `%(field)` atoms
+/// 
+/// The `Peeled` fields describe what an annotated tag points at, and are missing for other refs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RefField {
+    /// the full name, eg `refs/heads/main` (`refname`)
+    RefName,
+    /// the SHA the ref points at (`objectname`)
+    ObjectName,
+    /// the kind of object the ref points at (`objecttype`)
+    ObjectType,
+    /// the full name of the upstream, for a branch with one (`upstream`)
+    Upstream,
+    /// seconds since the unix epoch, for a ref to a commit (`committerdate`)
+    CommitterDate,
+    /// the first line of the commit or tag message (`subject`)
+    Subject,
+    /// `*objectname`
+    PeeledObjectName,
+    /// `*objecttype`
+    PeeledObjectType,
+    /// `*committerdate`
+    PeeledCommitterDate,
+    /// `*subject`
+    PeeledSubject,
+}
+
+impl RefField {
+    /// the atom, as it goes in `--format`
+    fn atom(&self) -> &'static str {
+        match self {
+            RefField::RefName => "refname",
+            RefField::ObjectName => "objectname",
+            RefField::ObjectType => "objecttype",
+            RefField::Upstream => "upstream",
+            RefField::CommitterDate => "committerdate:unix",
+            RefField::Subject => "subject",
+            RefField::PeeledObjectName => "*objectname",
+            RefField::PeeledObjectType => "*objecttype",
+            RefField::PeeledCommitterDate => "*committerdate:unix",
+            RefField::PeeledSubject => "*subject",
+        }
+    }
+}
+
+/// options for `Repo::refs`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefsOptions {
+    /// the fields to read - the others are `None` in every `RefInfo`
+    pub fields: Vec<RefField>,
+
+    /// only refs under these prefixes or matching these globs, eg `refs/remotes` or `refs/heads/release-*`
+    pub patterns: Vec<String>,
+
+    /// order by this field, rather than by name (`--sort`)
+    pub sort: Option<RefField>,
+
+    /// reverse the order, newest first for a date
+    pub descending: bool,
+
+    /// stop after this many refs (`--count`)
+    pub count: Option<usize>,
+}
+
+/// a ref listed by `Repo::refs`, holding the fields asked for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefInfo {
+    /// each field asked for which git gave a value
+    values: Vec<(RefField, String)>,
+}
+
+impl RefInfo {
+    /// The raw value of `field`, or `None` if it wasn't asked for or git gave nothing
+    pub fn get(&self, field: RefField) -> Option<&str> {
+        self.values.iter().find(|(x, _)| *x == field).map(|(_, value)| value.as_str())
+    }
+
+    pub fn ref_name(&self) -> Option<&str> {
+        self.get(RefField::RefName)
+    }
+
+    pub fn object_name(&self) -> Option<&str> {
+        self.get(RefField::ObjectName)
+    }
+
+    pub fn object_type(&self) -> Option<ObjectKind> {
+        self.get(RefField::ObjectType).and_then(ObjectKind::parse)
+    }
+
+    pub fn upstream(&self) -> Option<&str> {
+        self.get(RefField::Upstream)
+    }
+
+    /// seconds since the unix epoch
+    pub fn committer_date(&self) -> Option<i64> {
+        self.get(RefField::CommitterDate).and_then(|x| x.parse().ok())
+    }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.get(RefField::Subject)
+    }
+
+    pub fn peeled_object_name(&self) -> Option<&str> {
+        self.get(RefField::PeeledObjectName)
+    }
+
+    pub fn peeled_object_type(&self) -> Option<ObjectKind> {
+        self.get(RefField::PeeledObjectType).and_then(ObjectKind::parse)
+    }
+
+    /// seconds since the unix epoch
+    pub fn peeled_committer_date(&self) -> Option<i64> {
+        self.get(RefField::PeeledCommitterDate).and_then(|x| x.parse().ok())
+    }
+
+    pub fn peeled_subject(&self) -> Option<&str> {
+        self.get(RefField::PeeledSubject)
+    }
+}
+
+impl Repo {
+    /// Reads the ref a symbolic ref points at, eg `HEAD` to `refs/heads/main`
+    /// 
+    /// Fails if `ref_name` is not a symbolic ref, such as a detached `HEAD`
+    pub fn symbolic_ref_get(&self, ref_name: &str) -> Result<String, Failure> {
+        let output = self.git(vec!["symbolic-ref", "--", ref_name]).run()?;
+        Ok(output.stdout.unwrap_or_default().trim().to_string())
+    }
+
+    /// Resolves a symbolic ref, eg `HEAD` to `refs/heads/main` - shorthand for `symbolic_ref_get`
+    pub fn symbolic_ref(&self, name: &str) -> Result<String, Failure> {
+        self.symbolic_ref_get(name)
+    }
+
+    /// Names `sha` relative to the nearest ref, eg `tags/v1.0~2`, using `name-rev --name-only`
+    /// 
+    /// Fails if `sha` can't be resolved, or no ref reaches it
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// println!("HEAD is {}", repo.name_rev("HEAD").unwrap());
+    /// ```
+    pub fn name_rev(&self, sha: &str) -> Result<String, Failure> {
+        let output = self
+            .git(vec!["name-rev", "--name-only", "--no-undefined", sha])
+            .run()?;
+        let name = output.stdout.unwrap_or_default().trim().to_string();
+
+        // an unresolvable sha is only a warning to name-rev
+        if name.is_empty() {
+            return Err(Failure::parse(format!("could not resolve {}", sha)));
+        }
+        Ok(name)
+    }
+
+    /// Points the symbolic ref `ref_name` at `target`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.symbolic_ref_set("HEAD", "refs/heads/main").unwrap();
+    /// ```
+    pub fn symbolic_ref_set(&self, ref_name: &str, target: &str) -> Result<(), Failure> {
+        self.git(vec!["symbolic-ref", "--", ref_name, target]).run()?;
+        Ok(())
+    }
+
+    /// Points `ref_name` at `new_sha`
+    /// 
+    /// With `expected_old_sha`, the update only happens if the ref currently points there, making it an atomic
+    /// compare-and-swap - otherwise it fails and the ref is left alone
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let old = repo.rev_parse("refs/heads/deploy").unwrap();
+    /// let new = repo.rev_parse("HEAD").unwrap();
+    /// repo.update_ref("refs/heads/deploy", &new, Some(&old)).unwrap();
+    /// ```
+    pub fn update_ref(
+        &self,
+        ref_name: &str,
+        new_sha: &str,
+        expected_old_sha: Option<&str>,
+    ) -> Result<(), Failure> {
+        let mut cmd = self.git(vec!["update-ref", "--", ref_name, new_sha]);
+        if let Some(old) = expected_old_sha {
+            cmd = cmd.arg(old);
+        }
+        cmd.run()?;
+        Ok(())
+    }
+
+    /// Formats each ref matching `pattern` with `format`, using `for-each-ref`
+    /// 
+    /// `format` takes `for-each-ref`'s `%(field)` placeholders, and may span lines. `pattern` is a ref prefix such as
+    /// `refs/tags`, or a glob such as `refs/heads/release-*` - with `None`, every ref is listed
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for line in repo.for_each_ref(Some("refs/heads"), "%(refname:short) %(objectname:short)").unwrap() {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    pub fn for_each_ref(&self, pattern: Option<&str>, format: &str) -> Result<Vec<String>, Failure> {
+        // each ref ends with a NUL, before git's newline, so a format can have newlines of its own
+        let cmd = self
+            .git(vec!["for-each-ref", &format!("--format={}%00", format), "--end-of-options"])
+            .args(pattern);
+        let output = cmd.run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        Ok(stdout
+            .split_terminator("\0\n")
+            .map(|x| x.to_string())
+            .collect())
+    }
+
+    /// Lists refs with the fields `opts` picks, using `for-each-ref`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{RefField, RefsOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = RefsOptions {
+    ///     fields: vec![RefField::RefName, RefField::CommitterDate],
+    ///     patterns: vec!["refs/remotes".to_string()],
+    ///     sort: Some(RefField::CommitterDate),
+    ///     descending: true,
+    ///     count: Some(5),
+    /// };
+    /// for info in repo.refs(opts).unwrap() {
+    ///     println!("{:?} {:?}", info.ref_name(), info.committer_date());
+    /// }
+    /// ```
+    pub fn refs(&self, opts: RefsOptions) -> Result<Vec<RefInfo>, Failure> {
+        let atoms: Vec<String> = opts.fields.iter().map(|x| format!("%({})", x.atom())).collect();
+        let format = format!("--format={}%00", atoms.join("%1f"));
+        let mut cmd = self.git(vec!["for-each-ref", &format]);
+        if let Some(sort) = opts.sort {
+            // the `:unix` of dates doesn't change the order, so it's left on
+            let order = if opts.descending { "-" } else { "" };
+            cmd = cmd.arg(format!("--sort={}{}", order, sort.atom()));
+        } else if opts.descending {
+            cmd = cmd.arg("--sort=-refname");
+        }
+        if let Some(count) = opts.count {
+            cmd = cmd.arg(format!("--count={}", count));
+        }
+
+        let output = cmd.arg("--end-of-options").args(&opts.patterns).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        stdout
+            .split_terminator("\0\n")
+            .map(|record| {
+                let values: Vec<&str> = record.split('\x1f').collect();
+                if values.len() != opts.fields.len().max(1) {
+                    return Err(Failure::parse(format!("unexpected for-each-ref record: {:?}", record)));
+                }
+                Ok(RefInfo {
+                    values: opts
+                        .fields
+                        .iter()
+                        .zip(values)
+                        .filter(|(_, value)| !value.is_empty())
+                        .map(|(field, value)| (*field, value.to_string()))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_symbolic_ref() {
+        let fixture = Fixture::new();
+        let sha = fixture.commit_all("initial");
+
+        fixture
+            .repo
+            .symbolic_ref_set("HEAD", "refs/heads/other")
+            .unwrap();
+        assert_eq!(
+            fixture.repo.symbolic_ref_get("HEAD").unwrap(),
+            "refs/heads/other"
+        );
+
+        fixture
+            .repo
+            .git(vec!["update-ref", "--no-deref", "HEAD", &sha])
+            .run()
+            .unwrap();
+        assert!(fixture.repo.symbolic_ref_get("HEAD").is_err());
+    }
+
+    #[test]
+    fn test_name_rev() {
+        let fixture = Fixture::new();
+        let first = fixture.commit_all("one");
+        fixture.commit_all("two");
+        fixture.repo.git(vec!["tag", "v1"]).run().unwrap();
+
+        assert_eq!(fixture.repo.name_rev(&first).unwrap(), "tags/v1~1");
+        assert_eq!(fixture.repo.symbolic_ref("HEAD").unwrap(), "refs/heads/master");
+
+        assert!(fixture.repo.name_rev("nonsense").is_err());
+        assert!(fixture.repo.symbolic_ref("refs/heads/master").is_err());
+    }
+
+    #[test]
+    fn test_for_each_ref() {
+        let fixture = Fixture::new();
+        let sha = fixture.commit_all("one");
+        fixture.repo.git(vec!["branch", "feature"]).run().unwrap();
+        fixture.repo.git(vec!["tag", "v1"]).run().unwrap();
+
+        let branches = fixture
+            .repo
+            .for_each_ref(Some("refs/heads/*"), "%(refname:short)")
+            .unwrap();
+        assert_eq!(branches, ["feature", "master"]);
+        let all = fixture.repo.for_each_ref(None, "%(refname)%0a%(objectname)").unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2], format!("refs/tags/v1\n{}", sha));
+        assert!(fixture.repo.for_each_ref(Some("refs/remotes"), "%(refname)").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_refs() {
+        let fixture = Fixture::new();
+        let commit_at = |message: &str, date: &str| {
+            fixture
+                .repo
+                .git(vec!["commit", "-q", "--allow-empty", "-m", message])
+                .env("GIT_COMMITTER_DATE", date)
+                .run()
+                .unwrap();
+            fixture.repo.rev_parse("HEAD").unwrap()
+        };
+        let old = commit_at("old", "2020-01-01T00:00:00Z");
+        fixture.repo.git(vec!["branch", "zzz-old"]).run().unwrap();
+        fixture.repo.git(vec!["tag", "-a", "-m", "release", "v1"]).run().unwrap();
+        let new = commit_at("new", "2024-01-01T00:00:00Z");
+        fixture.repo.update_ref("refs/remotes/origin/master", &old, None).unwrap();
+        fixture.repo.git(vec!["branch", "-q", "--set-upstream-to=zzz-old"]).run().unwrap();
+
+        let opts = RefsOptions {
+            fields: vec![
+                RefField::RefName,
+                RefField::ObjectName,
+                RefField::ObjectType,
+                RefField::Upstream,
+                RefField::CommitterDate,
+                RefField::Subject,
+                RefField::PeeledObjectName,
+            ],
+            sort: Some(RefField::CommitterDate),
+            descending: true,
+            ..Default::default()
+        };
+        let refs = fixture.repo.refs(opts.clone()).unwrap();
+        let names: Vec<&str> = refs.iter().map(|x| x.ref_name().unwrap()).collect();
+        // the tag has no committer date of its own, so sorts last
+        assert_eq!(
+            names,
+            ["refs/heads/master", "refs/heads/zzz-old", "refs/remotes/origin/master", "refs/tags/v1"]
+        );
+        assert_eq!(refs[0].object_name(), Some(new.as_str()));
+        assert_eq!(refs[0].upstream(), Some("refs/heads/zzz-old"));
+        assert_eq!(refs[0].committer_date(), Some(1704067200));
+        assert_eq!(refs[0].subject(), Some("new"));
+        assert_eq!(refs[0].peeled_object_name(), None);
+        assert_eq!(refs[1].upstream(), None);
+        assert_eq!(refs[1].committer_date(), Some(1577836800));
+        assert_eq!(refs[3].object_type(), Some(ObjectKind::Tag));
+        assert_eq!(refs[3].subject(), Some("release"));
+        assert_eq!(refs[3].peeled_object_name(), Some(old.as_str()));
+        assert_eq!(refs[3].get(RefField::PeeledObjectType), None);
+
+        let remotes = RefsOptions {
+            fields: vec![RefField::RefName],
+            patterns: vec!["refs/remotes".to_string(), "refs/tags/*".to_string()],
+            ..Default::default()
+        };
+        let refs = fixture.repo.refs(remotes).unwrap();
+        let names: Vec<&str> = refs.iter().filter_map(|x| x.get(RefField::RefName)).collect();
+        assert_eq!(names, ["refs/remotes/origin/master", "refs/tags/v1"]);
+        let oldest = RefsOptions {
+            sort: Some(RefField::CommitterDate),
+            descending: false,
+            count: Some(2),
+            ..opts
+        };
+        let refs = fixture.repo.refs(oldest).unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].ref_name(), Some("refs/tags/v1"));
+        assert_eq!(refs[1].committer_date(), Some(1577836800));
+    }
+
+    #[test]
+    fn test_update_ref_cas() {
+        let fixture = Fixture::new();
+        let first = fixture.commit_all("one");
+        let second = fixture.commit_all("two");
+
+        fixture
+            .repo
+            .update_ref("refs/heads/deploy", &first, None)
+            .unwrap();
+        fixture
+            .repo
+            .update_ref("refs/heads/deploy", &second, Some(&first))
+            .unwrap();
+        assert_eq!(fixture.repo.rev_parse("refs/heads/deploy").unwrap(), second);
+
+        // deploy no longer points at `first`, so the swap must fail
+        let stale = fixture
+            .repo
+            .update_ref("refs/heads/deploy", &first, Some(&first));
+        assert!(stale.is_err());
+        assert_eq!(fixture.repo.rev_parse("refs/heads/deploy").unwrap(), second);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{Failure, Repo};
+
+impl Repo {
+    /// Lists the names of every remote, eg `origin`
+    pub fn remotes(&self) -> Result<Vec<String>, Failure> {
+        let output = self.git(vec!["remote"]).run()?;
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Adds the remote `name`, fetching from and pushing to `url`
+    /// 
+    /// Fails with git's message if `name` is already a remote
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.remote_add("upstream", "https://github.com/werdl/gitrs.git").unwrap();
+    /// ```
+    pub fn remote_add(&self, name: &str, url: &str) -> Result<(), Failure> {
+        self.git(vec!["remote", "add", "--", name, url]).run()?;
+        Ok(())
+    }
+
+    /// Removes the remote `name`, along with its remote-tracking branches and config
+    /// 
+    /// Fails with git's message if there is no such remote
+    pub fn remote_remove(&self, name: &str) -> Result<(), Failure> {
+        self.git(vec!["remote", "remove", "--", name]).run()?;
+        Ok(())
+    }
+
+    /// Points the remote `name` at `url`
+    pub fn remote_set_url(&self, name: &str, url: &str) -> Result<(), Failure> {
+        self.git(vec!["remote", "set-url", "--", name, url]).run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::Fixture;
+
+    fn url(fixture: &Fixture, name: &str) -> String {
+        let output = fixture.repo.git(vec!["remote", "get-url", name]).run().unwrap();
+        output.stdout.unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn test_remote_add_set_url_and_remove() {
+        let fixture = Fixture::new();
+        assert!(fixture.repo.remotes().unwrap().is_empty());
+
+        fixture.repo.remote_add("origin", "https://example.com/a.git").unwrap();
+        fixture.repo.remote_add("upstream", "https://example.com/b.git").unwrap();
+        assert_eq!(fixture.repo.remotes().unwrap(), vec!["origin", "upstream"]);
+        assert_eq!(url(&fixture, "origin"), "https://example.com/a.git");
+        let failure = fixture.repo.remote_add("origin", "https://example.com/c.git").unwrap_err();
+        assert!(failure.stderr.unwrap().contains("already exists"));
+
+        fixture.repo.remote_set_url("origin", "git@example.com:a.git").unwrap();
+        assert_eq!(url(&fixture, "origin"), "git@example.com:a.git");
+
+        fixture.repo.remote_remove("origin").unwrap();
+        assert_eq!(fixture.repo.remotes().unwrap(), vec!["upstream"]);
+        assert!(fixture.repo.remote_remove("origin").is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::executor::ExecutorHandle;
+use crate::{Executor, Failure, FailureKind, Git};
+
+/// repo struct - runs `git` inside a specific repository
+/// 
+/// to initialize, see `Repo::open`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repo {
+    path: PathBuf,
+    /// passed as `--git-dir`, for bare repositories and separate working trees - otherwise git finds it from `path`
+    git_dir: Option<PathBuf>,
+    /// passed as `--work-tree`, alongside `git_dir`
+    work_tree: Option<PathBuf>,
+    envs: Vec<(OsString, OsString)>,
+    executor: Option<ExecutorHandle>,
+}
+
+impl Repo {
+    /// Opens the repository containing `path`
+    /// 
+    /// Fails if `path` is not inside a git repository
+    /// # Examples
+    /// ```rust
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".");
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Repo, Failure> {
+        let repo = Repo {
+            path: path.as_ref().to_path_buf(),
+            git_dir: None,
+            work_tree: None,
+            envs: Vec::new(),
+            executor: None,
+        };
+        repo.git(vec!["rev-parse", "--git-dir"]).run()?;
+        Ok(repo)
+    }
+
+    /// Opens the bare repository at `path`, which must be the repository's own directory, such as `project.git`
+    /// 
+    /// Every command is given `--git-dir`, so the repository is never looked for elsewhere. Methods which need a
+    /// working tree, such as `is_clean` and `add`, fail with `FailureKind::BareRepository` without running git
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open_bare("/srv/git/project.git").unwrap();
+    /// println!("main is at {}", repo.rev_parse("main").unwrap());
+    /// ```
+    pub fn open_bare<P: AsRef<Path>>(path: P) -> Result<Repo, Failure> {
+        let repo = Repo {
+            path: path.as_ref().to_path_buf(),
+            git_dir: Some(absolute(path.as_ref())),
+            work_tree: None,
+            envs: Vec::new(),
+            executor: None,
+        };
+        repo.git(vec!["rev-parse", "--git-dir"]).run()?;
+        Ok(repo)
+    }
+
+    /// Opens a repository whose git directory lives apart from its working tree, as with dotfiles kept in
+    /// `--git-dir=$HOME/.cfg --work-tree=$HOME`
+    /// 
+    /// Every command is given `--git-dir` and `--work-tree`, and runs in `work_tree`, which `path` returns
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::with_separate_worktree("/home/me/.cfg", "/home/me").unwrap();
+    /// repo.add([".bashrc"], Default::default()).unwrap();
+    /// ```
+    pub fn with_separate_worktree<P, Q>(git_dir: P, work_tree: Q) -> Result<Repo, Failure>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let repo = Repo {
+            path: work_tree.as_ref().to_path_buf(),
+            git_dir: Some(absolute(git_dir.as_ref())),
+            work_tree: Some(absolute(work_tree.as_ref())),
+            envs: Vec::new(),
+            executor: None,
+        };
+        repo.git(vec!["rev-parse", "--git-dir"]).run()?;
+        Ok(repo)
+    }
+
+    /// Opens the repository containing `path`, running every command through `executor`
+    /// 
+    /// The check that `path` is a repository goes through `executor` as well
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::sync::Arc;
+    /// use rsgit::{Repo, SystemExecutor};
+    /// let repo = Repo::open_with_executor(".", Arc::new(SystemExecutor)).unwrap();
+    /// ```
+    pub fn open_with_executor<P: AsRef<Path>>(
+        path: P,
+        executor: Arc<dyn Executor>,
+    ) -> Result<Repo, Failure> {
+        let repo = Repo {
+            path: path.as_ref().to_path_buf(),
+            git_dir: None,
+            work_tree: None,
+            envs: Vec::new(),
+            executor: Some(ExecutorHandle(executor)),
+        };
+        repo.git(vec!["rev-parse", "--git-dir"]).run()?;
+        Ok(repo)
+    }
+
+    /// Runs every later command through `executor` - see `Git::with_executor`
+    pub fn with_executor(mut self, executor: Arc<dyn Executor>) -> Repo {
+        self.executor = Some(ExecutorHandle(executor));
+        self
+    }
+
+    /// whether commands go through an executor other than the default `SystemExecutor`
+    pub(crate) fn has_executor(&self) -> bool {
+        self.executor.is_some()
+    }
+
+    /// Sets an environment variable for every later command - see `Git::env`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap().env("GIT_CONFIG_NOSYSTEM", "1");
+    /// ```
+    pub fn env<K, V>(mut self, key: K, value: V) -> Repo
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// the path the repository was opened at
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Creates a `Git` command which runs inside this repository
+    /// 
+    /// git's messages are forced into the C locale, as the `Repo` methods parse them
+    /// # Examples
+    /// ```rust
+    /// use rsgit::{Repo, IsFailure};
+    /// if let Ok(repo) = Repo::open(".") {
+    ///     let output = repo.git(vec!["log", "--oneline"]).run();
+    ///     println!("{}", output.stdout().unwrap_or_default());
+    /// }
+    /// ```
+    pub fn git<T>(&self, items: T) -> Git
+    where
+        T: IntoIterator,
+        T::Item: ToString,
+    {
+        let mut git = Git::new(items).current_dir(&self.path).env("LC_ALL", "C");
+        if let Some(git_dir) = &self.git_dir {
+            git = git.with_global_arg(option_arg("--git-dir=", git_dir));
+        }
+        if let Some(work_tree) = &self.work_tree {
+            git = git.with_global_arg(option_arg("--work-tree=", work_tree));
+        }
+        for (key, value) in &self.envs {
+            git = git.env(key, value);
+        }
+        match &self.executor {
+            Some(executor) => git.with_executor(executor.0.clone()),
+            None => git,
+        }
+    }
+
+    /// Resolves a revision to its full SHA, using `rev-parse --verify`
+    pub fn rev_parse(&self, rev: &str) -> Result<String, Failure> {
+        let output = self
+            .git(vec!["rev-parse", "--verify", "--end-of-options", rev])
+            .run()?;
+        Ok(output.stdout.unwrap_or_default().trim().to_string())
+    }
+}
+
+impl Repo {
+    /// fails with `FailureKind::BareRepository` if the repository was opened with `open_bare`
+    pub(crate) fn require_worktree(&self) -> Result<(), Failure> {
+        if self.git_dir.is_some() && self.work_tree.is_none() {
+            return Err(Failure::new(
+                FailureKind::BareRepository,
+                format!("{} is a bare repository, with no working tree", self.path.display()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `path` made absolute, as commands run in the working tree rather than the caller's directory
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// `option` followed directly by `path`, such as `--git-dir=/srv/project.git`
+fn option_arg(option: &str, path: &Path) -> OsString {
+    let mut arg = OsString::from(option);
+    arg.push(path);
+    arg
+}
+
+/// converts a path into a command argument
+pub(crate) fn path_arg<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref().to_string_lossy().into_owned()
+}
+
+/// a path as the bytes git reads from stdin - any path on unix, but elsewhere only one which is valid Unicode
+#[cfg(unix)]
+pub(crate) fn path_bytes(path: &Path) -> Result<Vec<u8>, Failure> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(path.as_os_str().as_bytes().to_vec())
+}
+
+/// a path as the bytes git reads from stdin - any path on unix, but elsewhere only one which is valid Unicode
+#[cfg(not(unix))]
+pub(crate) fn path_bytes(path: &Path) -> Result<Vec<u8>, Failure> {
+    path.to_str().map(|x| x.as_bytes().to_vec()).ok_or_else(|| {
+        Failure::new(
+            FailureKind::NonUnicodePath(path.to_path_buf()),
+            format!("'{}' is not valid Unicode, so can't be passed to git", path.display()),
+        )
+    })
+}
+
+/// a path from the bytes git wrote - the reverse of `path_bytes`
+#[cfg(unix)]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+/// a path from the bytes git wrote - the reverse of `path_bytes`
+#[cfg(not(unix))]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{Fixture, TempDir};
+    use crate::{init, InitOptions};
+
+    /// an identity for repositories made outside `Fixture`, which has its own
+    fn identify(repo: Repo) -> Repo {
+        [
+            ("GIT_AUTHOR_NAME", "Test User"),
+            ("GIT_AUTHOR_EMAIL", "test@example.com"),
+            ("GIT_COMMITTER_NAME", "Test User"),
+            ("GIT_COMMITTER_EMAIL", "test@example.com"),
+            ("GIT_CONFIG_NOSYSTEM", "1"),
+        ]
+        .into_iter()
+        .fold(repo, |repo, (key, value)| repo.env(key, value))
+    }
+
+    #[test]
+    fn test_open_bare_receives_push() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        let head = fixture.commit_all("initial");
+
+        let dir = TempDir::new();
+        let path = dir.path().join("remote.git");
+        let bare = InitOptions {
+            bare: true,
+            ..Default::default()
+        };
+        init(&path, bare).unwrap();
+        fixture
+            .repo
+            .git(vec!["push", "-q"])
+            .arg_os(&path)
+            .arg("master")
+            .run()
+            .unwrap();
+
+        let remote = Repo::open_bare(&path).unwrap();
+        assert!(remote.is_bare().unwrap());
+        assert_eq!(remote.rev_parse("master").unwrap(), head);
+
+        // refused before git runs
+        let failure = remote.is_clean().unwrap_err();
+        assert_eq!(failure.kind, FailureKind::BareRepository);
+        let failure = remote.add(["a.txt"], Default::default()).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::BareRepository);
+    }
+
+    #[test]
+    fn test_separate_worktree() {
+        let dir = TempDir::new();
+        let git_dir = dir.path().join(".cfg");
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(&home).unwrap();
+        let bare = InitOptions {
+            bare: true,
+            ..Default::default()
+        };
+        init(&git_dir, bare).unwrap();
+
+        let repo = identify(Repo::with_separate_worktree(&git_dir, &home).unwrap());
+        assert_eq!(repo.path(), home);
+        assert!(!repo.is_bare().unwrap());
+        std::fs::write(home.join(".bashrc"), "export EDITOR=vi\n").unwrap();
+        assert!(repo.has_untracked_files().unwrap());
+        repo.add([".bashrc"], Default::default()).unwrap();
+        repo.git(vec!["commit", "-q", "--no-gpg-sign", "-m", "add bashrc"]).run().unwrap();
+
+        assert!(repo.is_clean().unwrap());
+        assert!(!home.join(".git").exists());
+        let tree = Repo::open_bare(&git_dir).unwrap().git(vec!["ls-tree", "--name-only", "HEAD"]).run();
+        assert_eq!(tree.unwrap().stdout.unwrap(), ".bashrc\n");
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Failure, Repo};
+
+/// the outcome of `Repo::rerere_replay`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RerereResult {
+    /// these conflicted paths were resolved in the working tree from earlier resolutions - they are not staged
+    Resolved(Vec<PathBuf>),
+    /// no conflicted path has a recorded resolution
+    NothingToReplay,
+}
+
+impl Repo {
+    /// Checks whether rerere is on, following git's rules - `rerere.enabled` if it is set, otherwise whether the
+    /// `rr-cache` directory exists
+    pub fn rerere_enabled(&self) -> Result<bool, Failure> {
+        // `config` exits with 1 when the key is unset
+        match self.git(vec!["config", "--bool", "rerere.enabled"]).run() {
+            Ok(output) => Ok(output.stdout.unwrap_or_default().trim() == "true"),
+            Err(failure) if failure.code == 1 => {
+                let output = self.git(vec!["rev-parse", "--git-path", "rr-cache"]).run()?;
+                let rr_cache = PathBuf::from(output.stdout.unwrap_or_default().trim());
+                Ok(self.path().join(rr_cache).is_dir())
+            }
+            Err(failure) => Err(failure),
+        }
+    }
+
+    /// Turns rerere on for this repository, by setting `rerere.enabled`
+    /// 
+    /// Once on, `merge`, `rebase` and friends record conflicts and replay resolutions themselves
+    pub fn rerere_enable(&self) -> Result<(), Failure> {
+        self.git(vec!["config", "rerere.enabled", "true"]).run()?;
+        Ok(())
+    }
+
+    /// Records the current conflicts, or the resolutions of conflicts already recorded, using `rerere`
+    /// 
+    /// Call this once conflicted files have been fixed, before committing - `commit` records them too
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// // ... fix up the conflicted files ...
+    /// repo.rerere_record().unwrap();
+    /// ```
+    pub fn rerere_record(&self) -> Result<(), Failure> {
+        self.git(vec!["rerere"]).run()?;
+        Ok(())
+    }
+
+    /// Applies recorded resolutions to the current conflicts, returning the paths which were resolved
+    /// 
+    /// Resolved files are left unstaged, unless `rerere.autoUpdate` is set
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{RerereResult, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// if let RerereResult::Resolved(paths) = repo.rerere_replay().unwrap() {
+    ///     for path in paths {
+    ///         println!("reused a resolution for {}", path.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn rerere_replay(&self) -> Result<RerereResult, Failure> {
+        self.git(vec!["rerere"]).run()?;
+
+        // `merge` may have replayed already, so compare what's unmerged with what rerere couldn't resolve
+        let output = self.git(vec!["rerere", "remaining"]).run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        let remaining: Vec<&Path> = stdout.lines().map(Path::new).collect();
+
+        let resolved: Vec<PathBuf> = self
+            .conflicts()?
+            .into_iter()
+            .map(|x| x.path)
+            .filter(|path| !remaining.contains(&path.as_path()))
+            .collect();
+        if resolved.is_empty() {
+            Ok(RerereResult::NothingToReplay)
+        } else {
+            Ok(RerereResult::Resolved(resolved))
+        }
+    }
+
+    /// Forgets the recorded resolutions of the current conflicts, using `rerere forget`
+    /// 
+    /// For when a recorded resolution was wrong - the conflicts are recorded afresh, so the next fix is recorded
+    /// in its place
+    pub fn rerere_clear(&self) -> Result<(), Failure> {
+        self.git(vec!["rerere", "forget", "--", "."]).run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// conflicts on `a.txt` by merging `side` into `master`
+    fn conflict(fixture: &Fixture) {
+        assert!(fixture.repo.git(vec!["merge", "-q", "side"]).run().is_err());
+        assert_eq!(fixture.repo.conflicts().unwrap().len(), 1);
+    }
+
+    fn read(fixture: &Fixture) -> String {
+        std::fs::read_to_string(fixture.repo.path().join("a.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_rerere_lifecycle() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "base\n");
+        fixture.commit_all("base");
+        fixture.repo.git(vec!["checkout", "-q", "-b", "side"]).run().unwrap();
+        fixture.write("a.txt", "side\n");
+        fixture.commit_all("side");
+        fixture.repo.git(vec!["checkout", "-q", "master"]).run().unwrap();
+        fixture.write("a.txt", "master\n");
+        fixture.commit_all("master");
+
+        assert!(!fixture.repo.rerere_enabled().unwrap());
+        fixture.repo.rerere_enable().unwrap();
+        assert!(fixture.repo.rerere_enabled().unwrap());
+
+        conflict(&fixture);
+        assert_eq!(fixture.repo.rerere_replay().unwrap(), RerereResult::NothingToReplay);
+        fixture.write("a.txt", "resolved\n");
+        fixture.repo.rerere_record().unwrap();
+        fixture.repo.git(vec!["reset", "-q", "--hard"]).run().unwrap();
+
+        conflict(&fixture);
+        assert_eq!(read(&fixture), "resolved\n");
+        assert_eq!(
+            fixture.repo.rerere_replay().unwrap(),
+            RerereResult::Resolved(vec![PathBuf::from("a.txt")])
+        );
+
+        // a wrong resolution is forgotten, and the conflict comes back
+        fixture.repo.rerere_clear().unwrap();
+        fixture.repo.git(vec!["reset", "-q", "--hard"]).run().unwrap();
+        conflict(&fixture);
+        assert!(read(&fixture).contains("<<<<<<<"));
+        assert_eq!(fixture.repo.rerere_replay().unwrap(), RerereResult::NothingToReplay);
+    }
+}
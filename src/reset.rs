@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use crate::{Failure, FailureKind, Repo};
+
+/// how much `Repo::reset` resets, besides moving the branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResetMode {
+    /// leave the index and working tree alone (`--soft`)
+    Soft,
+    /// reset the index, keeping changes in the working tree - git's default (`--mixed`)
+    Mixed,
+    /// reset the index and working tree, discarding every change (`--hard`)
+    Hard,
+    /// like `Hard`, but fail rather than discard changes to files which differ between `HEAD` and the target
+    /// (`--keep`)
+    Keep,
+    /// like `Keep`, but for backing out of a merge, keeping changes which were not staged (`--merge`)
+    Merge,
+}
+
+impl ResetMode {
+    /// the flag for this mode, eg `--hard`
+    pub fn as_flag(&self) -> &'static str {
+        match self {
+            ResetMode::Soft => "--soft",
+            ResetMode::Mixed => "--mixed",
+            ResetMode::Hard => "--hard",
+            ResetMode::Keep => "--keep",
+            ResetMode::Merge => "--merge",
+        }
+    }
+}
+
+impl Repo {
+    /// Moves the current branch to `target`, resetting the index and working tree as `mode` says
+    /// 
+    /// A `target` starting with `-` fails with `FailureKind::InvalidRefName`, as `reset` would read it as an option
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, ResetMode};
+    /// let repo = Repo::open(".").unwrap();
+    /// // undo the last commit, keeping its changes staged
+    /// repo.reset("HEAD~1", ResetMode::Soft).unwrap();
+    /// ```
+    pub fn reset(&self, target: &str, mode: ResetMode) -> Result<(), Failure> {
+        // `reset` doesn't take `--end-of-options`
+        if target.starts_with('-') {
+            return Err(Failure::new(
+                FailureKind::InvalidRefName(target.to_string()),
+                format!("{:?} is not a revision", target),
+            ));
+        }
+        self.git(vec!["reset", "--quiet", mode.as_flag(), target, "--"]).run()?;
+        Ok(())
+    }
+
+    /// Unstages `paths`, resetting their index entries to `HEAD` and leaving the working tree alone
+    pub fn reset_paths<I>(&self, paths: I) -> Result<(), Failure>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        self.git(vec!["reset", "--quiet", "--"])
+            .args_os(paths.into_iter().map(|x| x.as_ref().to_path_buf()))
+            .run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn read(fixture: &Fixture, path: &str) -> Option<String> {
+        std::fs::read_to_string(fixture.repo.path().join(path)).ok()
+    }
+
+    #[test]
+    fn test_reset_hard() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "first\n");
+        let first = fixture.commit_all("first");
+        fixture.write("a.txt", "second\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("second");
+        fixture.write("a.txt", "uncommitted\n");
+
+        fixture.repo.reset(&first, ResetMode::Hard).unwrap();
+        assert_eq!(fixture.repo.rev_parse("HEAD").unwrap(), first);
+        assert_eq!(read(&fixture, "a.txt").unwrap(), "first\n");
+        assert_eq!(read(&fixture, "b.txt"), None);
+        assert!(fixture.repo.is_clean().unwrap());
+    }
+
+    #[test]
+    fn test_reset_soft_and_paths() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "first\n");
+        let first = fixture.commit_all("first");
+        fixture.write("a.txt", "second\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("second");
+
+        fixture.repo.reset("HEAD~1", ResetMode::Soft).unwrap();
+        assert_eq!(fixture.repo.rev_parse("HEAD").unwrap(), first);
+        assert!(fixture.repo.has_staged_changes().unwrap());
+
+        fixture.repo.reset_paths(["a.txt"]).unwrap();
+        let staged = fixture.repo.git(vec!["diff", "--cached", "--name-only"]).run().unwrap();
+        assert_eq!(staged.stdout.unwrap(), "b.txt\n");
+        assert_eq!(read(&fixture, "a.txt").unwrap(), "second\n");
+
+        assert!(fixture.repo.reset("not-a-rev", ResetMode::Mixed).is_err());
+        let failure = fixture.repo.reset("--hard", ResetMode::Mixed).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::InvalidRefName("--hard".to_string()));
+    }
+}
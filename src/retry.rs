@@ -0,0 +1,281 @@
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Failure, Git, Success};
+
+/// how long `Git::retry` waits between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// the same wait every time
+    Fixed(Duration),
+    /// `initial`, doubling after each attempt up to `max` - with `jitter`, each wait is a random amount up to that
+    Exponential {
+        initial: Duration,
+        max: Duration,
+        jitter: bool,
+    },
+}
+
+impl Backoff {
+    /// the wait after attempt number `attempt`, counting from 1
+    fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max, jitter } => {
+                let doubled = initial.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+                let delay = doubled.min(max);
+                if jitter {
+                    delay.mul_f64(random_fraction())
+                } else {
+                    delay
+                }
+            }
+        }
+    }
+}
+
+/// a number in `[0, 1)`, good enough to spread out retries without pulling in a random number crate
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    // scramble the low bits, which are all that differ between close calls
+    let mixed = nanos.wrapping_mul(2_654_435_761) % 1_000_000;
+    mixed as f64 / 1_000_000.0
+}
+
+/// decides whether a failure is worth retrying
+type Retryable = Arc<dyn Fn(&Failure) -> bool + Send + Sync>;
+
+/// When and how often `Git::retry` tries a command again
+/// 
+/// By default a command is tried 3 times, waiting up to 1s and then up to 2s, and only failures which
+/// `is_transient` accepts are retried
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use rsgit::{Backoff, RetryPolicy};
+/// let policy = RetryPolicy::new(5)
+///     .backoff(Backoff::Fixed(Duration::from_secs(2)))
+///     .retry_if(|failure| RetryPolicy::is_transient(failure) || failure.code == 141);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Backoff,
+    retryable: Retryable,
+}
+
+impl RetryPolicy {
+    /// Tries a command at most `max_attempts` times, counting the first - 0 is treated as 1
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::Exponential {
+                initial: Duration::from_secs(1),
+                max: Duration::from_secs(30),
+                jitter: true,
+            },
+            retryable: Arc::new(RetryPolicy::is_transient),
+        }
+    }
+
+    /// Waits according to `backoff` between attempts
+    pub fn backoff(mut self, backoff: Backoff) -> RetryPolicy {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Only retries failures for which `retryable` returns true, in place of `is_transient`
+    pub fn retry_if<F>(mut self, retryable: F) -> RetryPolicy
+    where
+        F: Fn(&Failure) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    /// Whether `failure` looks like a network problem which may go away on its own - a host which didn't resolve,
+    /// or a connection which was refused, timed out or dropped part way through
+    /// 
+    /// Authentication failures never count, even when git also reports the connection closing
+    pub fn is_transient(failure: &Failure) -> bool {
+        const PERMANENT: [&str; 6] = [
+            "Authentication failed",
+            "Permission denied",
+            "could not read Username",
+            "could not read Password",
+            "returned error: 401",
+            "returned error: 403",
+        ];
+        const TRANSIENT: [&str; 10] = [
+            "Could not resolve host",
+            "Could not resolve hostname",
+            "early EOF",
+            "The remote end hung up",
+            "Connection timed out",
+            "Operation timed out",
+            "Connection refused",
+            "Connection reset",
+            "Failed to connect",
+            "RPC failed",
+        ];
+
+        let stderr = failure.stderr.as_deref().unwrap_or_default();
+        !PERMANENT.iter().any(|x| stderr.contains(x)) && TRANSIENT.iter().any(|x| stderr.contains(x))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new(3)
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+/// the result of `Git::retry`, and how many attempts it took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryOutcome {
+    /// the result of the last attempt
+    pub result: Result<Success, Failure>,
+    /// how many times the command was run, counting the first
+    pub attempts: u32,
+}
+
+impl Git {
+    /// Runs the command with `run`, trying again after failures `policy` deems retryable
+    /// 
+    /// Gives up straight away on any other failure, or once `policy`'s attempts run out
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Git, RetryPolicy};
+    /// let outcome = Git::new(vec!["fetch", "origin"]).retry(&RetryPolicy::default());
+    /// if outcome.attempts > 1 {
+    ///     eprintln!("fetch took {} attempts", outcome.attempts);
+    /// }
+    /// let fetched = outcome.result.unwrap();
+    /// ```
+    pub fn retry(&self, policy: &RetryPolicy) -> RetryOutcome {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = self.run();
+            match &result {
+                Err(failure) if attempts < policy.max_attempts && (policy.retryable)(failure) => {
+                    thread::sleep(policy.backoff.delay(attempts));
+                }
+                _ => return RetryOutcome { result, attempts },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockExecutor;
+    use crate::{ExecContext, Executor, FailureKind};
+    use std::ffi::OsString;
+    use std::sync::Mutex;
+
+    /// fails with each of `stderrs` in turn, then succeeds
+    struct Flaky {
+        stderrs: Mutex<Vec<&'static str>>,
+        calls: Mutex<u32>,
+    }
+
+    impl Flaky {
+        fn new(stderrs: Vec<&'static str>) -> Arc<Flaky> {
+            Arc::new(Flaky {
+                stderrs: Mutex::new(stderrs),
+                calls: Mutex::new(0),
+            })
+        }
+    }
+
+    impl Executor for Flaky {
+        fn execute(&self, _: &str, _: &[OsString], _: &ExecContext) -> Result<Success, Failure> {
+            *self.calls.lock().unwrap() += 1;
+            let mut stderrs = self.stderrs.lock().unwrap();
+            if stderrs.is_empty() {
+                return Ok(Success {
+                    stdout: Some("fetched\n".to_string()),
+                    code: 0,
+                });
+            }
+            Err(Failure {
+                stderr: Some(stderrs.remove(0).to_string()),
+                stdout: Some(String::new()),
+                code: 128,
+                kind: FailureKind::Exit,
+            })
+        }
+    }
+
+    fn fast(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).backoff(Backoff::Fixed(Duration::ZERO))
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let flaky = Flaky::new(vec![
+            "fatal: unable to access 'https://example.com/': Could not resolve host: example.com",
+            "fatal: early EOF",
+        ]);
+        let outcome = Git::new(vec!["fetch"]).with_executor(flaky.clone()).retry(&fast(5));
+
+        assert_eq!(*flaky.calls.lock().unwrap(), 3);
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.result.unwrap().stdout.unwrap(), "fetched\n");
+    }
+
+    #[test]
+    fn test_retry_gives_up() {
+        let flaky = Flaky::new(vec!["fatal: The remote end hung up unexpectedly"; 5]);
+        let outcome = Git::new(vec!["fetch"]).with_executor(flaky.clone()).retry(&fast(3));
+        assert_eq!(outcome.attempts, 3);
+        assert!(outcome.result.is_err());
+
+        // an authentication failure is never retried
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_failure(["push"], 128, "fatal: Authentication failed for 'https://example.com/'\n");
+        let outcome = Git::new(vec!["push"]).with_executor(mock.clone()).retry(&fast(3));
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(mock.calls().len(), 1);
+
+        let policy = fast(3).retry_if(|failure| failure.code == 128);
+        let outcome = Git::new(vec!["push"]).with_executor(mock.clone()).retry(&policy);
+        assert_eq!(outcome.attempts, 3);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let second = Duration::from_secs(1);
+        let exponential = Backoff::Exponential {
+            initial: second,
+            max: second * 5,
+            jitter: false,
+        };
+        let delays: Vec<Duration> = (1..=5).map(|x| exponential.delay(x)).collect();
+        assert_eq!(delays, vec![second, second * 2, second * 4, second * 5, second * 5]);
+        assert_eq!(Backoff::Fixed(second).delay(10), second);
+
+        let jittered = Backoff::Exponential {
+            initial: second,
+            max: second * 5,
+            jitter: true,
+        };
+        assert!((1..=5).all(|x| jittered.delay(x) <= exponential.delay(x)));
+    }
+}
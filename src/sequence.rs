@@ -0,0 +1,131 @@
+use crate::{Failure, Git, Success};
+
+/// the result of `Git::run_sequence` or `Git::run_sequence_with_rollback`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceResult {
+    /// the result of each command which ran, in order - the last is the failure, if there was one
+    pub completed: Vec<Result<Success, Failure>>,
+    /// the index of the command which failed, or `None` if every command succeeded
+    pub failed_at: Option<usize>,
+    /// the result of each rollback command which ran, in the order they ran
+    pub rolled_back: Vec<Result<Success, Failure>>,
+}
+
+impl SequenceResult {
+    /// Whether every command succeeded
+    pub fn is_ok(&self) -> bool {
+        self.failed_at.is_none()
+    }
+}
+
+impl Git {
+    /// Runs each of `commands` in turn with `run`, stopping at the first to fail
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let result = Git::run_sequence(vec![
+    ///     Git::new(vec!["add", "--all"]),
+    ///     Git::new(vec!["commit", "-m", "release"]),
+    /// ]);
+    /// if let Some(index) = result.failed_at {
+    ///     eprintln!("step {} failed", index);
+    /// }
+    /// ```
+    pub fn run_sequence(commands: Vec<Git>) -> SequenceResult {
+        Git::run_sequence_with_rollback(commands, Vec::new())
+    }
+
+    /// Runs `commands` as `run_sequence` does, and if one fails, undoes the ones before it
+    /// 
+    /// `rollbacks[i]` undoes `commands[i]` - those for the commands which succeeded are run newest first, and
+    /// commands with no rollback, as `rollbacks` is shorter, are skipped. Rolling back is best effort: a failed
+    /// rollback does not stop the rest, and is only recorded in `SequenceResult::rolled_back`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Git;
+    /// let result = Git::run_sequence_with_rollback(
+    ///     vec![
+    ///         Git::new(vec!["tag", "v1.0"]),
+    ///         Git::new(vec!["push", "origin", "v1.0"]),
+    ///     ],
+    ///     vec![
+    ///         Git::new(vec!["tag", "--delete", "v1.0"]),
+    ///         Git::new(vec!["push", "--delete", "origin", "v1.0"]),
+    ///     ],
+    /// );
+    /// ```
+    pub fn run_sequence_with_rollback(commands: Vec<Git>, rollbacks: Vec<Git>) -> SequenceResult {
+        let mut completed = Vec::new();
+        let mut failed_at = None;
+        for (index, command) in commands.iter().enumerate() {
+            let result = command.run();
+            let failed = result.is_err();
+            completed.push(result);
+            if failed {
+                failed_at = Some(index);
+                break;
+            }
+        }
+
+        let rolled_back = match failed_at {
+            Some(index) => rollbacks.iter().take(index).rev().map(Git::run).collect(),
+            None => Vec::new(),
+        };
+        SequenceResult {
+            completed,
+            failed_at,
+            rolled_back,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockExecutor;
+    use std::ffi::OsString;
+    use std::sync::Arc;
+
+    fn command(mock: &Arc<MockExecutor>, name: &str) -> Git {
+        Git::new(vec![name]).with_executor(mock.clone())
+    }
+
+    fn called(mock: &MockExecutor) -> Vec<OsString> {
+        mock.calls().into_iter().map(|x| x.args[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_run_sequence() {
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_success(["one"], "1\n").on_success(["two"], "2\n");
+        let result = Git::run_sequence(vec![command(&mock, "one"), command(&mock, "two")]);
+        assert!(result.is_ok());
+        assert_eq!(result.completed.len(), 2);
+        assert_eq!(result.completed[1].as_ref().unwrap().stdout.as_deref(), Some("2\n"));
+        assert!(result.rolled_back.is_empty());
+    }
+
+    #[test]
+    fn test_run_sequence_rollback() {
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_success(["one"], "")
+            .on_success(["two"], "")
+            .on_failure(["three"], 1, "error: three failed")
+            .on_failure(["undo-two"], 1, "error: undo failed")
+            .on_success(["undo-one"], "");
+
+        let commands = ["one", "two", "three", "four"].map(|x| command(&mock, x)).to_vec();
+        let rollbacks = ["undo-one", "undo-two", "undo-three", "undo-four"]
+            .map(|x| command(&mock, x))
+            .to_vec();
+        let result = Git::run_sequence_with_rollback(commands, rollbacks);
+
+        assert_eq!(result.failed_at, Some(2));
+        assert_eq!(result.completed.len(), 3);
+        assert!(result.completed[2].is_err());
+        // the failed undo-two doesn't stop undo-one
+        assert_eq!(called(&mock), ["one", "two", "three", "undo-two", "undo-one"]);
+        assert!(result.rolled_back[0].is_err());
+        assert!(result.rolled_back[1].is_ok());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{Failure, Repo};
+
+/// an author's commit count from `Repo::shortlog`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthorSummary {
+    pub count: u32,
+    pub name: String,
+    pub email: String,
+}
+
+impl Repo {
+    /// Counts the commits in `range` per author, using `shortlog -sne` - `range` defaults to `HEAD`
+    /// 
+    /// Authors come in git's order, most commits first. `.mailmap` is applied, so one person with several
+    /// identities is counted once
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// for author in repo.shortlog(Some("v1.0..HEAD")).unwrap() {
+    ///     println!("{:>5} {} <{}>", author.count, author.name, author.email);
+    /// }
+    /// ```
+    pub fn shortlog(&self, range: Option<&str>) -> Result<Vec<AuthorSummary>, Failure> {
+        // with no revision and stdin not a terminal, shortlog would read a log from stdin instead
+        let output = self
+            .git(vec!["--no-pager", "shortlog", "-sne", "--end-of-options"])
+            .arg(range.unwrap_or("HEAD"))
+            .arg("--")
+            .run()?;
+        output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_summary)
+            .collect()
+    }
+}
+
+/// parses `<count>\t<name> <<email>>`
+fn parse_summary(line: &str) -> Result<AuthorSummary, Failure> {
+    let error = || Failure::parse(format!("unexpected shortlog line: {:?}", line));
+    let (count, author) = line.trim_start().split_once('\t').ok_or_else(error)?;
+    let (name, email) = author
+        .strip_suffix('>')
+        .and_then(|x| x.rsplit_once(" <"))
+        .ok_or_else(error)?;
+    Ok(AuthorSummary {
+        count: count.parse().map_err(|_| error())?,
+        name: name.to_string(),
+        email: email.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::CommitOptions;
+
+    #[test]
+    fn test_shortlog() {
+        let fixture = Fixture::new();
+        for (author, message) in [
+            ("Alice <alice@example.com>", "one"),
+            ("Bob <bob@example.com>", "two"),
+            ("Alice <alice@example.com>", "three"),
+            ("Alice <alice@example.com>", "four"),
+        ] {
+            let opts = CommitOptions {
+                allow_empty: true,
+                author: Some(author.to_string()),
+                ..Default::default()
+            };
+            fixture.repo.commit(message, opts).unwrap();
+        }
+
+        let summary = |count: u32, name: &str, email: &str| AuthorSummary {
+            count,
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        assert_eq!(
+            fixture.repo.shortlog(None).unwrap(),
+            vec![
+                summary(3, "Alice", "alice@example.com"),
+                summary(1, "Bob", "bob@example.com"),
+            ]
+        );
+        assert_eq!(
+            fixture.repo.shortlog(Some("HEAD~2..HEAD")).unwrap(),
+            vec![summary(2, "Alice", "alice@example.com")]
+        );
+    }
+
+    #[test]
+    fn test_parse_summary() {
+        assert_eq!(
+            parse_summary("    12\tA. N. Other <other@example.com>").unwrap(),
+            AuthorSummary {
+                count: 12,
+                name: "A. N. Other".to_string(),
+                email: "other@example.com".to_string(),
+            }
+        );
+        assert!(parse_summary("12 nobody").is_err());
+    }
+}
@@ -0,0 +1,436 @@
+use crate::{Failure, Repo};
+
+/// how a signature checked out, mirroring git's `%G?` codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureValidity {
+    /// `G` - a good signature from a trusted key
+    Good,
+
+    /// `B` - the signature does not match the object
+    Bad,
+
+    /// `U` - a good signature from a key of unknown trust
+    UnknownValidity,
+
+    /// `X`/`Y` - a good signature, but the signature or key has expired
+    Expired,
+
+    /// `R` - a good signature made by a revoked key
+    Revoked,
+
+    /// `E` - the signature can't be checked, usually as the key is not in the keyring
+    UnknownKey,
+
+    /// `N` - the object is not signed
+    Unsigned,
+}
+
+impl SignatureValidity {
+    fn from_code(code: &str) -> Option<SignatureValidity> {
+        match code {
+            "G" => Some(SignatureValidity::Good),
+            "B" => Some(SignatureValidity::Bad),
+            "U" => Some(SignatureValidity::UnknownValidity),
+            "X" | "Y" => Some(SignatureValidity::Expired),
+            "R" => Some(SignatureValidity::Revoked),
+            "E" => Some(SignatureValidity::UnknownKey),
+            "N" => Some(SignatureValidity::Unsigned),
+            _ => None,
+        }
+    }
+}
+
+/// how far gpg trusts the signing key, from its `TRUST_*` status lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TrustLevel {
+    Undefined,
+    Never,
+    Marginal,
+    Fully,
+    Ultimate,
+}
+
+impl TrustLevel {
+    fn from_status(keyword: &str) -> Option<TrustLevel> {
+        match keyword {
+            "TRUST_UNDEFINED" => Some(TrustLevel::Undefined),
+            "TRUST_NEVER" => Some(TrustLevel::Never),
+            "TRUST_MARGINAL" => Some(TrustLevel::Marginal),
+            "TRUST_FULLY" => Some(TrustLevel::Fully),
+            "TRUST_ULTIMATE" => Some(TrustLevel::Ultimate),
+            _ => None,
+        }
+    }
+}
+
+/// the result of `Repo::verify_commit` or `Repo::verify_tag`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub status: SignatureValidity,
+
+    /// the key that made the signature, if known
+    pub key_id: Option<String>,
+
+    /// the signer, eg `Name <email>`, if the key is known
+    pub signer: Option<String>,
+
+    /// the trust gpg has in the key - `None` when unsigned, the key is unknown, or the signature is not gpg
+    pub trust: Option<TrustLevel>,
+}
+
+impl SignatureInfo {
+    fn unsigned() -> SignatureInfo {
+        SignatureInfo {
+            status: SignatureValidity::Unsigned,
+            key_id: None,
+            signer: None,
+            trust: None,
+        }
+    }
+
+    /// whether the signature matches the object, whatever the trust in the key
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self.status,
+            SignatureValidity::Good | SignatureValidity::UnknownValidity
+        )
+    }
+}
+
+impl Repo {
+    /// Checks the signature of the commit `rev`
+    /// 
+    /// An unsigned commit is not an error, and reports `SignatureValidity::Unsigned`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, SignatureValidity};
+    /// let repo = Repo::open(".").unwrap();
+    /// let info = repo.verify_commit("HEAD").unwrap();
+    /// if info.status == SignatureValidity::Good {
+    ///     println!("signed by {}", info.signer.unwrap_or_default());
+    /// }
+    /// ```
+    pub fn verify_commit(&self, rev: &str) -> Result<SignatureInfo, Failure> {
+        let output = self
+            .git(vec!["log", "-1", "--format=%G?%x1f%GK%x1f%GS", "--end-of-options", rev])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+
+        let mut fields = stdout.trim_end_matches('\n').split('\x1f');
+        let status = fields
+            .next()
+            .and_then(SignatureValidity::from_code)
+            .ok_or_else(|| Failure::parse(format!("unexpected signature status: {:?}", stdout)))?;
+        let non_empty = |field: Option<&str>| field.filter(|x| !x.is_empty()).map(|x| x.to_string());
+
+        let mut info = SignatureInfo {
+            status,
+            key_id: non_empty(fields.next()),
+            signer: non_empty(fields.next()),
+            trust: None,
+        };
+
+        // `%G?` has no trust level, but the raw gpg status lines do
+        if status != SignatureValidity::Unsigned {
+            let output = self
+                .git(vec!["verify-commit", "--raw", "--end-of-options", rev])
+                .output()?;
+            info.trust = parse_gpg_status(&String::from_utf8_lossy(&output.stderr))
+                .and_then(|raw| raw.trust);
+        }
+        Ok(info)
+    }
+
+    /// Checks the signature of the annotated tag `name`
+    /// 
+    /// An unsigned tag is not an error, and reports `SignatureValidity::Unsigned`. An SSH signature has no trust
+    /// level, and is only `Good` when `gpg.ssh.allowedSignersFile` names its key
+    pub fn verify_tag(&self, name: &str) -> Result<SignatureInfo, Failure> {
+        // the status lines are on stderr whatever the exit code, which is non-zero for anything but a good signature
+        let output = self
+            .git(vec!["verify-tag", "--raw", "--end-of-options", name])
+            .output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        match parse_gpg_status(&stderr).or_else(|| parse_ssh_status(&stderr)) {
+            Some(info) => Ok(info),
+            None if output.status.success() => Err(Failure::parse(format!(
+                "unexpected verify-tag output: {:?}",
+                stderr
+            ))),
+            None => Err(Failure::from_output(output)),
+        }
+    }
+}
+
+/// reads ssh-keygen's `Good "git" signature for <signer> with <alg> key <fingerprint>`, which has no `for` when
+/// the key matches no one in `gpg.ssh.allowedSignersFile`, or its `incorrect signature`
+fn parse_ssh_status(stderr: &str) -> Option<SignatureInfo> {
+    if stderr.contains("incorrect signature") {
+        return Some(SignatureInfo {
+            status: SignatureValidity::Bad,
+            ..SignatureInfo::unsigned()
+        });
+    }
+
+    let rest = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Good \"git\" signature"))?;
+    let (rest, key_id) = rest.rsplit_once(" key ")?;
+    let (signer, _algorithm) = rest.rsplit_once(" with ")?;
+    let signer = signer.strip_prefix(" for ").map(|x| x.to_string());
+    Some(SignatureInfo {
+        // as `%G?`, a key with no principal is `U`
+        status: if signer.is_some() {
+            SignatureValidity::Good
+        } else {
+            SignatureValidity::UnknownValidity
+        },
+        key_id: Some(key_id.trim().to_string()),
+        signer,
+        trust: None,
+    })
+}
+
+/// reads gpg's `[GNUPG:]` status lines, or git's `no signature found`
+fn parse_gpg_status(stderr: &str) -> Option<SignatureInfo> {
+    if stderr.contains("no signature found") {
+        return Some(SignatureInfo::unsigned());
+    }
+
+    let mut info: Option<SignatureInfo> = None;
+    let mut trust = None;
+    for line in stderr.lines() {
+        let mut words = line.splitn(4, ' ');
+        if words.next() != Some("[GNUPG:]") {
+            continue;
+        }
+        let keyword = words.next().unwrap_or_default();
+        let key_id = words.next().map(|x| x.to_string());
+        let signer = words.next().map(|x| x.to_string());
+
+        let status = match keyword {
+            "GOODSIG" => SignatureValidity::Good,
+            "BADSIG" => SignatureValidity::Bad,
+            "EXPSIG" | "EXPKEYSIG" => SignatureValidity::Expired,
+            "REVKEYSIG" => SignatureValidity::Revoked,
+            "ERRSIG" => SignatureValidity::UnknownKey,
+            _ => {
+                trust = TrustLevel::from_status(keyword).or(trust);
+                continue;
+            }
+        };
+        let signer = if status == SignatureValidity::UnknownKey {
+            None
+        } else {
+            signer
+        };
+        info = Some(SignatureInfo {
+            status,
+            key_id,
+            signer,
+            trust: None,
+        });
+    }
+
+    info.map(|mut info| {
+        if info.status != SignatureValidity::UnknownKey {
+            info.trust = trust;
+        }
+        if matches!(trust, Some(TrustLevel::Undefined | TrustLevel::Never))
+            && info.status == SignatureValidity::Good
+        {
+            info.status = SignatureValidity::UnknownValidity;
+        }
+        info
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{Fixture, TempDir};
+    use crate::CommitOptions;
+
+    /// a commit signed by a throwaway key which no keyring has
+    const SIGNED_COMMIT: &str = "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904
+author Signer <signer@example.com> 1700000000 +0000
+committer Signer <signer@example.com> 1792088687 +0000
+gpgsig -----BEGIN PGP SIGNATURE-----
+ 
+ iIkEABYIADEWIQQfODC/eyR+d6bQpFtoPlmhgToXYQUCatEabxMcc2lnbmVyQGV4
+ YW1wbGUuY29tAAoJEGg+WaGBOhdh/KQA/2uQsSQ/zqMllb1BsC1a1tSwXr+RdE4Q
+ yNXHvZ+gspfbAQCi3YxIm69g7VlnAqRODmXjX9nNLIK9IZb3QtFykfZqCg==
+ =Jj3j
+ -----END PGP SIGNATURE-----
+
+signed
+";
+
+    #[test]
+    fn test_verify_unsigned() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        fixture
+            .repo
+            .git(vec!["tag", "-a", "-m", "release", "v1"])
+            .run()
+            .unwrap();
+
+        let commit = fixture.repo.verify_commit("HEAD").unwrap();
+        assert_eq!(commit, SignatureInfo::unsigned());
+
+        let tag = fixture.repo.verify_tag("v1").unwrap();
+        assert_eq!(tag, SignatureInfo::unsigned());
+    }
+
+    #[test]
+    fn test_verify_unknown_key() {
+        let fixture = Fixture::new();
+        let written = fixture
+            .repo
+            .git(vec!["hash-object", "-t", "commit", "-w", "--stdin"])
+            .stdin(SIGNED_COMMIT)
+            .run()
+            .unwrap();
+        let sha = written.stdout.unwrap().trim().to_string();
+
+        let info = fixture.repo.verify_commit(&sha).unwrap();
+        assert_eq!(info.status, SignatureValidity::UnknownKey);
+        assert_eq!(info.key_id.as_deref(), Some("683E59A1813A1761"));
+        assert_eq!(info.trust, None);
+    }
+
+    #[test]
+    fn test_parse_gpg_status() {
+        let good = "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 683E59A1813A1761 Signer <signer@example.com>\n[GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+        let info = parse_gpg_status(good).unwrap();
+        assert_eq!(info.status, SignatureValidity::Good);
+        assert_eq!(info.signer.as_deref(), Some("Signer <signer@example.com>"));
+        assert_eq!(info.trust, Some(TrustLevel::Ultimate));
+        assert!(info.is_valid());
+
+        let untrusted = "[GNUPG:] GOODSIG 683E59A1813A1761 Signer <signer@example.com>\n[GNUPG:] TRUST_UNDEFINED 0 pgp\n";
+        assert_eq!(
+            parse_gpg_status(untrusted).unwrap().status,
+            SignatureValidity::UnknownValidity
+        );
+
+        let missing = "[GNUPG:] ERRSIG 683E59A1813A1761 22 8 00 1792088687 9 1F38\n[GNUPG:] NO_PUBKEY 683E59A1813A1761\n";
+        let info = parse_gpg_status(missing).unwrap();
+        assert_eq!(info.status, SignatureValidity::UnknownKey);
+        assert_eq!(info.signer, None);
+        assert_eq!(info.trust, None);
+        assert!(!info.is_valid());
+
+        assert_eq!(parse_gpg_status("error: something else"), None);
+    }
+
+    #[test]
+    fn test_parse_ssh_status() {
+        let good = "Good \"git\" signature for t@example.com with ED25519 key SHA256:Fo6Ne4ONALxM\n";
+        let info = parse_ssh_status(good).unwrap();
+        assert_eq!(info.status, SignatureValidity::Good);
+        assert_eq!(info.key_id.as_deref(), Some("SHA256:Fo6Ne4ONALxM"));
+        assert_eq!(info.signer.as_deref(), Some("t@example.com"));
+        assert_eq!(info.trust, None);
+
+        let unknown = "Good \"git\" signature with ED25519 key SHA256:Fo6Ne4ONALxM\nNo principal matched.\n";
+        let info = parse_ssh_status(unknown).unwrap();
+        assert_eq!(info.status, SignatureValidity::UnknownValidity);
+        assert_eq!(info.signer, None);
+
+        let bad = "Could not verify signature.\nSignature verification failed: incorrect signature\n";
+        assert_eq!(parse_ssh_status(bad).unwrap().status, SignatureValidity::Bad);
+
+        assert_eq!(parse_ssh_status("error: something else"), None);
+    }
+
+    #[test]
+    fn test_verify_ssh_tag() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+
+        let temp = TempDir::new();
+        let dir = temp.path();
+        let key = dir.join("key");
+        let status = std::process::Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "signer", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let public = std::fs::read_to_string(dir.join("key.pub")).unwrap();
+        let allowed = dir.join("allowed_signers");
+        std::fs::write(&allowed, format!("signer@example.com {}", public)).unwrap();
+
+        let config = |key: &str, value: &str| {
+            fixture.repo.git(vec!["config", key, value]).run().unwrap();
+        };
+        config("gpg.format", "ssh");
+        config("user.signingkey", &dir.join("key.pub").to_string_lossy());
+        fixture
+            .repo
+            .git(vec!["tag", "-s", "-m", "release", "v1"])
+            .run()
+            .unwrap();
+
+        // the key matches no one yet
+        config("gpg.ssh.allowedSignersFile", "/dev/null");
+        let info = fixture.repo.verify_tag("v1").unwrap();
+        assert_eq!(info.status, SignatureValidity::UnknownValidity);
+        assert!(info.key_id.unwrap().starts_with("SHA256:"));
+
+        config("gpg.ssh.allowedSignersFile", &allowed.to_string_lossy());
+        let info = fixture.repo.verify_tag("v1").unwrap();
+        assert_eq!(info.status, SignatureValidity::Good);
+        assert_eq!(info.signer.as_deref(), Some("signer@example.com"));
+        assert!(info.is_valid());
+    }
+
+    /// needs a usable secret key - set `RSGIT_TEST_SIGNING_KEY` to its id to run, otherwise it passes without checking
+    #[test]
+    fn test_verify_signed() {
+        let key = match std::env::var("RSGIT_TEST_SIGNING_KEY") {
+            Ok(key) => key,
+            Err(_) => return,
+        };
+        let fixture = Fixture::new();
+        fixture
+            .repo
+            .git(vec!["config", "user.signingkey", &key])
+            .run()
+            .unwrap();
+
+        let opts = CommitOptions {
+            allow_empty: true,
+            sign: true,
+            ..Default::default()
+        };
+        fixture.repo.commit("signed", opts).unwrap();
+        let info = fixture.repo.verify_commit("HEAD").unwrap();
+        assert!(info.is_valid());
+        assert!(info.key_id.is_some());
+        assert!(info.trust.is_some());
+
+        let unsigned = CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        fixture.repo.commit("unsigned", unsigned).unwrap();
+        assert_eq!(
+            fixture.repo.verify_commit("HEAD").unwrap(),
+            SignatureInfo::unsigned()
+        );
+
+        fixture
+            .repo
+            .git(vec!["tag", "-s", "-m", "release", "v1"])
+            .run()
+            .unwrap();
+        let info = fixture.repo.verify_tag("v1").unwrap();
+        assert!(info.is_valid());
+        assert!(info.trust.is_some());
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+
+use crate::{Failure, FailureKind, Repo};
+
+/// `sparse-checkout` and cone mode first shipped in git 2.25
+const SPARSE_CHECKOUT: (u32, u32, u32) = (2, 25, 0);
+
+/// `sparse-checkout add` arrived a release later
+const SPARSE_CHECKOUT_ADD: (u32, u32, u32) = (2, 26, 0);
+
+/// a single sparse checkout pattern, checked to be something git will read back as written
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SparseCheckoutPattern(String);
+
+impl SparseCheckoutPattern {
+    /// Validates `pattern`
+    /// 
+    /// Patterns are stored one per line, so empty patterns, line breaks and NULs are rejected, as is a leading `#`,
+    /// which git would read as a comment
+    pub fn new(pattern: &str) -> Result<SparseCheckoutPattern, Failure> {
+        let reason = if pattern.trim().is_empty() {
+            Some("is empty")
+        } else if pattern.contains(['\n', '\r', '\0']) {
+            Some("contains a line break or NUL")
+        } else if pattern.starts_with('#') {
+            Some("would be read as a comment")
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => Err(Failure::new(
+                FailureKind::InvalidPattern(pattern.to_string()),
+                format!("sparse checkout pattern {:?} {}", pattern, reason),
+            )),
+            None => Ok(SparseCheckoutPattern(pattern.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SparseCheckoutPattern {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SparseCheckoutPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// sparse checkout operations on a repository - see `Repo::sparse_checkout`
+/// 
+/// patterns given to `set` and `add` are validated with `SparseCheckoutPattern::new` before git is run
+#[derive(Debug, Clone, Copy)]
+pub struct SparseCheckoutManager<'a> {
+    repo: &'a Repo,
+}
+
+impl SparseCheckoutManager<'_> {
+    /// see `Repo::sparse_checkout_init`
+    pub fn init(&self, cone_mode: bool) -> Result<(), Failure> {
+        self.repo.sparse_checkout_init(cone_mode)
+    }
+
+    /// see `Repo::sparse_checkout_set`
+    pub fn set(&self, patterns: &[&str]) -> Result<(), Failure> {
+        validate(patterns)?;
+        self.repo.sparse_checkout_set(patterns)
+    }
+
+    /// see `Repo::sparse_checkout_add`
+    pub fn add(&self, patterns: &[&str]) -> Result<(), Failure> {
+        validate(patterns)?;
+        self.repo.sparse_checkout_add(patterns)
+    }
+
+    /// see `Repo::sparse_checkout_list`
+    pub fn list(&self) -> Result<Vec<String>, Failure> {
+        self.repo.sparse_checkout_list()
+    }
+
+    /// see `Repo::sparse_checkout_disable`
+    pub fn disable(&self) -> Result<(), Failure> {
+        self.repo.sparse_checkout_disable()
+    }
+}
+
+fn validate(patterns: &[&str]) -> Result<(), Failure> {
+    for pattern in patterns {
+        SparseCheckoutPattern::new(pattern)?;
+    }
+    Ok(())
+}
+
+impl Repo {
+    /// Groups the sparse checkout operations
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let sparse = repo.sparse_checkout();
+    /// sparse.init(true).unwrap();
+    /// sparse.set(&["docs"]).unwrap();
+    /// ```
+    pub fn sparse_checkout(&self) -> SparseCheckoutManager<'_> {
+        SparseCheckoutManager { repo: self }
+    }
+
+    /// Enables sparse checkout, in cone mode if `cone` is set
+    /// 
+    /// On a git without `sparse-checkout`, fails with `FailureKind::UnsupportedVersion`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.sparse_checkout_init(true).unwrap();
+    /// repo.sparse_checkout_set(&["docs", "src/core"]).unwrap();
+    /// ```
+    pub fn sparse_checkout_init(&self, cone: bool) -> Result<(), Failure> {
+        self.require_version(SPARSE_CHECKOUT)?;
+        let mut cmd = self.git(vec!["sparse-checkout", "init"]);
+        if cone {
+            cmd = cmd.arg("--cone");
+        }
+        cmd.run()?;
+        Ok(())
+    }
+
+    /// Replaces the sparse checkout patterns - in cone mode, these are directories
+    pub fn sparse_checkout_set(&self, patterns: &[&str]) -> Result<(), Failure> {
+        self.require_version(SPARSE_CHECKOUT)?;
+        self.git(vec!["sparse-checkout", "set", "--stdin"])
+            .stdin(stdin_patterns(patterns))
+            .run()?;
+        Ok(())
+    }
+
+    /// Adds to the sparse checkout patterns
+    pub fn sparse_checkout_add(&self, patterns: &[&str]) -> Result<(), Failure> {
+        self.require_version(SPARSE_CHECKOUT_ADD)?;
+        self.git(vec!["sparse-checkout", "add", "--stdin"])
+            .stdin(stdin_patterns(patterns))
+            .run()?;
+        Ok(())
+    }
+
+    /// Lists the sparse checkout patterns
+    pub fn sparse_checkout_list(&self) -> Result<Vec<String>, Failure> {
+        self.require_version(SPARSE_CHECKOUT)?;
+        let output = self.git(vec!["sparse-checkout", "list"]).run()?;
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .map(|x| x.to_string())
+            .collect())
+    }
+
+    /// Disables sparse checkout, restoring the full working tree
+    pub fn sparse_checkout_disable(&self) -> Result<(), Failure> {
+        self.require_version(SPARSE_CHECKOUT)?;
+        self.git(vec!["sparse-checkout", "disable"]).run()?;
+        Ok(())
+    }
+
+    /// Checks `core.sparseCheckout`
+    pub fn is_sparse(&self) -> Result<bool, Failure> {
+        // `config` exits with 1 when the key is unset
+        match self
+            .git(vec!["config", "--bool", "core.sparseCheckout"])
+            .run()
+        {
+            Ok(output) => Ok(output.stdout.unwrap_or_default().trim() == "true"),
+            Err(failure) if failure.code == 1 => Ok(false),
+            Err(failure) => Err(failure),
+        }
+    }
+}
+
+/// patterns go through stdin, so ones starting with `-` are not taken as flags
+fn stdin_patterns(patterns: &[&str]) -> String {
+    patterns.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::path::Path;
+
+    #[test]
+    fn test_sparse_checkout_cone() {
+        let fixture = Fixture::new();
+        fixture.write("docs/guide.md", "guide\n");
+        fixture.write("src/main.rs", "fn main() {}\n");
+        fixture.write("tools/build.sh", "echo\n");
+        fixture.commit_all("initial");
+        let root = fixture.repo.path();
+
+        assert!(!fixture.repo.is_sparse().unwrap());
+        fixture.repo.sparse_checkout_init(true).unwrap();
+        fixture.repo.sparse_checkout_set(&["docs"]).unwrap();
+
+        assert!(fixture.repo.is_sparse().unwrap());
+        assert!(root.join("docs/guide.md").exists());
+        assert!(!root.join("src").exists());
+        let in_head = fixture
+            .repo
+            .ls_tree("HEAD", Some(Path::new("src/main.rs")), false)
+            .unwrap();
+        assert_eq!(in_head.len(), 1);
+
+        fixture.repo.sparse_checkout_add(&["tools"]).unwrap();
+        assert!(root.join("tools/build.sh").exists());
+        let patterns = fixture.repo.sparse_checkout_list().unwrap();
+        assert_eq!(patterns, vec!["docs".to_string(), "tools".to_string()]);
+
+        fixture.repo.sparse_checkout_disable().unwrap();
+        assert!(root.join("src/main.rs").exists());
+        assert!(!fixture.repo.is_sparse().unwrap());
+    }
+
+    #[test]
+    fn test_sparse_checkout_manager() {
+        let fixture = Fixture::new();
+        fixture.write("app/main.rs", "fn main() {}\n");
+        fixture.write("lib/core/mod.rs", "\n");
+        fixture.write("lib/extra/mod.rs", "\n");
+        fixture.write("README", "readme\n");
+        fixture.commit_all("initial");
+        let root = fixture.repo.path();
+
+        let sparse = fixture.repo.sparse_checkout();
+        sparse.init(true).unwrap();
+        sparse.set(&["lib/core"]).unwrap();
+
+        // cone mode always keeps files at the root
+        assert!(root.join("README").exists());
+        assert!(root.join("lib/core/mod.rs").exists());
+        assert!(!root.join("lib/extra").exists());
+        assert!(!root.join("app").exists());
+        assert_eq!(sparse.list().unwrap(), vec!["lib/core".to_string()]);
+
+        let invalid = sparse.add(&["app", "#comment"]).unwrap_err();
+        assert_eq!(invalid.kind, FailureKind::InvalidPattern("#comment".to_string()));
+        assert!(!root.join("app").exists());
+
+        sparse.disable().unwrap();
+        assert!(root.join("app/main.rs").exists());
+    }
+
+    #[test]
+    fn test_sparse_checkout_pattern() {
+        assert_eq!(SparseCheckoutPattern::new("docs").unwrap().as_str(), "docs");
+        assert_eq!(SparseCheckoutPattern::new("/*.md").unwrap().to_string(), "/*.md");
+        assert!(SparseCheckoutPattern::new("").is_err());
+        assert!(SparseCheckoutPattern::new("a\nb").is_err());
+        assert!(SparseCheckoutPattern::new("# note").is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::repo::path_arg;
+use crate::{Failure, FailureKind, Git, Repo};
+
+/// options for `Repo::add`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddOptions {
+    /// stage every change in the working tree, including removals (`--all`)
+    pub all: bool,
+
+    /// only stage files git already tracks (`--update`)
+    pub update: bool,
+
+    /// allow adding ignored files (`--force`)
+    pub force: bool,
+
+    /// record the path without its contents (`--intent-to-add`)
+    pub intent_to_add: bool,
+}
+
+/// options for `Repo::rm`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RmOptions {
+    /// only remove from the index, keeping the working tree file (`--cached`)
+    pub cached: bool,
+
+    /// allow removing directories (`-r`)
+    pub recursive: bool,
+}
+
+impl Repo {
+    /// Stages `paths`, relative to the directory the repository was opened at
+    /// 
+    /// Paths are passed after `--`, so a file named like a flag is still treated as a file
+    /// 
+    /// A path outside the working tree fails with `FailureKind::PathOutsideRepository`, and a bare repository with
+    /// `FailureKind::BareRepository`, before git is run. A path which matches nothing fails with
+    /// `FailureKind::PathspecNotMatched`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{AddOptions, Repo};
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.add(["src/lib.rs"], AddOptions::default()).unwrap();
+    /// ```
+    pub fn add<I>(&self, paths: I, opts: AddOptions) -> Result<(), Failure>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        self.require_worktree()?;
+        let mut cmd = self.git(vec!["add"]);
+        if opts.all {
+            cmd = cmd.arg("--all");
+        }
+        if opts.update {
+            cmd = cmd.arg("--update");
+        }
+        if opts.force {
+            cmd = cmd.arg("--force");
+        }
+        if opts.intent_to_add {
+            cmd = cmd.arg("--intent-to-add");
+        }
+
+        run_with_paths(cmd, self.worktree_paths(paths)?)
+    }
+
+    /// Stages every change in the working tree, including new and removed files - `add --all`
+    pub fn add_all(&self) -> Result<(), Failure> {
+        self.require_worktree()?;
+        self.git(vec!["add", "--all"]).run()?;
+        Ok(())
+    }
+
+    /// Picks which hunks of `path` to stage, using `add --patch`
+    /// 
+    /// This is interactive - stdin, stdout and stderr are inherited, so it should only be used from a terminal
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// repo.add_patch("src/lib.rs").unwrap();
+    /// ```
+    pub fn add_patch<P: AsRef<Path>>(&self, path: P) -> Result<(), Failure> {
+        self.require_worktree()?;
+        let path = self.worktree_path(path.as_ref())?;
+        self.git(vec!["add", "--patch", "--"]).arg_os(path).stream()?;
+        Ok(())
+    }
+
+    /// Records that `paths` will be added, without staging their contents - `add --intent-to-add`
+    /// 
+    /// The files then show up in `diff`, and are staged by `commit -a`
+    pub fn add_intent<I>(&self, paths: I) -> Result<(), Failure>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let opts = AddOptions {
+            intent_to_add: true,
+            ..Default::default()
+        };
+        self.add(paths, opts)
+    }
+
+    /// Removes `paths` from the index, and from the working tree unless `cached` is set
+    pub fn rm<I>(&self, paths: I, opts: RmOptions) -> Result<(), Failure>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut cmd = self.git(vec!["rm", "--quiet"]);
+        if opts.cached {
+            cmd = cmd.arg("--cached");
+        }
+        if opts.recursive {
+            cmd = cmd.arg("-r");
+        }
+
+        run_with_paths(cmd, paths)
+    }
+
+    /// Moves or renames a tracked file
+    pub fn mv<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> Result<(), Failure> {
+        self.git(vec!["mv", "--"])
+            .arg_os(from.as_ref())
+            .arg_os(to.as_ref())
+            .run()?;
+        Ok(())
+    }
+}
+
+impl Repo {
+    fn worktree_paths<I>(&self, paths: I) -> Result<Vec<PathBuf>, Failure>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        paths.into_iter().map(|x| self.worktree_path(x.as_ref())).collect()
+    }
+
+    /// `path` relative to the directory the repository was opened at, which git runs from, or a
+    /// `FailureKind::PathOutsideRepository` if it leads out of the working tree
+    /// 
+    /// This is lexical, so a symlink out of the tree is left for git to refuse. The opened directory may be below the
+    /// top of the tree, so a path leading out of it is checked again against the top, found with
+    /// `rev-parse --show-prefix`
+    fn worktree_path(&self, path: &Path) -> Result<PathBuf, Failure> {
+        if let Some(relative) = self.relative_path(path, Path::new("")) {
+            return Ok(relative);
+        }
+
+        let output = self.git(vec!["rev-parse", "--show-prefix"]).run()?;
+        let prefix = PathBuf::from(output.stdout.unwrap_or_default().trim_end_matches('\n'));
+        Some(prefix)
+            .filter(|x| !x.as_os_str().is_empty())
+            .and_then(|prefix| self.relative_path(path, &prefix))
+            .ok_or_else(|| {
+                Failure::new(
+                    FailureKind::PathOutsideRepository(path_arg(path)),
+                    format!("'{}' is outside the working tree of '{}'", path.display(), self.path().display()),
+                )
+            })
+    }
+
+    /// `path` relative to the opened directory, if it stays in the tree whose top is `prefix` above that directory
+    fn relative_path(&self, path: &Path, prefix: &Path) -> Option<PathBuf> {
+        let from_top = if path.is_absolute() {
+            let path = normalize(path)?;
+            let depth = prefix.components().count();
+            let roots = [
+                std::path::absolute(self.path()).ok(),
+                self.path().canonicalize().ok(),
+            ];
+            roots
+                .into_iter()
+                .flatten()
+                .filter_map(|root| {
+                    let top = normalize(&root)?.ancestors().nth(depth)?.to_path_buf();
+                    Some(path.strip_prefix(top).ok()?.to_path_buf())
+                })
+                .next()?
+        } else {
+            normalize(&prefix.join(path))?
+        };
+
+        // git wants `.` for the opened directory itself
+        let relative = relative_to(&from_top, prefix);
+        if relative.as_os_str().is_empty() {
+            return Some(PathBuf::from("."));
+        }
+        Some(relative)
+    }
+}
+
+/// `path` and `base` are both relative to the same directory - returns `path` relative to `base` instead
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let mut path = path.components().peekable();
+    let mut base = base.components().peekable();
+    while path.peek().is_some() && path.peek() == base.peek() {
+        path.next();
+        base.next();
+    }
+    base.map(|_| Component::ParentDir).chain(path).collect()
+}
+
+/// resolves `.` and `..` without touching the filesystem - `None` if `..` climbs above the start
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if depth == 0 => return None,
+            Component::ParentDir => {
+                out.pop();
+                depth -= 1;
+            }
+            Component::Normal(x) => {
+                out.push(x);
+                depth += 1;
+            }
+            Component::RootDir | Component::Prefix(_) => out.push(component),
+        }
+    }
+    Some(out)
+}
+
+/// appends `-- paths...`, and classifies unmatched pathspecs
+fn run_with_paths<I>(cmd: Git, paths: I) -> Result<(), Failure>
+where
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
+{
+    cmd.arg("--")
+        .args_os(paths.into_iter().map(|x| x.as_ref().to_path_buf()))
+        .run()
+        .map_err(classify_pathspec)?;
+    Ok(())
+}
+
+/// turns `fatal: pathspec 'x' did not match any files` into `FailureKind::PathspecNotMatched`
+pub(crate) fn classify_pathspec(mut failure: Failure) -> Failure {
+    let stderr = failure.stderr.clone().unwrap_or_default();
+    let pathspec = stderr
+        .lines()
+        .filter(|line| line.contains("did not match any file"))
+        .find_map(|line| {
+            let start = line.find('\'')? + 1;
+            let end = line.rfind('\'')?;
+            line.get(start..end).map(|x| x.to_string())
+        });
+
+    if let Some(pathspec) = pathspec {
+        failure.kind = FailureKind::PathspecNotMatched(pathspec);
+    }
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn staged(fixture: &Fixture) -> String {
+        let output = fixture
+            .repo
+            .git(vec!["diff", "--cached", "--name-only", "--no-renames"])
+            .run()
+            .unwrap();
+        output.stdout.unwrap()
+    }
+
+    #[test]
+    fn test_add_flag_like_name() {
+        let fixture = Fixture::new();
+        fixture.write("--version", "not a flag\n");
+
+        fixture.repo.add(["--version"], AddOptions::default()).unwrap();
+        assert_eq!(staged(&fixture), "--version\n");
+    }
+
+    #[test]
+    fn test_add_missing_path() {
+        let fixture = Fixture::new();
+
+        let failure = fixture
+            .repo
+            .add(["missing.txt"], AddOptions::default())
+            .unwrap_err();
+        assert_eq!(
+            failure.kind,
+            FailureKind::PathspecNotMatched("missing.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_add_intent_to_add() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+
+        let opts = AddOptions {
+            intent_to_add: true,
+            ..Default::default()
+        };
+        fixture.repo.add(["a.txt"], opts).unwrap();
+
+        let files = fixture.repo.git(vec!["ls-files"]).run().unwrap();
+        assert_eq!(files.stdout.unwrap(), "a.txt\n");
+    }
+
+    #[test]
+    fn test_add_all_and_intent() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("initial");
+
+        std::fs::remove_file(fixture.repo.path().join("a.txt")).unwrap();
+        fixture.write("b.txt", "changed\n");
+        fixture.write("c.txt", "c\n");
+        fixture.repo.add_all().unwrap();
+        assert_eq!(staged(&fixture), "a.txt\nb.txt\nc.txt\n");
+
+        fixture.write("dir/d.txt", "d\n");
+        fixture.repo.add_intent(["./dir/../dir/d.txt"]).unwrap();
+        let files = fixture.repo.git(vec!["ls-files", "dir"]).run().unwrap();
+        assert_eq!(files.stdout.unwrap(), "dir/d.txt\n");
+        let diff = fixture.repo.git(vec!["diff", "--name-only"]).run().unwrap();
+        assert_eq!(diff.stdout.unwrap(), "dir/d.txt\n");
+    }
+
+    #[test]
+    fn test_add_outside_repository() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+
+        let absolute = fixture.repo.path().join("a.txt");
+        fixture.repo.add([&absolute], AddOptions::default()).unwrap();
+        assert_eq!(staged(&fixture), "a.txt\n");
+
+        for path in ["../a.txt", "dir/../../a.txt", "/etc/passwd"] {
+            let failure = fixture.repo.add([path], AddOptions::default()).unwrap_err();
+            assert_eq!(failure.kind, FailureKind::PathOutsideRepository(path.to_string()));
+        }
+        let failure = fixture.repo.add_patch("../a.txt").unwrap_err();
+        assert_eq!(failure.kind, FailureKind::PathOutsideRepository("../a.txt".to_string()));
+        assert!(fixture.repo.add_intent(["../a.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_add_from_subdirectory() {
+        let fixture = Fixture::new();
+        fixture.write("top.txt", "top\n");
+        fixture.write("sub/inner.txt", "inner\n");
+        fixture.write("other/o.txt", "o\n");
+        let sub = Repo::open(fixture.repo.path().join("sub")).unwrap();
+
+        // paths are relative to `sub`, but may lead anywhere in the working tree
+        sub.add(["inner.txt", "../top.txt"], AddOptions::default()).unwrap();
+        sub.add([fixture.repo.path().join("other/o.txt")], AddOptions::default()).unwrap();
+        assert_eq!(staged(&fixture), "other/o.txt\nsub/inner.txt\ntop.txt\n");
+
+        for path in ["../../a.txt", "../sub/../../a.txt"] {
+            let failure = sub.add([path], AddOptions::default()).unwrap_err();
+            assert_eq!(failure.kind, FailureKind::PathOutsideRepository(path.to_string()));
+        }
+        let beside = fixture.repo.path().parent().unwrap().join("beside.txt");
+        assert!(sub.add([&beside], AddOptions::default()).is_err());
+
+        assert_eq!(relative_to(Path::new("top.txt"), Path::new("sub")), Path::new("../top.txt"));
+        assert_eq!(relative_to(Path::new("sub/a/b.txt"), Path::new("sub")), Path::new("a/b.txt"));
+        assert_eq!(relative_to(Path::new("sub"), Path::new("sub")), Path::new(""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fixture = Fixture::new();
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        std::fs::write(fixture.repo.path().join(name), "latin-1\n").unwrap();
+
+        fixture.repo.add([Path::new(name)], AddOptions::default()).unwrap();
+        fixture.repo.commit("non-UTF-8 name", Default::default()).unwrap();
+
+        let tree = fixture
+            .repo
+            .git(vec!["ls-tree", "-z", "--name-only", "HEAD"])
+            .run_bytes()
+            .unwrap();
+        assert_eq!(tree, b"caf\xe9.txt\0");
+
+        let renamed = OsStr::from_bytes(b"\xff\xfe.txt");
+        fixture.repo.mv(name, renamed).unwrap();
+        assert!(fixture.repo.path().join(renamed).exists());
+    }
+
+    #[test]
+    fn test_rm_cached_and_mv() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("initial");
+
+        let opts = RmOptions {
+            cached: true,
+            ..Default::default()
+        };
+        fixture.repo.rm(["a.txt"], opts).unwrap();
+        assert!(fixture.repo.path().join("a.txt").exists());
+
+        fixture.repo.mv("b.txt", "c.txt").unwrap();
+        assert!(fixture.repo.path().join("c.txt").exists());
+        assert_eq!(staged(&fixture), "a.txt\nb.txt\nc.txt\n");
+    }
+}
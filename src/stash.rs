@@ -0,0 +1,111 @@
+use crate::{Failure, Git, Repo};
+
+impl Repo {
+    /// Stashes the changes in the working tree and index, using `stash push`
+    /// 
+    /// Returns `false`, without creating a stash, if there was nothing to stash
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let stashed = repo.stash_push(Some("before rebase"), true).unwrap();
+    /// // ... rebase ...
+    /// if stashed {
+    ///     repo.stash_pop(None).unwrap();
+    /// }
+    /// ```
+    pub fn stash_push(&self, message: Option<&str>, include_untracked: bool) -> Result<bool, Failure> {
+        let mut cmd = self.git(vec!["stash", "push"]);
+        if include_untracked {
+            cmd = cmd.arg("--include-untracked");
+        }
+        if let Some(message) = message {
+            cmd = cmd.arg("--message").arg(message);
+        }
+
+        let output = cmd.run()?;
+        Ok(!output
+            .stdout
+            .unwrap_or_default()
+            .contains("No local changes to save"))
+    }
+
+    /// Applies the stash at `index`, or the latest if `None`, and drops it, using `stash pop`
+    /// 
+    /// If applying conflicts, the `Failure` carries git's message and the stash is kept
+    pub fn stash_pop(&self, index: Option<usize>) -> Result<(), Failure> {
+        stash_ref(self.git(vec!["stash", "pop"]), index).run()?;
+        Ok(())
+    }
+
+    /// Applies the stash at `index`, or the latest if `None`, keeping it, using `stash apply`
+    pub fn stash_apply(&self, index: Option<usize>) -> Result<(), Failure> {
+        stash_ref(self.git(vec!["stash", "apply"]), index).run()?;
+        Ok(())
+    }
+}
+
+/// appends `stash@{index}`, if one is given
+fn stash_ref(cmd: Git, index: Option<usize>) -> Git {
+    match index {
+        Some(index) => cmd.arg(format!("stash@{{{}}}", index)),
+        None => cmd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::Fixture;
+
+    fn read(fixture: &Fixture, path: &str) -> String {
+        std::fs::read_to_string(fixture.repo.path().join(path)).unwrap()
+    }
+
+    fn stash_count(fixture: &Fixture) -> usize {
+        let output = fixture.repo.git(vec!["stash", "list"]).run().unwrap();
+        output.stdout.unwrap().lines().count()
+    }
+
+    #[test]
+    fn test_stash_push_and_pop() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+        assert!(!fixture.repo.stash_push(None, false).unwrap());
+
+        fixture.write("a.txt", "changed\n");
+        fixture.write("new.txt", "untracked\n");
+        assert!(fixture.repo.stash_push(Some("work in progress"), true).unwrap());
+        assert!(fixture.repo.is_clean().unwrap());
+        assert!(!fixture.repo.path().join("new.txt").exists());
+        let list = fixture.repo.git(vec!["stash", "list"]).run().unwrap();
+        assert!(list.stdout.unwrap().contains("work in progress"));
+
+        fixture.repo.stash_pop(None).unwrap();
+        assert_eq!(read(&fixture, "a.txt"), "changed\n");
+        assert_eq!(read(&fixture, "new.txt"), "untracked\n");
+        assert_eq!(stash_count(&fixture), 0);
+    }
+
+    #[test]
+    fn test_stash_apply_and_conflict() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+
+        fixture.write("a.txt", "first\n");
+        fixture.repo.stash_push(None, false).unwrap();
+        fixture.write("a.txt", "second\n");
+        fixture.repo.stash_push(None, false).unwrap();
+
+        fixture.repo.stash_apply(Some(1)).unwrap();
+        assert_eq!(read(&fixture, "a.txt"), "first\n");
+        assert_eq!(stash_count(&fixture), 2);
+
+        // the tree now disagrees with the other stash
+        fixture.commit_all("first");
+        let failure = fixture.repo.stash_pop(Some(0)).unwrap_err();
+        assert!(failure.stdout.unwrap().contains("CONFLICT"));
+        assert_eq!(stash_count(&fixture), 2);
+    }
+}
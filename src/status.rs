@@ -0,0 +1,103 @@
+use crate::{Failure, Git, Repo};
+
+impl Repo {
+    /// Checks there are no staged, unstaged or untracked changes
+    /// 
+    /// Like the other status checks, this fails with `FailureKind::BareRepository` for a repository opened with
+    /// `Repo::open_bare`
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// if !repo.is_clean().unwrap() {
+    ///     panic!("refusing to release from a dirty tree");
+    /// }
+    /// ```
+    pub fn is_clean(&self) -> Result<bool, Failure> {
+        Ok(!self.has_staged_changes()?
+            && !self.has_unstaged_changes()?
+            && !self.has_untracked_files()?)
+    }
+
+    /// Checks for any staged, unstaged or untracked changes - the opposite of `is_clean`
+    pub fn is_dirty(&self) -> Result<bool, Failure> {
+        Ok(!self.is_clean()?)
+    }
+
+    /// Checks whether the index differs from `HEAD`, using `diff --cached --quiet`
+    pub fn has_staged_changes(&self) -> Result<bool, Failure> {
+        self.require_worktree()?;
+        differs(self.git(vec!["diff", "--cached", "--quiet"]))
+    }
+
+    /// Checks whether the working tree differs from the index, using `diff --quiet`
+    pub fn has_unstaged_changes(&self) -> Result<bool, Failure> {
+        self.require_worktree()?;
+        differs(self.git(vec!["diff", "--quiet"]))
+    }
+
+    /// Checks for files which are neither tracked nor ignored
+    pub fn has_untracked_files(&self) -> Result<bool, Failure> {
+        self.require_worktree()?;
+        let output = self
+            .git(vec!["ls-files", "--others", "--exclude-standard", "-z"])
+            .run()?;
+        Ok(!output.stdout.unwrap_or_default().is_empty())
+    }
+}
+
+/// `diff --quiet` exits with 1 when there are differences, which is an answer rather than a failure
+fn differs(cmd: Git) -> Result<bool, Failure> {
+    match cmd.run() {
+        Ok(_) => Ok(false),
+        Err(failure) if failure.code == 1 => Ok(true),
+        Err(failure) => Err(failure),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::Fixture;
+
+    fn probes(fixture: &Fixture) -> (bool, bool, bool, bool) {
+        (
+            fixture.repo.is_clean().unwrap(),
+            fixture.repo.has_staged_changes().unwrap(),
+            fixture.repo.has_unstaged_changes().unwrap(),
+            fixture.repo.has_untracked_files().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_cleanliness_probes() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.write("b.txt", "b\n");
+        fixture.commit_all("initial");
+        assert_eq!(probes(&fixture), (true, false, false, false));
+
+        fixture.write("untracked.txt", "new\n");
+        assert_eq!(probes(&fixture), (false, false, false, true));
+        std::fs::remove_file(fixture.repo.path().join("untracked.txt")).unwrap();
+
+        fixture.write("a.txt", "changed\n");
+        assert_eq!(probes(&fixture), (false, false, true, false));
+
+        fixture.repo.git(vec!["add", "a.txt"]).run().unwrap();
+        assert_eq!(probes(&fixture), (false, true, false, false));
+
+        fixture.write("b.txt", "changed\n");
+        fixture.write("c.txt", "new\n");
+        assert_eq!(probes(&fixture), (false, true, true, true));
+    }
+
+    #[test]
+    fn test_ignored_files_are_clean() {
+        let fixture = Fixture::new();
+        fixture.write(".gitignore", "*.log\n");
+        fixture.commit_all("initial");
+
+        fixture.write("debug.log", "noise\n");
+        assert!(fixture.repo.is_clean().unwrap());
+    }
+}
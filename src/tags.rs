@@ -0,0 +1,163 @@
+use crate::{Failure, Repo};
+
+/// options for `Repo::tag_create`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagOptions {
+    /// make an annotated tag (`-a`) with this message, rather than a lightweight one
+    pub message: Option<String>,
+
+    /// replace the tag if it already exists (`--force`)
+    pub force: bool,
+
+    /// the commit to tag, or `HEAD` if `None`
+    pub target: Option<String>,
+
+    /// sign the tag with the configured key (`-s`) - a signed tag is annotated, with an empty message if `message`
+    /// is `None`
+    pub sign: bool,
+
+    /// sign the tag with this key instead, implying `sign` (`-u`)
+    pub sign_key: Option<String>,
+}
+
+impl Repo {
+    /// Lists the names of every tag, sorted as `tag --list` sorts them
+    pub fn tags(&self) -> Result<Vec<String>, Failure> {
+        let output = self.git(vec!["tag", "--list"]).run()?;
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Creates the tag `name`
+    /// 
+    /// Fails with git's message if the tag already exists, unless `force` is set
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::{Repo, TagOptions};
+    /// let repo = Repo::open(".").unwrap();
+    /// let opts = TagOptions { message: Some("Release 1.0".to_string()), ..Default::default() };
+    /// repo.tag_create("v1.0", opts).unwrap();
+    /// ```
+    pub fn tag_create(&self, name: &str, opts: TagOptions) -> Result<(), Failure> {
+        let mut cmd = self.git(vec!["tag"]);
+        if let Some(key) = &opts.sign_key {
+            cmd = cmd.args(vec!["-u", key]);
+        } else if opts.sign {
+            cmd = cmd.arg("-s");
+        }
+        let signed = opts.sign || opts.sign_key.is_some();
+        match &opts.message {
+            Some(message) => cmd = cmd.args(vec!["-a", "-m", message]),
+            // git would otherwise open an editor for the message
+            None if signed => cmd = cmd.args(vec!["-m", ""]),
+            None => {}
+        }
+        if opts.force {
+            cmd = cmd.arg("--force");
+        }
+        cmd = cmd.args(vec!["--", name]);
+        if let Some(target) = &opts.target {
+            cmd = cmd.arg(target);
+        }
+        cmd.run()?;
+        Ok(())
+    }
+
+    /// Deletes the tag `name`, failing if there is no such tag
+    pub fn tag_delete(&self, name: &str) -> Result<(), Failure> {
+        self.git(vec!["tag", "--delete", "--", name]).run()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_tag_create_and_delete() {
+        let fixture = Fixture::new();
+        fixture.write("a.txt", "a\n");
+        fixture.commit_all("initial");
+
+        let annotated = TagOptions {
+            message: Some("Release 1.0".to_string()),
+            ..Default::default()
+        };
+        fixture.repo.tag_create("v1.0", annotated.clone()).unwrap();
+        assert_eq!(fixture.repo.tags().unwrap(), vec!["v1.0"]);
+        let kind = fixture.repo.git(vec!["cat-file", "-t", "v1.0"]).run().unwrap();
+        assert_eq!(kind.stdout.unwrap().trim(), "tag");
+        let message = fixture.repo.git(vec!["tag", "-l", "-n1", "v1.0"]).run().unwrap();
+        assert!(message.stdout.unwrap().contains("Release 1.0"));
+
+        // an existing tag is only replaced with force
+        fixture.write("a.txt", "b\n");
+        fixture.commit_all("second");
+        let failure = fixture.repo.tag_create("v1.0", annotated.clone()).unwrap_err();
+        assert!(failure.stderr.unwrap().contains("already exists"));
+        let forced = TagOptions { force: true, ..annotated };
+        fixture.repo.tag_create("v1.0", forced).unwrap();
+        assert_eq!(
+            fixture.repo.rev_parse("v1.0^{commit}").unwrap(),
+            fixture.repo.rev_parse("HEAD").unwrap()
+        );
+
+        let lightweight = TagOptions {
+            target: Some("HEAD~1".to_string()),
+            ..Default::default()
+        };
+        fixture.repo.tag_create("old", lightweight).unwrap();
+        assert_eq!(fixture.repo.rev_parse("old").unwrap(), fixture.repo.rev_parse("HEAD~1").unwrap());
+        assert_eq!(fixture.repo.tags().unwrap(), vec!["old", "v1.0"]);
+
+        fixture.repo.tag_delete("v1.0").unwrap();
+        assert_eq!(fixture.repo.tags().unwrap(), vec!["old"]);
+        assert!(fixture.repo.tag_delete("v1.0").is_err());
+    }
+
+    #[test]
+    fn test_tag_sign_args() {
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+        // no such key, so git fails only once it tries to sign
+        let opts = TagOptions {
+            sign_key: Some("no-such-key@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(fixture.repo.tag_create("v1", opts).is_err());
+        assert!(fixture.repo.tags().unwrap().is_empty());
+    }
+
+    /// needs a usable secret key - set `RSGIT_TEST_SIGNING_KEY` to its id to run, otherwise it passes without checking
+    #[test]
+    fn test_tag_create_signed() {
+        let key = match std::env::var("RSGIT_TEST_SIGNING_KEY") {
+            Ok(key) => key,
+            Err(_) => return,
+        };
+        let fixture = Fixture::new();
+        fixture.commit_all("initial");
+
+        let opts = TagOptions {
+            message: Some("Release 1.0".to_string()),
+            sign_key: Some(key.clone()),
+            ..Default::default()
+        };
+        fixture.repo.tag_create("v1.0", opts).unwrap();
+        assert!(fixture.repo.verify_tag("v1.0").unwrap().is_valid());
+
+        fixture.repo.git(vec!["config", "user.signingkey", &key]).run().unwrap();
+        let opts = TagOptions {
+            sign: true,
+            ..Default::default()
+        };
+        fixture.repo.tag_create("v1.1", opts).unwrap();
+        assert!(fixture.repo.verify_tag("v1.1").unwrap().is_valid());
+    }
+}
@@ -0,0 +1,194 @@
+//! helpers for testing code built on `rsgit` - mock and replayed executors, and temporary repositories
+
+use std::ffi::{OsStr, OsString};
+use std::sync::Mutex;
+
+use crate::{ExecContext, Executor, Failure, FailureKind, Success};
+
+mod json;
+mod replay;
+#[cfg(any(test, feature = "test-util"))]
+mod temp_repo;
+
+pub use replay::{RecordedCall, RecordingExecutor, ReplayExecutor};
+#[cfg(any(test, feature = "test-util"))]
+pub use temp_repo::{TempRemote, TempRepo};
+#[cfg(test)]
+pub(crate) use temp_repo::TempDir;
+
+/// a response, and the pattern which picks it
+type MockResponse = (Vec<String>, Result<Success, Failure>);
+
+/// a single command seen by a `MockExecutor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    pub program: String,
+    pub args: Vec<OsString>,
+    pub context: ExecContext,
+}
+
+/// An `Executor` which returns canned results, and records every call it gets
+/// 
+/// A response is picked by its pattern - a list of arguments which must all appear in the call, in order, though not
+/// necessarily next to each other. The first pattern registered which matches wins. A call matching nothing fails
+/// with `FailureKind::Io`, naming the command
+/// # Examples
+/// ```rust
+/// use std::sync::Arc;
+/// use rsgit::testing::MockExecutor;
+/// use rsgit::Repo;
+/// let mock = Arc::new(MockExecutor::new());
+/// mock.on_success(["rev-parse", "--git-dir"], ".git\n");
+/// mock.on_success(["ls-files", "--others"], "new.txt\0");
+/// 
+/// let repo = Repo::open_with_executor("/not/a/real/repo", mock.clone()).unwrap();
+/// assert!(repo.has_untracked_files().unwrap());
+/// assert_eq!(mock.calls().len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockExecutor {
+    responses: Mutex<Vec<MockResponse>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+impl MockExecutor {
+    pub fn new() -> MockExecutor {
+        MockExecutor::default()
+    }
+
+    /// Returns `response` for calls matching `pattern`
+    pub fn on<T>(&self, pattern: T, response: Result<Success, Failure>) -> &MockExecutor
+    where
+        T: IntoIterator,
+        T::Item: ToString,
+    {
+        let pattern = pattern.into_iter().map(|x| x.to_string()).collect();
+        self.responses.lock().unwrap().push((pattern, response));
+        self
+    }
+
+    /// Succeeds with `stdout` for calls matching `pattern`
+    pub fn on_success<T, S>(&self, pattern: T, stdout: S) -> &MockExecutor
+    where
+        T: IntoIterator,
+        T::Item: ToString,
+        S: ToString,
+    {
+        let success = Success {
+            stdout: Some(stdout.to_string()),
+            code: 0,
+        };
+        self.on(pattern, Ok(success))
+    }
+
+    /// Fails with exit code `code` and `stderr` for calls matching `pattern`
+    pub fn on_failure<T, S>(&self, pattern: T, code: i32, stderr: S) -> &MockExecutor
+    where
+        T: IntoIterator,
+        T::Item: ToString,
+        S: ToString,
+    {
+        let failure = Failure {
+            stderr: Some(stderr.to_string()),
+            stdout: Some(String::new()),
+            code,
+            kind: FailureKind::Exit,
+        };
+        self.on(pattern, Err(failure))
+    }
+
+    /// Every call so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl Executor for MockExecutor {
+    fn execute(&self, program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Success, Failure> {
+        self.calls.lock().unwrap().push(MockCall {
+            program: program.to_string(),
+            args: args.to_vec(),
+            context: ctx.clone(),
+        });
+
+        let responses = self.responses.lock().unwrap();
+        responses
+            .iter()
+            .find(|(pattern, _)| matches(pattern, args))
+            .map(|(_, response)| response.clone())
+            .unwrap_or_else(|| {
+                Err(Failure::new(
+                    FailureKind::Io,
+                    format!("no mock response for `{} {}`", program, args.join(OsStr::new(" ")).to_string_lossy()),
+                ))
+            })
+    }
+}
+
+/// whether every argument of `pattern` appears in `args`, in order
+fn matches(pattern: &[String], args: &[OsString]) -> bool {
+    let mut args = args.iter();
+    pattern.iter().all(|wanted| args.any(|arg| arg.as_os_str() == wanted.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::Repo;
+
+    fn open(mock: &Arc<MockExecutor>) -> Repo {
+        mock.on_success(["rev-parse", "--git-dir"], ".git\n");
+        Repo::open_with_executor("/mock", mock.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_matches() {
+        let args: Vec<OsString> = ["diff", "--cached", "--quiet"].iter().map(OsString::from).collect();
+        assert!(matches(&[], &args));
+        assert!(matches(&["diff".to_string(), "--quiet".to_string()], &args));
+        assert!(!matches(&["--quiet".to_string(), "diff".to_string()], &args));
+        assert!(!matches(&["status".to_string()], &args));
+    }
+
+    #[test]
+    fn test_mock_is_clean() {
+        let mock = Arc::new(MockExecutor::new());
+        let repo = open(&mock);
+        mock.on_success(["diff", "--cached"], "");
+        mock.on_failure(["diff", "--quiet"], 1, "");
+        mock.on_success(["ls-files"], "");
+
+        assert!(!repo.is_clean().unwrap());
+        assert!(repo.has_unstaged_changes().unwrap());
+        assert!(!repo.has_staged_changes().unwrap());
+
+        let calls = mock.calls();
+        assert_eq!(calls[0].args, vec!["rev-parse", "--git-dir"]);
+        assert_eq!(calls[1].args, vec!["diff", "--cached", "--quiet"]);
+        assert_eq!(calls[1].program, "git");
+        assert_eq!(calls[1].context.dir, Some(PathBuf::from("/mock")));
+    }
+
+    #[test]
+    fn test_mock_parses_output() {
+        let mock = Arc::new(MockExecutor::new());
+        let repo = open(&mock);
+        mock.on_success(["rev-list", "--count", "main..HEAD"], "3\n");
+        mock.on_success(["rev-list", "--count"], "nonsense\n");
+
+        assert_eq!(repo.commit_count("main..HEAD").unwrap(), 3);
+        assert_eq!(repo.commit_count("HEAD").unwrap_err().kind, FailureKind::Parse);
+    }
+
+    #[test]
+    fn test_mock_unmatched() {
+        let mock = Arc::new(MockExecutor::new());
+        let failure = Repo::open_with_executor("/mock", mock.clone()).unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Io);
+        assert!(failure.stderr.unwrap().contains("rev-parse --git-dir"));
+        assert_eq!(mock.calls().len(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+//! just enough JSON for recordings, as the crate has no dependencies
+
+use std::fmt::Write;
+
+/// a parsed JSON value - numbers are whole, which is all recordings hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write(&self, out: &mut String) {
+        match self {
+            Value::Null => out.push_str("null"),
+            Value::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Value::Number(value) => out.push_str(&value.to_string()),
+            Value::String(value) => write_string(out, value),
+            Value::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            Value::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// parses a whole document, failing on anything after the value
+pub(crate) fn parse(input: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        chars: input.char_indices().peekable(),
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(value),
+        Some((at, _)) => Err(format!("trailing characters at {}", at)),
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some((_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, wanted: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some((_, c)) if c == wanted => Ok(()),
+            Some((at, c)) => Err(format!("expected {:?} at {}, found {:?}", wanted, at, c)),
+            None => Err(format!("expected {:?}, found the end", wanted)),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for wanted in word.chars() {
+            match self.chars.next() {
+                Some((_, c)) if c == wanted => {}
+                _ => return Err(format!("expected {}", word)),
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek().map(|(_, c)| *c) {
+            Some('n') => self.keyword("null", Value::Null),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => {
+                self.chars.next();
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if let Some((_, ']')) = self.chars.peek() {
+                    self.chars.next();
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some((_, ',')) => continue,
+                        Some((_, ']')) => return Ok(Value::Array(items)),
+                        _ => return Err("unterminated array".to_string()),
+                    }
+                }
+            }
+            Some('{') => {
+                self.chars.next();
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if let Some((_, '}')) = self.chars.peek() {
+                    self.chars.next();
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some((_, ',')) => continue,
+                        Some((_, '}')) => return Ok(Value::Object(fields)),
+                        _ => return Err("unterminated object".to_string()),
+                    }
+                }
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some((_, c)) = self.chars.peek() {
+                    if *c != '-' && !c.is_ascii_digit() {
+                        break;
+                    }
+                    number.push(*c);
+                    self.chars.next();
+                }
+                number
+                    .parse()
+                    .map(Value::Number)
+                    .map_err(|_| format!("unexpected number {:?}", number))
+            }
+            Some(c) => Err(format!("unexpected {:?}", c)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(out),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, '"')) => out.push('"'),
+                    Some((_, '\\')) => out.push('\\'),
+                    Some((_, '/')) => out.push('/'),
+                    Some((_, 'n')) => out.push('\n'),
+                    Some((_, 'r')) => out.push('\r'),
+                    Some((_, 't')) => out.push('\t'),
+                    Some((_, 'b')) => out.push('\u{8}'),
+                    Some((_, 'f')) => out.push('\u{c}'),
+                    Some((_, 'u')) => {
+                        let code: String = (0..4)
+                            .filter_map(|_| self.chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        let c = u32::from_str_radix(&code, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("bad escape \\u{}", code))?;
+                        out.push(c);
+                    }
+                    _ => return Err("bad escape".to_string()),
+                },
+                Some((_, c)) => out.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let value = Value::Array(vec![Value::Object(vec![
+            ("args".to_string(), Value::Array(vec![Value::String("a \"b\"\n\u{1}".to_string())])),
+            ("dir".to_string(), Value::Null),
+            ("code".to_string(), Value::Number(-1)),
+            ("ok".to_string(), Value::Bool(true)),
+        ])]);
+        let mut out = String::new();
+        value.write(&mut out);
+        assert_eq!(parse(&out).unwrap(), value);
+
+        assert_eq!(parse(" { } ").unwrap(), Value::Object(vec![]));
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("[] x").is_err());
+    }
+}
@@ -0,0 +1,410 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::json::{self, Value};
+use crate::{ExecContext, Executor, Failure, FailureKind, Success, SystemExecutor};
+
+/// a single command and its outcome, as saved by `RecordingExecutor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCall {
+    /// the arguments, converted lossily if they were not valid UTF-8
+    pub args: Vec<String>,
+    pub dir: Option<PathBuf>,
+    /// what was fed to stdin, if anything
+    pub stdin: Option<String>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub code: i32,
+}
+
+impl RecordedCall {
+    /// whether a call with `args`, `dir` and `stdin` would get this response
+    fn matches(&self, args: &[String], dir: &Option<PathBuf>, stdin: &Option<String>) -> bool {
+        self.args == args && &self.dir == dir && &self.stdin == stdin
+    }
+
+    fn result(&self) -> Result<Success, Failure> {
+        if self.code == 0 {
+            Ok(Success {
+                stdout: self.stdout.clone(),
+                code: 0,
+            })
+        } else {
+            Err(Failure {
+                stderr: self.stderr.clone(),
+                stdout: self.stdout.clone(),
+                code: self.code,
+                kind: FailureKind::Exit,
+            })
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let string = |x: &Option<String>| x.clone().map(Value::String).unwrap_or(Value::Null);
+        Value::Object(vec![
+            (
+                "args".to_string(),
+                Value::Array(self.args.iter().cloned().map(Value::String).collect()),
+            ),
+            (
+                "dir".to_string(),
+                string(&self.dir.as_ref().map(|x| x.to_string_lossy().into_owned())),
+            ),
+            ("stdin".to_string(), string(&self.stdin)),
+            ("stdout".to_string(), string(&self.stdout)),
+            ("stderr".to_string(), string(&self.stderr)),
+            ("code".to_string(), Value::Number(self.code as i64)),
+        ])
+    }
+
+    fn from_json(value: &Value) -> Result<RecordedCall, String> {
+        let string = |key: &str| match value.get(key) {
+            Some(Value::String(x)) => Ok(Some(x.clone())),
+            Some(Value::Null) | None => Ok(None),
+            Some(other) => Err(format!("expected a string for {:?}, found {:?}", key, other)),
+        };
+        let args = match value.get("args") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|x| match x {
+                    Value::String(x) => Ok(x.clone()),
+                    other => Err(format!("expected a string argument, found {:?}", other)),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err("expected an args array".to_string()),
+        };
+        let code = match value.get("code") {
+            Some(Value::Number(x)) => *x as i32,
+            _ => return Err("expected a code".to_string()),
+        };
+        Ok(RecordedCall {
+            args,
+            dir: string("dir")?.map(PathBuf::from),
+            stdin: string("stdin")?,
+            stdout: string("stdout")?,
+            stderr: string("stderr")?,
+            code,
+        })
+    }
+}
+
+/// replaces every `from` in `text` with `to`, in order
+fn substitute(text: &str, substitutions: &[(String, String)]) -> String {
+    substitutions
+        .iter()
+        .fold(text.to_string(), |text, (from, to)| text.replace(from.as_str(), to))
+}
+
+fn substitute_path(path: &Option<PathBuf>, substitutions: &[(String, String)]) -> Option<PathBuf> {
+    path.as_ref()
+        .map(|x| PathBuf::from(substitute(&x.to_string_lossy(), substitutions)))
+}
+
+/// An `Executor` which runs commands through another, keeping every call and its outcome so they can be saved for a
+/// `ReplayExecutor`
+/// 
+/// Substitutions are applied to everything recorded, so volatile text such as a temporary directory can be replaced
+/// with a placeholder
+/// # Examples
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use rsgit::testing::RecordingExecutor;
+/// use rsgit::Repo;
+/// let recorder = Arc::new(RecordingExecutor::system().with_substitution("/home/me/project", "<repo>"));
+/// let repo = Repo::open_with_executor("/home/me/project", recorder.clone()).unwrap();
+/// repo.is_clean().unwrap();
+/// recorder.save("tests/recordings/is_clean.json").unwrap();
+/// ```
+pub struct RecordingExecutor {
+    inner: Arc<dyn Executor>,
+    substitutions: Vec<(String, String)>,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl RecordingExecutor {
+    /// Records the calls run through `inner`
+    pub fn new(inner: Arc<dyn Executor>) -> RecordingExecutor {
+        RecordingExecutor {
+            inner,
+            substitutions: Vec::new(),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Records real git, through `SystemExecutor`
+    pub fn system() -> RecordingExecutor {
+        RecordingExecutor::new(Arc::new(SystemExecutor))
+    }
+
+    /// Replaces `from` with `to` in everything recorded from now on
+    pub fn with_substitution<F, T>(mut self, from: F, to: T) -> RecordingExecutor
+    where
+        F: ToString,
+        T: ToString,
+    {
+        self.substitutions.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Every call so far, oldest first
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The calls so far as JSON, which `ReplayExecutor::from_json` reads
+    pub fn to_json(&self) -> String {
+        let calls = Value::Array(self.calls().iter().map(RecordedCall::to_json).collect());
+        let mut out = String::new();
+        calls.write(&mut out);
+        out.push('\n');
+        out
+    }
+
+    /// Writes the calls so far to `path`, as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+impl Executor for RecordingExecutor {
+    fn execute(&self, program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Success, Failure> {
+        let result = self.inner.execute(program, args, ctx);
+
+        let subs = &self.substitutions;
+        let text = |x: &Option<String>| x.as_ref().map(|x| substitute(x, subs));
+        let (stdout, stderr, code) = match &result {
+            Ok(success) => (text(&success.stdout), None, success.code),
+            Err(failure) => (text(&failure.stdout), text(&failure.stderr), failure.code),
+        };
+        let stdin = ctx.stdin.as_ref().map(|x| String::from_utf8_lossy(x).into_owned());
+        self.calls.lock().unwrap().push(RecordedCall {
+            args: args.iter().map(|x| substitute(&x.to_string_lossy(), subs)).collect(),
+            dir: substitute_path(&ctx.dir, subs),
+            stdin: text(&stdin),
+            stdout,
+            stderr,
+            code,
+        });
+        result
+    }
+}
+
+/// An `Executor` which serves the calls saved by a `RecordingExecutor`, without running anything
+/// 
+/// Each call is answered by the first unused recording with the same arguments, directory and stdin. A call with no
+/// recording panics, as does dropping the executor with recordings left over - see `remaining`
+/// 
+/// Substitutions are applied to each call before it is matched, and undone on the output served, so placeholders
+/// in a recording can stand for a different temporary directory on every run
+/// # Examples
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use rsgit::testing::ReplayExecutor;
+/// use rsgit::Repo;
+/// let replay = Arc::new(
+///     ReplayExecutor::load("tests/recordings/is_clean.json")
+///         .unwrap()
+///         .with_substitution("/tmp/scratch", "<repo>"),
+/// );
+/// let repo = Repo::open_with_executor("/tmp/scratch", replay.clone()).unwrap();
+/// assert!(repo.is_clean().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ReplayExecutor {
+    substitutions: Vec<(String, String)>,
+    calls: Mutex<Vec<Option<RecordedCall>>>,
+}
+
+impl ReplayExecutor {
+    /// Serves `calls`
+    pub fn new(calls: Vec<RecordedCall>) -> ReplayExecutor {
+        ReplayExecutor {
+            substitutions: Vec::new(),
+            calls: Mutex::new(calls.into_iter().map(Some).collect()),
+        }
+    }
+
+    /// Reads the JSON written by `RecordingExecutor::to_json`
+    pub fn from_json(input: &str) -> Result<ReplayExecutor, Failure> {
+        let invalid = |err: String| Failure::parse(format!("invalid recording: {}", err));
+        let calls = match json::parse(input).map_err(invalid)? {
+            Value::Array(items) => items
+                .iter()
+                .map(RecordedCall::from_json)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(invalid)?,
+            _ => return Err(invalid("expected an array of calls".to_string())),
+        };
+        Ok(ReplayExecutor::new(calls))
+    }
+
+    /// Reads the file written by `RecordingExecutor::save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ReplayExecutor, Failure> {
+        let input = fs::read_to_string(&path).map_err(|err| {
+            Failure::new(
+                FailureKind::Io,
+                format!("failed to read {}: {}", path.as_ref().display(), err),
+            )
+        })?;
+        ReplayExecutor::from_json(&input)
+    }
+
+    /// Replaces `from` with `to` in each call before matching it, and `to` with `from` in the output served
+    pub fn with_substitution<F, T>(mut self, from: F, to: T) -> ReplayExecutor
+    where
+        F: ToString,
+        T: ToString,
+    {
+        self.substitutions.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// The recordings which have not been served yet
+    pub fn remaining(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().iter().flatten().cloned().collect()
+    }
+}
+
+impl Executor for ReplayExecutor {
+    fn execute(&self, program: &str, args: &[OsString], ctx: &ExecContext) -> Result<Success, Failure> {
+        let subs = &self.substitutions;
+        let args: Vec<String> = args.iter().map(|x| substitute(&x.to_string_lossy(), subs)).collect();
+        let dir = substitute_path(&ctx.dir, subs);
+        let stdin = ctx
+            .stdin
+            .as_ref()
+            .map(|x| substitute(&String::from_utf8_lossy(x), subs));
+
+        let recorded = {
+            let mut calls = self.calls.lock().unwrap();
+            calls
+                .iter_mut()
+                .find(|x| x.as_ref().is_some_and(|x| x.matches(&args, &dir, &stdin)))
+                .and_then(|x| x.take())
+        };
+        let recorded = match recorded {
+            Some(recorded) => recorded,
+            None => panic!(
+                "no recording left for `{} {}` in {:?}",
+                program,
+                args.join(" "),
+                dir
+            ),
+        };
+
+        let undo: Vec<(String, String)> = subs
+            .iter()
+            .rev()
+            .map(|(from, to)| (to.clone(), from.clone()))
+            .collect();
+        let text = |x: &Option<String>| x.as_ref().map(|x| substitute(x, &undo));
+        match recorded.result() {
+            Ok(success) => Ok(Success {
+                stdout: text(&success.stdout),
+                ..success
+            }),
+            Err(failure) => Err(Failure {
+                stdout: text(&failure.stdout),
+                stderr: text(&failure.stderr),
+                ..failure
+            }),
+        }
+    }
+}
+
+impl Drop for ReplayExecutor {
+    fn drop(&mut self) {
+        let remaining = self.remaining();
+        if !remaining.is_empty() && !thread::panicking() {
+            let args: Vec<String> = remaining.iter().map(|x| x.args.join(" ")).collect();
+            panic!("recordings were never replayed: {:?}", args);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempDir;
+    use crate::{Git, Repo};
+
+    /// `init`, then a few status queries, returning their answers
+    fn session(dir: &Path, executor: Arc<dyn Executor>) -> (bool, bool, String) {
+        Git::new(vec!["init", "-q"])
+            .current_dir(dir)
+            .with_executor(executor.clone())
+            .run()
+            .unwrap();
+        let repo = Repo::open_with_executor(dir, executor).unwrap();
+        let status = repo
+            .git(vec!["status", "--porcelain", "--untracked-files=all"])
+            .run()
+            .unwrap();
+        (
+            repo.is_clean().unwrap(),
+            repo.has_untracked_files().unwrap(),
+            status.stdout.unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let live = TempDir::new();
+        fs::write(live.path().join("new.txt"), "new\n").unwrap();
+        let live_path = live.path().to_string_lossy().into_owned();
+
+        let recorder = Arc::new(RecordingExecutor::system().with_substitution(&live_path, "<repo>"));
+        let recorded = session(live.path(), recorder.clone());
+        assert_eq!(recorded, (false, true, "?? new.txt\n".to_string()));
+
+        let json = recorder.to_json();
+        assert!(json.contains("\"dir\": \"<repo>\""));
+        assert!(!json.contains(&live_path));
+
+        // a directory which doesn't exist, so nothing can have really run
+        let replay = Arc::new(
+            ReplayExecutor::from_json(&json)
+                .unwrap()
+                .with_substitution("/nowhere/at/all", "<repo>"),
+        );
+        assert_eq!(session(Path::new("/nowhere/at/all"), replay.clone()), recorded);
+        assert!(replay.remaining().is_empty());
+    }
+
+    #[test]
+    fn test_replay_failure_and_leftovers() {
+        let failed = RecordedCall {
+            args: vec!["rev-parse".to_string(), "--verify".to_string(), "nope".to_string()],
+            dir: None,
+            stdin: None,
+            stdout: Some(String::new()),
+            stderr: Some("fatal: Needed a single revision\n".to_string()),
+            code: 128,
+        };
+        let replay = ReplayExecutor::new(vec![failed.clone(), failed]);
+        let args = ["rev-parse".into(), "--verify".into(), "nope".into()];
+        let result = replay.execute("git", &args, &ExecContext::default());
+        assert_eq!(result.unwrap_err().code, 128);
+        assert_eq!(replay.remaining().len(), 1);
+
+        let leftover = std::panic::catch_unwind(move || drop(replay));
+        assert!(leftover.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "no recording left for `git status`")]
+    fn test_replay_unexpected() {
+        let replay = ReplayExecutor::new(Vec::new());
+        let _ = replay.execute("git", &["status".into()], &ExecContext::default());
+    }
+
+    #[test]
+    fn test_invalid_recording() {
+        let failure = ReplayExecutor::from_json("[{\"args\": 1}]").unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Parse);
+    }
+}
@@ -0,0 +1,256 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::repo::path_arg;
+use crate::{Git, Repo};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// a throwaway directory, removed on drop
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new() -> TempDir {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let path = std::env::temp_dir().join(format!(
+            "rsgit-test-{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst),
+            nanos
+        ));
+        fs::create_dir_all(&path).expect("failed to create temp dir");
+        TempDir(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// the environment every `TempRepo` command runs with, so the developer's own config can't leak in
+fn isolation(home: &Path) -> Vec<(&'static str, OsString)> {
+    vec![
+        ("HOME", home.into()),
+        ("XDG_CONFIG_HOME", home.into()),
+        ("GIT_CONFIG_GLOBAL", home.join(".gitconfig").into()),
+        ("GIT_CONFIG_NOSYSTEM", "1".into()),
+    ]
+}
+
+fn isolate_git(git: Git, home: &Path) -> Git {
+    isolation(home)
+        .into_iter()
+        .fold(git, |git, (key, value)| git.env(key, value))
+}
+
+fn isolate_repo(repo: Repo, home: &Path) -> Repo {
+    isolation(home)
+        .into_iter()
+        .fold(repo, |repo, (key, value)| repo.env(key, value))
+}
+
+/// A repository in a temporary directory, for tests - removed on drop
+/// 
+/// The branch is `master`, whatever `init.defaultBranch` says, and an identity is configured with signing off. Every
+/// command runs with `HOME` and `GIT_CONFIG_GLOBAL` pointing into the temporary directory, and with the system
+/// config ignored
+/// 
+/// Only built with the `test-util` feature
+/// # Examples
+/// ```rust
+/// use rsgit::testing::TempRepo;
+/// let temp = TempRepo::new();
+/// temp.write_file("src/lib.rs", "pub fn answer() -> u32 { 42 }\n");
+/// let sha = temp.commit_all("initial");
+/// assert_eq!(temp.repo().rev_parse("HEAD").unwrap(), sha);
+/// ```
+pub struct TempRepo {
+    repo: Repo,
+    home: PathBuf,
+    dir: TempDir,
+}
+
+impl TempRepo {
+    /// Initializes an empty repository
+    /// 
+    /// Panics if git fails, as this is only meant for tests
+    pub fn new() -> TempRepo {
+        let dir = TempDir::new();
+        let home = dir.path().join("home");
+        let path = dir.path().join("repo");
+        fs::create_dir_all(&home).expect("failed to create temp home");
+        fs::create_dir_all(&path).expect("failed to create temp repo");
+        fs::write(home.join(".gitconfig"), "").expect("failed to create temp config");
+
+        isolate_git(Git::new(vec!["init", "-q"]), &home)
+            .current_dir(&path)
+            .run()
+            .expect("git init failed");
+        let repo = isolate_repo(Repo::open(&path).expect("failed to open temp repo"), &home);
+
+        // pin the branch name, whatever init.defaultBranch says
+        repo.git(vec!["symbolic-ref", "HEAD", "refs/heads/master"])
+            .run()
+            .expect("failed to set the branch");
+        for (key, value) in [
+            ("user.name", "Test User"),
+            ("user.email", "test@example.com"),
+            ("commit.gpgsign", "false"),
+            ("tag.gpgsign", "false"),
+        ] {
+            repo.git(vec!["config", key, value])
+                .run()
+                .expect("failed to configure temp repo");
+        }
+        TempRepo { repo, home, dir }
+    }
+
+    /// the repository, whose commands all run in the isolated environment
+    pub fn repo(&self) -> &Repo {
+        &self.repo
+    }
+
+    /// the root of the working tree
+    pub fn path(&self) -> &Path {
+        self.repo.path()
+    }
+
+    /// Writes `contents` to `path`, relative to the working tree, creating directories as needed
+    pub fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(&self, path: P, contents: C) {
+        let full = self.path().join(path);
+        if let Some(parent) = full.parent() {
+            fs::create_dir_all(parent).expect("failed to create directory");
+        }
+        fs::write(full, contents).expect("failed to write file");
+    }
+
+    /// Stages everything and commits, returning the new SHA - an empty commit is made if nothing changed
+    pub fn commit_all(&self, message: &str) -> String {
+        self.repo.git(vec!["add", "-A"]).run().expect("git add failed");
+        self.repo
+            .git(vec!["commit", "-q", "--allow-empty", "-m", message])
+            .run()
+            .expect("git commit failed");
+        self.repo.rev_parse("HEAD").expect("HEAD does not resolve")
+    }
+
+    /// Creates the branch `name` at `HEAD`, and checks it out
+    pub fn branch(&self, name: &str) {
+        self.repo
+            .git(vec!["checkout", "-q", "-b", name])
+            .run()
+            .expect("failed to create branch");
+    }
+
+    /// Turns this repository into a bare one, to push to and fetch from - the working tree is removed
+    /// # Examples
+    /// ```rust
+    /// use rsgit::testing::TempRepo;
+    /// let upstream = TempRepo::new();
+    /// upstream.commit_all("initial");
+    /// let remote = upstream.into_bare_remote();
+    /// 
+    /// let local = TempRepo::new();
+    /// local.repo().git(vec!["remote", "add", "origin", &remote.url()]).run().unwrap();
+    /// local.repo().git(vec!["fetch", "-q", "origin"]).run().unwrap();
+    /// ```
+    pub fn into_bare_remote(self) -> TempRemote {
+        let bare = self.dir.path().join("remote.git");
+        isolate_git(Git::new(vec!["clone", "-q", "--bare"]), &self.home)
+            .args_os([self.path(), &bare])
+            .run()
+            .expect("failed to clone bare remote");
+        let _ = fs::remove_dir_all(self.path());
+
+        let repo = isolate_repo(Repo::open(&bare).expect("failed to open bare remote"), &self.home);
+        TempRemote {
+            repo,
+            _dir: self.dir,
+        }
+    }
+}
+
+impl Default for TempRepo {
+    fn default() -> TempRepo {
+        TempRepo::new()
+    }
+}
+
+/// A bare repository in a temporary directory, made by `TempRepo::into_bare_remote` - removed on drop
+pub struct TempRemote {
+    repo: Repo,
+    _dir: TempDir,
+}
+
+impl TempRemote {
+    /// the bare repository
+    pub fn repo(&self) -> &Repo {
+        &self.repo
+    }
+
+    /// the path of the bare repository
+    pub fn path(&self) -> &Path {
+        self.repo.path()
+    }
+
+    /// the path as a remote URL, for `git remote add` or `git clone`
+    pub fn url(&self) -> String {
+        path_arg(self.path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_repo() {
+        let temp = TempRepo::new();
+        let path = temp.path().to_path_buf();
+
+        let home = temp.repo().git(vec!["config", "--global", "--list"]).run().unwrap();
+        assert_eq!(home.stdout.unwrap(), "");
+        let name = temp.repo().git(vec!["config", "user.name"]).run().unwrap();
+        assert_eq!(name.stdout.unwrap(), "Test User\n");
+
+        temp.write_file("nested/a.txt", "a\n");
+        let first = temp.commit_all("first");
+        temp.branch("topic");
+        let second = temp.commit_all("second");
+        assert_eq!(temp.repo().rev_parse("master").unwrap(), first);
+        assert_eq!(temp.repo().rev_parse("topic").unwrap(), second);
+
+        drop(temp);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_into_bare_remote() {
+        let upstream = TempRepo::new();
+        let sha = upstream.commit_all("initial");
+        let remote = upstream.into_bare_remote();
+        assert_eq!(remote.repo().rev_parse("master").unwrap(), sha);
+
+        let local = TempRepo::new();
+        local.repo().git(vec!["remote", "add", "origin", &remote.url()]).run().unwrap();
+        local.repo().git(vec!["fetch", "-q", "origin"]).run().unwrap();
+        assert_eq!(local.repo().rev_parse("origin/master").unwrap(), sha);
+
+        local.repo().git(vec!["checkout", "-q", "origin/master"]).run().unwrap();
+        let pushed = local.commit_all("pushed");
+        local.repo().git(vec!["push", "-q", "origin", "HEAD:master"]).run().unwrap();
+        assert_eq!(remote.repo().rev_parse("master").unwrap(), pushed);
+    }
+}
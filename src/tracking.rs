@@ -0,0 +1,169 @@
+use crate::{Failure, Repo};
+
+/// how a branch relates to its upstream - see `Repo::tracking_info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackingInfo {
+    /// the upstream branch, eg `origin/main`
+    pub upstream: String,
+
+    /// the remote the upstream belongs to, `None` for a local upstream
+    pub remote: Option<String>,
+
+    /// commits on the branch which are not on the upstream
+    pub ahead: u32,
+
+    /// commits on the upstream which are not on the branch
+    pub behind: u32,
+
+    /// the upstream is configured, but no longer exists - usually deleted on the remote and pruned
+    pub gone: bool,
+}
+
+impl Repo {
+    /// Describes the upstream of `branch`, or `None` if it has no upstream configured
+    /// 
+    /// Built on `for-each-ref`, so unlike `branch -vv` it doesn't depend on git's translations
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// if let Some(info) = repo.tracking_info("main").unwrap() {
+    ///     println!("{} ahead, {} behind {}", info.ahead, info.behind, info.upstream);
+    /// }
+    /// ```
+    pub fn tracking_info(&self, branch: &str) -> Result<Option<TrackingInfo>, Failure> {
+        let refname = format!("refs/heads/{}", branch);
+        self.rev_parse(&refname)?;
+
+        let output = self
+            .git(vec![
+                "for-each-ref",
+                "--format=%(upstream:short)%09%(upstream:remotename)%09%(upstream:track)",
+                &refname,
+            ])
+            .run()?;
+        let stdout = output.stdout.unwrap_or_default();
+        let mut fields = stdout.trim_end_matches('\n').split('\t');
+
+        let upstream = fields.next().unwrap_or_default();
+        if upstream.is_empty() {
+            return Ok(None);
+        }
+        let remote = fields
+            .next()
+            .filter(|x| !x.is_empty() && *x != ".")
+            .map(|x| x.to_string());
+        let (ahead, behind, gone) = parse_track(fields.next().unwrap_or_default());
+
+        Ok(Some(TrackingInfo {
+            upstream: upstream.to_string(),
+            remote,
+            ahead,
+            behind,
+            gone,
+        }))
+    }
+
+    /// Lists local branches whose upstream no longer exists
+    /// 
+    /// These are usually branches merged and deleted on the remote, then pruned by `fetch --prune`
+    pub fn branches_with_gone_upstream(&self) -> Result<Vec<String>, Failure> {
+        let output = self
+            .git(vec![
+                "for-each-ref",
+                "--format=%(refname:short)%09%(upstream:track)",
+                "refs/heads",
+            ])
+            .run()?;
+
+        Ok(output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter(|(_, track)| parse_track(track).2)
+            .map(|(name, _)| name.to_string())
+            .collect())
+    }
+}
+
+/// parses `%(upstream:track)` - `[ahead 1, behind 2]`, `[gone]` or empty
+fn parse_track(track: &str) -> (u32, u32, bool) {
+    let inner = track.trim().trim_start_matches('[').trim_end_matches(']');
+    if inner == "gone" {
+        return (0, 0, true);
+    }
+
+    let (mut ahead, mut behind) = (0, 0);
+    for part in inner.split(", ") {
+        match part.split_once(' ') {
+            Some(("ahead", count)) => ahead = count.parse().unwrap_or(0),
+            Some(("behind", count)) => behind = count.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    (ahead, behind, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{Fixture, TempDir};
+    use crate::CloneOptions;
+
+    #[test]
+    fn test_parse_track() {
+        assert_eq!(parse_track(""), (0, 0, false));
+        assert_eq!(parse_track("[ahead 3]"), (3, 0, false));
+        assert_eq!(parse_track("[behind 2]"), (0, 2, false));
+        assert_eq!(parse_track("[ahead 1, behind 4]"), (1, 4, false));
+        assert_eq!(parse_track("[gone]"), (0, 0, true));
+    }
+
+    #[test]
+    fn test_gone_upstream() {
+        let origin = Fixture::new();
+        origin.commit_all("initial");
+
+        let dir = TempDir::new();
+        let bare_path = dir.path().join("bare.git");
+        origin
+            .repo
+            .git(vec!["clone", "-q", "--bare", ".", &bare_path.to_string_lossy()])
+            .run()
+            .unwrap();
+        let bare = Repo::open(&bare_path).unwrap();
+        let work = Repo::clone_from(
+            &bare_path.to_string_lossy(),
+            dir.path().join("work"),
+            CloneOptions::default(),
+        )
+        .unwrap();
+        for (key, value) in [("user.name", "Test User"), ("user.email", "test@example.com")] {
+            work.git(vec!["config", key, value]).run().unwrap();
+        }
+
+        work.git(vec!["checkout", "-q", "-b", "feature"]).run().unwrap();
+        work.git(vec!["push", "-q", "-u", "origin", "feature"]).run().unwrap();
+        work.git(vec!["commit", "-q", "--allow-empty", "-m", "local"])
+            .run()
+            .unwrap();
+
+        let info = work.tracking_info("feature").unwrap().unwrap();
+        assert_eq!(info.upstream, "origin/feature");
+        assert_eq!(info.remote.as_deref(), Some("origin"));
+        assert_eq!((info.ahead, info.behind, info.gone), (1, 0, false));
+        assert!(work.branches_with_gone_upstream().unwrap().is_empty());
+
+        bare.git(vec!["branch", "-D", "feature"]).run().unwrap();
+        work.git(vec!["fetch", "-q", "--prune"]).run().unwrap();
+
+        let info = work.tracking_info("feature").unwrap().unwrap();
+        assert!(info.gone);
+        assert_eq!(work.branches_with_gone_upstream().unwrap(), vec!["feature"]);
+
+        work.git(vec!["branch", "--unset-upstream"]).run().unwrap();
+        assert_eq!(work.tracking_info("feature").unwrap(), None);
+        assert!(work.tracking_info("missing").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Failure, Repo};
+
+/// a commit message trailer, eg `Signed-off-by: Name <email>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommitTrailer {
+    pub token: String,
+    pub value: String,
+}
+
+impl CommitTrailer {
+    pub fn new<T: ToString, V: ToString>(token: T, value: V) -> CommitTrailer {
+        CommitTrailer {
+            token: token.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Repo {
+    /// Parses the trailers at the end of a commit message, using `interpret-trailers --parse`
+    /// 
+    /// Values folded across several lines are joined into one
+    /// # Examples
+    /// ```rust,no_run
+    /// use rsgit::Repo;
+    /// let repo = Repo::open(".").unwrap();
+    /// let trailers = repo.parse_trailers("Fix\n\nSigned-off-by: A <a@example.com>\n").unwrap();
+    /// assert_eq!(trailers[0].token, "Signed-off-by");
+    /// ```
+    pub fn parse_trailers(&self, message: &str) -> Result<Vec<CommitTrailer>, Failure> {
+        let output = self
+            .git(vec!["interpret-trailers", "--parse"])
+            .stdin(message)
+            .run()?;
+
+        output
+            .stdout
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.split_once(':')
+                    .map(|(token, value)| CommitTrailer::new(token.trim(), value.trim()))
+                    .ok_or_else(|| Failure::parse(format!("unexpected trailer: {:?}", line)))
+            })
+            .collect()
+    }
+
+    /// Returns `message` with `trailer` appended, using `interpret-trailers --trailer`
+    pub fn add_trailer(&self, message: &str, trailer: &CommitTrailer) -> Result<String, Failure> {
+        let output = self
+            .git(vec!["interpret-trailers", "--trailer"])
+            .arg(format!("{}: {}", trailer.token, trailer.value))
+            .stdin(message)
+            .run()?;
+        Ok(output.stdout.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_parse_trailers() {
+        let fixture = Fixture::new();
+        let message = "Subject
+
+Body text
+
+Signed-off-by: Alice <alice@example.com>
+Reviewed-by: Bob
+  who looked very carefully
+Signed-off-by: Carol <carol@example.com>
+";
+
+        let trailers = fixture.repo.parse_trailers(message).unwrap();
+        assert_eq!(
+            trailers,
+            vec![
+                CommitTrailer::new("Signed-off-by", "Alice <alice@example.com>"),
+                CommitTrailer::new("Reviewed-by", "Bob who looked very carefully"),
+                CommitTrailer::new("Signed-off-by", "Carol <carol@example.com>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_no_trailers() {
+        let fixture = Fixture::new();
+        let trailers = fixture
+            .repo
+            .parse_trailers("Subject\n\nJust a body: with a colon\n")
+            .unwrap();
+        assert!(trailers.is_empty());
+    }
+
+    #[test]
+    fn test_add_trailer() {
+        let fixture = Fixture::new();
+        let trailer = CommitTrailer::new("Co-authored-by", "Dave <dave@example.com>");
+
+        let message = fixture.repo.add_trailer("Subject\n", &trailer).unwrap();
+        assert_eq!(message, "Subject\n\nCo-authored-by: Dave <dave@example.com>\n");
+        assert_eq!(fixture.repo.parse_trailers(&message).unwrap(), vec![trailer]);
+    }
+}
@@ -0,0 +1,159 @@
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::{Failure, FailureKind, Repo};
+
+/// the version of the `git` on `PATH`, once found - it can't change while we run
+static INSTALLED: OnceLock<(u32, u32, u32)> = OnceLock::new();
+
+/// Runs `--version` on `binary`, or the `git` on `PATH` if `None`, returning `(major, minor, patch)`
+/// 
+/// Platform suffixes such as `.windows.1` are ignored, and a missing patch number is 0
+/// # Examples
+/// ```rust
+/// use rsgit::git_version;
+/// let (major, minor, _) = git_version(None).unwrap();
+/// println!("git {}.{}", major, minor);
+/// ```
+pub fn git_version(binary: Option<&str>) -> Result<(u32, u32, u32), Failure> {
+    let binary = binary.unwrap_or("git");
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .map_err(|err| Failure::new(FailureKind::Io, format!("failed to run {}: {}", binary, err)))?;
+    if !output.status.success() {
+        return Err(Failure::from_output(output));
+    }
+
+    parse_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// the version of the `git` on `PATH`, only run the first time
+pub(crate) fn installed_version() -> Result<(u32, u32, u32), Failure> {
+    if let Some(version) = INSTALLED.get() {
+        return Ok(*version);
+    }
+    let version = git_version(None)?;
+    Ok(*INSTALLED.get_or_init(|| version))
+}
+
+/// Fails with `FailureKind::UnsupportedVersion` if the installed git is older than `required`
+/// 
+/// The failure's message names both versions, so it can be shown as is
+/// # Examples
+/// ```rust,no_run
+/// use rsgit::require_version;
+/// if let Err(failure) = require_version((2, 25, 0)) {
+///     eprintln!("{}", failure.stderr.unwrap_or_default());
+///     std::process::exit(1);
+/// }
+/// ```
+pub fn require_version(required: (u32, u32, u32)) -> Result<(), Failure> {
+    check_version(installed_version()?, required)
+}
+
+impl Repo {
+    /// The version of the git this repository runs, as `(major, minor, patch)`
+    /// 
+    /// With an executor, this asks it for `--version` every time, so a `MockExecutor` must be given a response.
+    /// Otherwise it is `Git::installed_version`, which runs git only once
+    pub fn git_version(&self) -> Result<(u32, u32, u32), Failure> {
+        if !self.has_executor() {
+            return installed_version();
+        }
+        let output = self.git(vec!["--version"]).run()?;
+        parse_output(&output.stdout.unwrap_or_default())
+    }
+
+    /// Fails with `FailureKind::UnsupportedVersion` if the git this repository runs is older than `required` - see
+    /// `require_version`
+    pub fn require_version(&self, required: (u32, u32, u32)) -> Result<(), Failure> {
+        check_version(self.git_version()?, required)
+    }
+}
+
+fn check_version(found: (u32, u32, u32), required: (u32, u32, u32)) -> Result<(), Failure> {
+    if found < required {
+        return Err(Failure::new(
+            FailureKind::UnsupportedVersion { required, found },
+            format!(
+                "git {}.{}.{} or newer is required, found {}.{}.{}",
+                required.0, required.1, required.2, found.0, found.1, found.2
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_output(stdout: &str) -> Result<(u32, u32, u32), Failure> {
+    parse_version(stdout).ok_or_else(|| Failure::parse(format!("unexpected version: {:?}", stdout)))
+}
+
+/// parses `git version X.Y.Z`, ignoring platform suffixes such as `.windows.1`
+fn parse_version(output: &str) -> Option<(u32, u32, u32)> {
+    let version = output.trim().strip_prefix("git version ")?;
+    let mut parts = version
+        .split(['.', ' ', '-'])
+        .map(|part| part.parse::<u32>());
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(|x| x.ok()).unwrap_or(0);
+    let patch = parts.next().and_then(|x| x.ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("git version 2.39.5\n"), Some((2, 39, 5)));
+        assert_eq!(parse_version("git version 2.42.0.windows.1"), Some((2, 42, 0)));
+        assert_eq!(parse_version("git version 2.30"), Some((2, 30, 0)));
+        assert_eq!(parse_version("something else"), None);
+    }
+
+    #[test]
+    fn test_git_version() {
+        let version = git_version(None).unwrap();
+        assert!(version >= (2, 0, 0), "{:?}", version);
+        assert_eq!(git_version(Some("git")).unwrap(), version);
+
+        let missing = git_version(Some("/nonexistent/git")).unwrap_err();
+        assert_eq!(missing.kind, FailureKind::Io);
+    }
+
+    #[test]
+    fn test_require_version() {
+        assert!(require_version((1, 0, 0)).is_ok());
+
+        let failure = require_version((999, 0, 0)).unwrap_err();
+        assert!(failure.stderr.as_deref().unwrap().starts_with("git 999.0.0 or newer is required, found "));
+        assert!(matches!(
+            failure.kind,
+            FailureKind::UnsupportedVersion { required: (999, 0, 0), .. }
+        ));
+    }
+
+    #[test]
+    fn test_repo_version_uses_executor() {
+        use crate::testing::MockExecutor;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockExecutor::new());
+        mock.on_success(["rev-parse", "--git-dir"], ".git\n")
+            .on_success(["--version"], "git version 2.20.1\n");
+        let repo = Repo::open_with_executor("/not/a/real/repo", mock.clone()).unwrap();
+        assert_eq!(repo.git_version().unwrap(), (2, 20, 1));
+
+        // too old for sparse-checkout, which is never run
+        let failure = repo.sparse_checkout_init(true).unwrap_err();
+        assert!(matches!(failure.kind, FailureKind::UnsupportedVersion { found: (2, 20, 1), .. }));
+        assert!(mock.calls().iter().all(|x| x.args[0] != "sparse-checkout"));
+        assert_eq!(mock.calls().iter().filter(|x| x.args[0] == "--version").count(), 2);
+
+        assert_eq!(installed_version().unwrap(), installed_version().unwrap());
+        assert_eq!(installed_version().unwrap(), git_version(None).unwrap());
+    }
+}